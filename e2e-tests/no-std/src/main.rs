@@ -64,8 +64,6 @@ fn now() -> DateTime<Utc> {
 /// Sample token claims.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct SampleClaims {
-    #[serde(rename = "sub")]
-    subject: String,
     name: String,
     #[serde(default)]
     admin: bool,
@@ -134,7 +132,6 @@ impl TokenChecker {
         hprintln!("Testing algorithm: {}", alg.name());
 
         let claims = SampleClaims {
-            subject: "j.doe@example.com".to_owned(),
             name: "John Doe".to_owned(),
             admin: false,
         };
@@ -158,7 +155,6 @@ impl TokenChecker {
         hprintln!("Testing algorithm: {}", alg.name());
 
         let claims = SampleClaims {
-            subject: "j.doe@example.com".to_owned(),
             name: "John Doe".to_owned(),
             admin: false,
         };
@@ -37,11 +37,9 @@ extern "C" {
     fn json_stringify(value: &JsValue) -> String;
 }
 
-/// Sample token claims.
+/// Sample custom token claims.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SampleClaims {
-    #[serde(rename = "sub")]
-    subject: String,
     name: String,
     #[serde(default)]
     admin: bool,
@@ -62,12 +60,15 @@ fn into_serde<T: DeserializeOwned>(value: &JsValue) -> Result<T, JsonError> {
     serde_json::from_str(&json_string)
 }
 
-fn extract_claims(token: &Token<SampleClaims>) -> Result<&SampleClaims, JsValue> {
-    Ok(&token
+/// Extracts the subject and custom claims from the token.
+fn extract_claims(token: &Token<SampleClaims>) -> Result<Claims<SampleClaims>, JsValue> {
+    let claims = token
         .claims()
         .validate_expiration(&TimeOptions::default())
-        .map_err(to_js_error)?
-        .custom)
+        .map_err(to_js_error)?;
+    let mut extracted = Claims::new(claims.custom.clone());
+    extracted.subject = claims.subject.clone();
+    Ok(extracted)
 }
 
 fn do_verify_token<T, J>(alg: &T, token: &UntrustedToken, jwk: J) -> Result<JsValue, JsValue>
@@ -82,16 +83,16 @@ where
         .validate(token)
         .map_err(to_js_error)?;
     let claims = extract_claims(&token)?;
-    Ok(from_serde(claims).expect("Cannot serialize claims"))
+    Ok(from_serde(&claims).expect("Cannot serialize claims"))
 }
 
-fn do_create_token<T, J>(alg: &T, claims: SampleClaims, jwk: J) -> Result<String, JsValue>
+fn do_create_token<T, J>(alg: &T, claims: Claims<SampleClaims>, jwk: J) -> Result<String, JsValue>
 where
     T: Algorithm,
     T::SigningKey: TryFrom<J, Error = JwkError>,
 {
     let secret_key = <T::SigningKey>::try_from(jwk).map_err(to_js_error)?;
-    let claims = Claims::new(claims).set_duration(&TimeOptions::default(), Duration::hours(1));
+    let claims = claims.set_duration(&TimeOptions::default(), Duration::hours(1));
 
     let token = alg
         .token(&Header::empty(), &claims, &secret_key)
@@ -119,7 +120,7 @@ pub fn create_hash_token(
     alg: &str,
 ) -> Result<String, JsValue> {
     let jwk: JsonWebKey<'_> = into_serde(secret_key).map_err(to_js_error)?;
-    let claims: SampleClaims = into_serde(claims).map_err(to_js_error)?;
+    let claims: Claims<SampleClaims> = into_serde(claims).map_err(to_js_error)?;
     match alg {
         "HS256" => do_create_token(&Hs256, claims, &jwk),
         "HS384" => do_create_token(&Hs384, claims, &jwk),
@@ -143,7 +144,7 @@ pub fn create_rsa_token(
     alg: &str,
 ) -> Result<String, JsValue> {
     let jwk: JsonWebKey<'_> = into_serde(private_key).map_err(to_js_error)?;
-    let claims: SampleClaims = into_serde(claims).map_err(to_js_error)?;
+    let claims: Claims<SampleClaims> = into_serde(claims).map_err(to_js_error)?;
     do_create_token(&Rsa::with_name(alg), claims, &jwk)
 }
 
@@ -157,7 +158,7 @@ pub fn verify_ed_token(token: &str, public_key: &JsValue) -> Result<JsValue, JsV
 #[wasm_bindgen(js_name = "createEdToken")]
 pub fn create_ed_token(claims: &JsValue, private_key: &JsValue) -> Result<String, JsValue> {
     let jwk: JsonWebKey<'_> = into_serde(private_key).map_err(to_js_error)?;
-    let claims: SampleClaims = into_serde(claims).map_err(to_js_error)?;
+    let claims: Claims<SampleClaims> = into_serde(claims).map_err(to_js_error)?;
     do_create_token(&Ed25519, claims, &jwk)
 }
 
@@ -171,7 +172,7 @@ pub fn verify_es256k_token(token: &str, public_key: &JsValue) -> Result<JsValue,
 #[wasm_bindgen(js_name = "createEs256kToken")]
 pub fn create_es256k_token(claims: &JsValue, private_key: &JsValue) -> Result<String, JsValue> {
     let jwk: JsonWebKey<'_> = into_serde(private_key).map_err(to_js_error)?;
-    let claims: SampleClaims = into_serde(claims).map_err(to_js_error)?;
+    let claims: Claims<SampleClaims> = into_serde(claims).map_err(to_js_error)?;
     do_create_token(&<Es256k>::default(), claims, &jwk)
 }
 
@@ -185,6 +186,6 @@ pub fn verify_es256_token(token: &str, public_key: &JsValue) -> Result<JsValue,
 #[wasm_bindgen(js_name = "createEs256Token")]
 pub fn create_es256_token(claims: &JsValue, private_key: &JsValue) -> Result<String, JsValue> {
     let jwk: JsonWebKey<'_> = into_serde(private_key).map_err(to_js_error)?;
-    let claims: SampleClaims = into_serde(claims).map_err(to_js_error)?;
+    let claims: Claims<SampleClaims> = into_serde(claims).map_err(to_js_error)?;
     do_create_token(&Es256, claims, &jwk)
 }
@@ -3,10 +3,14 @@
 
 use core::fmt;
 
-use crate::{alloc::Cow, Algorithm};
+use crate::{alloc::Cow, Algorithm, CreationError};
 
+mod any;
+#[cfg(feature = "pem")]
+pub(crate) mod encoding;
 mod generic;
 mod hmacs;
+mod keyed;
 // Alternative ES256K implementations.
 #[cfg(feature = "secp256k1")]
 mod es256k;
@@ -22,24 +26,38 @@ mod eddsa_sodium;
 // ES256 implemenation.
 #[cfg(feature = "p256")]
 mod p256;
+// ES384 implemenation.
+#[cfg(feature = "p384")]
+mod p384;
+// ES512 implemenation.
+#[cfg(feature = "p521")]
+mod p521;
 // RSA implementation.
 #[cfg(feature = "rsa")]
 mod rsa;
 
+pub use self::any::{AnyAlgorithm, AnySignature, AnySigningKey, AnyVerifyingKey};
 #[cfg(feature = "ed25519-compact")]
 pub use self::eddsa_compact::*;
 #[cfg(feature = "ed25519-dalek")]
 pub use self::eddsa_dalek::Ed25519;
 #[cfg(feature = "exonum-crypto")]
 pub use self::eddsa_sodium::Ed25519;
+#[cfg(feature = "pem")]
+pub use self::encoding::{KeyEncodingError, KeyFormat, PrivateKeyEncoding, PublicKeyEncoding};
 #[cfg(feature = "es256k")]
 pub use self::es256k::Es256k;
 pub use self::generic::{SecretBytes, SigningKey, VerifyingKey};
 pub use self::hmacs::*;
 #[cfg(feature = "k256")]
 pub use self::k256::Es256k;
+pub use self::keyed::{KeyedAlg, KeyedSigningKey};
 #[cfg(feature = "p256")]
 pub use self::p256::Es256;
+#[cfg(feature = "p384")]
+pub use self::p384::Es384;
+#[cfg(feature = "p521")]
+pub use self::p521::Es512;
 #[cfg(feature = "rsa")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rsa")))]
 pub use self::rsa::{
@@ -136,6 +154,14 @@ where
         self.0.name()
     }
 
+    fn key_id<'k>(&self, signing_key: &'k Self::SigningKey) -> Option<&'k str> {
+        self.0.key_id(&signing_key.0)
+    }
+
+    fn check_signing_key(&self, signing_key: &Self::SigningKey) -> Result<(), CreationError> {
+        self.0.check_signing_key(&signing_key.0)
+    }
+
     fn sign(&self, signing_key: &Self::SigningKey, message: &[u8]) -> Self::Signature {
         self.0.sign(&signing_key.0, message)
     }
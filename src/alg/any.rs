@@ -0,0 +1,782 @@
+//! Type-erased algorithm and keys allowing to work with algorithms chosen at runtime.
+
+use core::fmt;
+
+#[cfg(any(
+    feature = "exonum-crypto",
+    feature = "ed25519-dalek",
+    feature = "ed25519-compact"
+))]
+use super::Ed25519;
+#[cfg(feature = "p256")]
+use super::Es256;
+#[cfg(any(feature = "es256k", feature = "k256"))]
+use super::Es256k;
+#[cfg(feature = "p384")]
+use super::Es384;
+#[cfg(feature = "p521")]
+use super::Es512;
+use super::{Hs256, Hs256Key, Hs384, Hs384Key, Hs512, Hs512Key, SecretBytes};
+#[cfg(feature = "rsa")]
+use super::{Rsa, RsaPrivateKey, RsaPublicKey};
+use crate::{
+    alloc::{format, Cow, ToOwned, ToString, Vec},
+    jwk::{JsonWebKey, JwkError},
+    Algorithm, AlgorithmSignature, CreationError, UntrustedToken, ValidationError,
+};
+
+#[cfg(any(
+    feature = "exonum-crypto",
+    feature = "ed25519-dalek",
+    feature = "ed25519-compact"
+))]
+type Ed25519VerifyingKey = <Ed25519 as Algorithm>::VerifyingKey;
+#[cfg(any(
+    feature = "exonum-crypto",
+    feature = "ed25519-dalek",
+    feature = "ed25519-compact"
+))]
+type Ed25519SigningKey = <Ed25519 as Algorithm>::SigningKey;
+
+/// Kind of a type-erased key. Algorithms are compatible with keys of a single kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyKind {
+    Symmetric,
+    #[cfg(feature = "rsa")]
+    Rsa,
+    #[cfg(feature = "p256")]
+    P256,
+    #[cfg(feature = "p384")]
+    P384,
+    #[cfg(feature = "p521")]
+    P521,
+    #[cfg(any(feature = "es256k", feature = "k256"))]
+    Secp256k1,
+    #[cfg(any(
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact"
+    ))]
+    Ed25519,
+}
+
+/// Returns an error for an unsupported value of a JWK field.
+fn unsupported_value(field: &str, supported: &[&str], actual: &str) -> JwkError {
+    JwkError::UnexpectedValue {
+        field: field.to_owned(),
+        expected: format!("one of: {}", supported.join(", ")),
+        actual: actual.to_owned(),
+    }
+}
+
+/// Elliptic curves supported by [`AnyVerifyingKey`] and [`AnySigningKey`] for `EC` keys.
+const SUPPORTED_EC_CURVES: &[&str] = &[
+    #[cfg(feature = "p256")]
+    "P-256",
+    #[cfg(feature = "p384")]
+    "P-384",
+    #[cfg(feature = "p521")]
+    "P-521",
+    #[cfg(any(feature = "es256k", feature = "k256"))]
+    "secp256k1",
+];
+
+/// Elliptic curves supported by [`AnyVerifyingKey`] and [`AnySigningKey`] for `OKP` keys.
+const SUPPORTED_OKP_CURVES: &[&str] = &[
+    #[cfg(any(
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact"
+    ))]
+    "Ed25519",
+];
+
+/// Type-erased verifying key for one of the algorithms supported by [`AnyAlgorithm`].
+///
+/// The key is usually created from a [`JsonWebKey`] using the [`TryFrom`] trait;
+/// the key kind is determined from the key type (`kty`) and, for elliptic curve keys,
+/// the curve name (`crv`).
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum AnyVerifyingKey {
+    /// Symmetric key for `HS*` algorithms.
+    Symmetric(SecretBytes<'static>),
+    /// Public RSA key for `RS*` and `PS*` algorithms.
+    #[cfg(feature = "rsa")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rsa")))]
+    Rsa(RsaPublicKey),
+    /// Public key on the P-256 curve for the `ES256` algorithm.
+    #[cfg(feature = "p256")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p256")))]
+    P256(<Es256 as Algorithm>::VerifyingKey),
+    /// Public key on the P-384 curve for the `ES384` algorithm.
+    #[cfg(feature = "p384")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p384")))]
+    P384(<Es384 as Algorithm>::VerifyingKey),
+    /// Public key on the P-521 curve for the `ES512` algorithm.
+    #[cfg(feature = "p521")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p521")))]
+    P521(<Es512 as Algorithm>::VerifyingKey),
+    /// Public key on the secp256k1 curve for the `ES256K` algorithm.
+    #[cfg(any(feature = "es256k", feature = "k256"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "es256k", feature = "k256"))))]
+    Secp256k1(<Es256k as Algorithm>::VerifyingKey),
+    /// Ed25519 public key for the `EdDSA` algorithm.
+    #[cfg(any(
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact"
+    ))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "exonum-crypto",
+            feature = "ed25519-dalek",
+            feature = "ed25519-compact"
+        )))
+    )]
+    Ed25519(Ed25519VerifyingKey),
+}
+
+impl fmt::Debug for AnyVerifyingKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_tuple("AnyVerifyingKey")
+            .field(&self.kind())
+            .finish()
+    }
+}
+
+impl AnyVerifyingKey {
+    fn kind(&self) -> KeyKind {
+        match self {
+            Self::Symmetric(_) => KeyKind::Symmetric,
+            #[cfg(feature = "rsa")]
+            Self::Rsa(_) => KeyKind::Rsa,
+            #[cfg(feature = "p256")]
+            Self::P256(_) => KeyKind::P256,
+            #[cfg(feature = "p384")]
+            Self::P384(_) => KeyKind::P384,
+            #[cfg(feature = "p521")]
+            Self::P521(_) => KeyKind::P521,
+            #[cfg(any(feature = "es256k", feature = "k256"))]
+            Self::Secp256k1(_) => KeyKind::Secp256k1,
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            Self::Ed25519(_) => KeyKind::Ed25519,
+        }
+    }
+}
+
+impl TryFrom<&JsonWebKey<'_>> for AnyVerifyingKey {
+    type Error = JwkError;
+
+    fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+        Ok(match jwk {
+            JsonWebKey::Symmetric { secret } => {
+                Self::Symmetric(SecretBytes::owned(secret.to_vec()))
+            }
+
+            #[cfg(feature = "rsa")]
+            JsonWebKey::Rsa { .. } => Self::Rsa(RsaPublicKey::try_from(jwk)?),
+
+            JsonWebKey::EllipticCurve { curve, .. } => match curve.as_ref() {
+                #[cfg(feature = "p256")]
+                "P-256" => Self::P256(jwk.try_into()?),
+                #[cfg(feature = "p384")]
+                "P-384" => Self::P384(jwk.try_into()?),
+                #[cfg(feature = "p521")]
+                "P-521" => Self::P521(jwk.try_into()?),
+                #[cfg(any(feature = "es256k", feature = "k256"))]
+                "secp256k1" => Self::Secp256k1(jwk.try_into()?),
+                _ => return Err(unsupported_value("crv", SUPPORTED_EC_CURVES, curve)),
+            },
+
+            JsonWebKey::KeyPair { curve, .. } => match curve.as_ref() {
+                #[cfg(any(
+                    feature = "exonum-crypto",
+                    feature = "ed25519-dalek",
+                    feature = "ed25519-compact"
+                ))]
+                "Ed25519" => Self::Ed25519(jwk.try_into()?),
+                _ => return Err(unsupported_value("crv", SUPPORTED_OKP_CURVES, curve)),
+            },
+
+            #[allow(unreachable_patterns)] // reachable if the `rsa` feature is off
+            _ => return Err(unsupported_key_type(jwk)),
+        })
+    }
+}
+
+fn unsupported_key_type(jwk: &JsonWebKey<'_>) -> JwkError {
+    const SUPPORTED_KEY_TYPES: &[&str] = &[
+        "oct",
+        #[cfg(feature = "rsa")]
+        "RSA",
+        "EC",
+        "OKP",
+    ];
+    unsupported_value("kty", SUPPORTED_KEY_TYPES, &jwk.key_type().to_string())
+}
+
+/// Type-erased signing key for one of the algorithms supported by [`AnyAlgorithm`].
+///
+/// Similar to [`AnyVerifyingKey`], the key is usually created from a [`JsonWebKey`]
+/// using the [`TryFrom`] trait.
+#[derive(Clone)]
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)] // keys are usually long-lived and are not moved around
+pub enum AnySigningKey {
+    /// Symmetric key for `HS*` algorithms.
+    Symmetric(SecretBytes<'static>),
+    /// Private RSA key for `RS*` and `PS*` algorithms.
+    #[cfg(feature = "rsa")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rsa")))]
+    Rsa(RsaPrivateKey),
+    /// Private key on the P-256 curve for the `ES256` algorithm.
+    #[cfg(feature = "p256")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p256")))]
+    P256(<Es256 as Algorithm>::SigningKey),
+    /// Private key on the P-384 curve for the `ES384` algorithm.
+    #[cfg(feature = "p384")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p384")))]
+    P384(<Es384 as Algorithm>::SigningKey),
+    /// Private key on the P-521 curve for the `ES512` algorithm.
+    #[cfg(feature = "p521")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p521")))]
+    P521(<Es512 as Algorithm>::SigningKey),
+    /// Private key on the secp256k1 curve for the `ES256K` algorithm.
+    #[cfg(any(feature = "es256k", feature = "k256"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "es256k", feature = "k256"))))]
+    Secp256k1(<Es256k as Algorithm>::SigningKey),
+    /// Ed25519 private key for the `EdDSA` algorithm.
+    #[cfg(any(
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact"
+    ))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "exonum-crypto",
+            feature = "ed25519-dalek",
+            feature = "ed25519-compact"
+        )))
+    )]
+    Ed25519(Ed25519SigningKey),
+}
+
+impl fmt::Debug for AnySigningKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_tuple("AnySigningKey")
+            .field(&self.kind())
+            .finish()
+    }
+}
+
+impl AnySigningKey {
+    fn kind(&self) -> KeyKind {
+        match self {
+            Self::Symmetric(_) => KeyKind::Symmetric,
+            #[cfg(feature = "rsa")]
+            Self::Rsa(_) => KeyKind::Rsa,
+            #[cfg(feature = "p256")]
+            Self::P256(_) => KeyKind::P256,
+            #[cfg(feature = "p384")]
+            Self::P384(_) => KeyKind::P384,
+            #[cfg(feature = "p521")]
+            Self::P521(_) => KeyKind::P521,
+            #[cfg(any(feature = "es256k", feature = "k256"))]
+            Self::Secp256k1(_) => KeyKind::Secp256k1,
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            Self::Ed25519(_) => KeyKind::Ed25519,
+        }
+    }
+
+    /// Returns the verifying key corresponding to this signing key.
+    pub fn to_verifying_key(&self) -> AnyVerifyingKey {
+        #[allow(unused_imports)] // unused if only `HS*` algorithms are enabled
+        use super::SigningKey as _;
+
+        match self {
+            Self::Symmetric(secret) => AnyVerifyingKey::Symmetric(secret.clone()),
+            #[cfg(feature = "rsa")]
+            Self::Rsa(key) => AnyVerifyingKey::Rsa(key.to_public_key()),
+            #[cfg(feature = "p256")]
+            Self::P256(key) => AnyVerifyingKey::P256(key.to_verifying_key()),
+            #[cfg(feature = "p384")]
+            Self::P384(key) => AnyVerifyingKey::P384(key.to_verifying_key()),
+            #[cfg(feature = "p521")]
+            Self::P521(key) => AnyVerifyingKey::P521(key.to_verifying_key()),
+            #[cfg(any(feature = "es256k", feature = "k256"))]
+            Self::Secp256k1(key) => AnyVerifyingKey::Secp256k1(key.to_verifying_key()),
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            Self::Ed25519(key) => AnyVerifyingKey::Ed25519(key.to_verifying_key()),
+        }
+    }
+}
+
+impl TryFrom<&JsonWebKey<'_>> for AnySigningKey {
+    type Error = JwkError;
+
+    fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+        Ok(match jwk {
+            JsonWebKey::Symmetric { secret } => {
+                Self::Symmetric(SecretBytes::owned(secret.to_vec()))
+            }
+
+            #[cfg(feature = "rsa")]
+            JsonWebKey::Rsa { .. } => Self::Rsa(RsaPrivateKey::try_from(jwk)?),
+
+            JsonWebKey::EllipticCurve { curve, .. } => match curve.as_ref() {
+                #[cfg(feature = "p256")]
+                "P-256" => Self::P256(jwk.try_into()?),
+                #[cfg(feature = "p384")]
+                "P-384" => Self::P384(jwk.try_into()?),
+                #[cfg(feature = "p521")]
+                "P-521" => Self::P521(jwk.try_into()?),
+                #[cfg(any(feature = "es256k", feature = "k256"))]
+                "secp256k1" => Self::Secp256k1(jwk.try_into()?),
+                _ => return Err(unsupported_value("crv", SUPPORTED_EC_CURVES, curve)),
+            },
+
+            JsonWebKey::KeyPair { curve, .. } => match curve.as_ref() {
+                #[cfg(any(
+                    feature = "exonum-crypto",
+                    feature = "ed25519-dalek",
+                    feature = "ed25519-compact"
+                ))]
+                "Ed25519" => Self::Ed25519(jwk.try_into()?),
+                _ => return Err(unsupported_value("crv", SUPPORTED_OKP_CURVES, curve)),
+            },
+
+            #[allow(unreachable_patterns)] // reachable if the `rsa` feature is off
+            _ => return Err(unsupported_key_type(jwk)),
+        })
+    }
+}
+
+/// Signature produced by [`AnyAlgorithm`]. The signature length is not fixed;
+/// it is checked by the concrete algorithm during verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnySignature(Vec<u8>);
+
+impl AlgorithmSignature for AnySignature {
+    fn try_from_slice(slice: &[u8]) -> anyhow::Result<Self> {
+        Ok(Self(slice.to_vec()))
+    }
+
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.0)
+    }
+}
+
+fn sign_with<A: Algorithm>(algorithm: &A, key: &A::SigningKey, message: &[u8]) -> AnySignature {
+    AnySignature(algorithm.sign(key, message).as_bytes().into_owned())
+}
+
+fn verify_with<A: Algorithm>(
+    algorithm: &A,
+    signature: &AnySignature,
+    key: &A::VerifyingKey,
+    message: &[u8],
+) -> bool {
+    if let Some(expected_len) = A::Signature::LENGTH {
+        if signature.0.len() != expected_len.get() {
+            return false;
+        }
+    }
+    A::Signature::try_from_slice(&signature.0).map_or(false, |signature| {
+        algorithm.verify_signature(&signature, key, message)
+    })
+}
+
+/// Type-erased JWT algorithm choosing one of algorithms supported by the crate at runtime.
+///
+/// Each algorithm is compatible with a single kind of [`AnyVerifyingKey`]s and [`AnySigningKey`]s
+/// (e.g., `HS*` algorithms are only compatible with symmetric keys, and `RS*` / `PS*` algorithms
+/// only with RSA keys). Algorithms should be [selected](Self::select()) from an explicit allow-list
+/// based on the token header, which makes algorithm confusion attacks (such as using
+/// an RSA public key as an `HS256` secret) impossible.
+///
+/// # Examples
+///
+/// ```
+/// use jwt_compact::{alg::{AnyAlgorithm, AnyVerifyingKey}, jwk::JsonWebKey, prelude::*};
+/// # use jwt_compact::{alg::{Hs256, Hs256Key}, Empty};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// # let key = Hs256Key::new(b"super_secret_key_donut_steel");
+/// # let token = Hs256.token(&Header::empty(), &Claims::empty(), &key)?;
+/// let jwk: JsonWebKey<'_> = // ...
+/// #   JsonWebKey::from(&key);
+/// let key = AnyVerifyingKey::try_from(&jwk)?;
+/// let allowed_algorithms = [AnyAlgorithm::Hs256, AnyAlgorithm::Hs512];
+///
+/// let token = UntrustedToken::new(&token)?;
+/// let algorithm = AnyAlgorithm::select(&allowed_algorithms, &token, &key)?;
+/// let token = algorithm.validator::<Empty>(&key).validate(&token)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AnyAlgorithm {
+    /// `HS256` algorithm.
+    Hs256,
+    /// `HS384` algorithm.
+    Hs384,
+    /// `HS512` algorithm.
+    Hs512,
+    /// One of `RS*` and `PS*` algorithms.
+    #[cfg(feature = "rsa")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rsa")))]
+    Rsa(Rsa),
+    /// `ES256` algorithm.
+    #[cfg(feature = "p256")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p256")))]
+    Es256,
+    /// `ES384` algorithm.
+    #[cfg(feature = "p384")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p384")))]
+    Es384,
+    /// `ES512` algorithm.
+    #[cfg(feature = "p521")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p521")))]
+    Es512,
+    /// `ES256K` algorithm.
+    #[cfg(any(feature = "es256k", feature = "k256"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "es256k", feature = "k256"))))]
+    Es256k(Es256k),
+    /// `EdDSA` algorithm on the Ed25519 curve.
+    #[cfg(any(
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact"
+    ))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(
+            feature = "exonum-crypto",
+            feature = "ed25519-dalek",
+            feature = "ed25519-compact"
+        )))
+    )]
+    EdDsa,
+}
+
+impl AnyAlgorithm {
+    /// Creates an algorithm based on its name as specified in the `alg` field of the JWT header.
+    /// Returns `None` if the algorithm is unknown, or is not enabled via crate features.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "HS256" => Self::Hs256,
+            "HS384" => Self::Hs384,
+            "HS512" => Self::Hs512,
+            #[cfg(feature = "rsa")]
+            "RS256" | "RS384" | "RS512" | "PS256" | "PS384" | "PS512" => {
+                Self::Rsa(name.parse().ok()?)
+            }
+            #[cfg(feature = "p256")]
+            "ES256" => Self::Es256,
+            #[cfg(feature = "p384")]
+            "ES384" => Self::Es384,
+            #[cfg(feature = "p521")]
+            "ES512" => Self::Es512,
+            #[cfg(any(feature = "es256k", feature = "k256"))]
+            "ES256K" => Self::Es256k(Es256k::default()),
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            "EdDSA" => Self::EdDsa,
+            _ => return None,
+        })
+    }
+
+    fn key_kind(&self) -> KeyKind {
+        match self {
+            Self::Hs256 | Self::Hs384 | Self::Hs512 => KeyKind::Symmetric,
+            #[cfg(feature = "rsa")]
+            Self::Rsa(_) => KeyKind::Rsa,
+            #[cfg(feature = "p256")]
+            Self::Es256 => KeyKind::P256,
+            #[cfg(feature = "p384")]
+            Self::Es384 => KeyKind::P384,
+            #[cfg(feature = "p521")]
+            Self::Es512 => KeyKind::P521,
+            #[cfg(any(feature = "es256k", feature = "k256"))]
+            Self::Es256k(_) => KeyKind::Secp256k1,
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            Self::EdDsa => KeyKind::Ed25519,
+        }
+    }
+
+    /// Checks whether this algorithm is compatible with the specified verifying key.
+    pub fn is_compatible_with(&self, verifying_key: &AnyVerifyingKey) -> bool {
+        self.key_kind() == verifying_key.kind()
+    }
+
+    /// Checks whether this algorithm is compatible with the specified signing key.
+    pub fn is_compatible_with_signing_key(&self, signing_key: &AnySigningKey) -> bool {
+        self.key_kind() == signing_key.kind()
+    }
+
+    /// Selects an algorithm to validate the `token` with the `verifying_key` from the `allowed`
+    /// list. The algorithm is selected based on the `alg` field of the token header.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`ValidationError::AlgorithmNotAllowed`] if the token algorithm is not
+    ///   in the `allowed` list.
+    /// - Returns [`ValidationError::IncompatibleKey`] if the token algorithm is allowed,
+    ///   but is not compatible with the verifying key.
+    pub fn select<'a, H>(
+        allowed: &'a [Self],
+        token: &UntrustedToken<'_, H>,
+        verifying_key: &AnyVerifyingKey,
+    ) -> Result<&'a Self, ValidationError> {
+        let token_algorithm = token.algorithm();
+        let algorithm = allowed
+            .iter()
+            .find(|alg| alg.name() == token_algorithm)
+            .ok_or_else(|| ValidationError::AlgorithmNotAllowed(token_algorithm.to_owned()))?;
+        if algorithm.is_compatible_with(verifying_key) {
+            Ok(algorithm)
+        } else {
+            Err(ValidationError::IncompatibleKey {
+                algorithm: token_algorithm.to_owned(),
+            })
+        }
+    }
+}
+
+impl Algorithm for AnyAlgorithm {
+    type SigningKey = AnySigningKey;
+    type VerifyingKey = AnyVerifyingKey;
+    type Signature = AnySignature;
+
+    fn name(&self) -> Cow<'static, str> {
+        match self {
+            Self::Hs256 => Hs256.name(),
+            Self::Hs384 => Hs384.name(),
+            Self::Hs512 => Hs512.name(),
+            #[cfg(feature = "rsa")]
+            Self::Rsa(alg) => alg.name(),
+            #[cfg(feature = "p256")]
+            Self::Es256 => Es256.name(),
+            #[cfg(feature = "p384")]
+            Self::Es384 => Es384.name(),
+            #[cfg(feature = "p521")]
+            Self::Es512 => Es512.name(),
+            #[cfg(any(feature = "es256k", feature = "k256"))]
+            Self::Es256k(alg) => alg.name(),
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            Self::EdDsa => Ed25519.name(),
+        }
+    }
+
+    fn check_signing_key(&self, signing_key: &Self::SigningKey) -> Result<(), CreationError> {
+        if self.is_compatible_with_signing_key(signing_key) {
+            Ok(())
+        } else {
+            Err(CreationError::IncompatibleKey {
+                algorithm: self.name().into_owned(),
+            })
+        }
+    }
+
+    /// Signs the `message` using the concrete algorithm.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signing key is [incompatible](Self::is_compatible_with_signing_key())
+    /// with the algorithm. Token creation methods check compatibility beforehand
+    /// and return [`CreationError::IncompatibleKey`] instead.
+    fn sign(&self, signing_key: &Self::SigningKey, message: &[u8]) -> Self::Signature {
+        match (self, signing_key) {
+            (Self::Hs256, AnySigningKey::Symmetric(secret)) => {
+                sign_with(&Hs256, &Hs256Key::new(secret), message)
+            }
+            (Self::Hs384, AnySigningKey::Symmetric(secret)) => {
+                sign_with(&Hs384, &Hs384Key::new(secret), message)
+            }
+            (Self::Hs512, AnySigningKey::Symmetric(secret)) => {
+                sign_with(&Hs512, &Hs512Key::new(secret), message)
+            }
+            #[cfg(feature = "rsa")]
+            (Self::Rsa(alg), AnySigningKey::Rsa(key)) => sign_with(alg, key, message),
+            #[cfg(feature = "p256")]
+            (Self::Es256, AnySigningKey::P256(key)) => sign_with(&Es256, key, message),
+            #[cfg(feature = "p384")]
+            (Self::Es384, AnySigningKey::P384(key)) => sign_with(&Es384, key, message),
+            #[cfg(feature = "p521")]
+            (Self::Es512, AnySigningKey::P521(key)) => sign_with(&Es512, key, message),
+            #[cfg(any(feature = "es256k", feature = "k256"))]
+            (Self::Es256k(alg), AnySigningKey::Secp256k1(key)) => sign_with(alg, key, message),
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            (Self::EdDsa, AnySigningKey::Ed25519(key)) => sign_with(&Ed25519, key, message),
+            #[allow(unreachable_patterns)] // reachable if any non-`HS*` algorithm is enabled
+            _ => panic!(
+                "signing key {signing_key:?} is incompatible with the {} algorithm",
+                self.name()
+            ),
+        }
+    }
+
+    /// Verifies the `signature` using the concrete algorithm. Returns `false` if
+    /// the verifying key is [incompatible](Self::is_compatible_with()) with the algorithm.
+    fn verify_signature(
+        &self,
+        signature: &Self::Signature,
+        verifying_key: &Self::VerifyingKey,
+        message: &[u8],
+    ) -> bool {
+        match (self, verifying_key) {
+            (Self::Hs256, AnyVerifyingKey::Symmetric(secret)) => {
+                verify_with(&Hs256, signature, &Hs256Key::new(secret), message)
+            }
+            (Self::Hs384, AnyVerifyingKey::Symmetric(secret)) => {
+                verify_with(&Hs384, signature, &Hs384Key::new(secret), message)
+            }
+            (Self::Hs512, AnyVerifyingKey::Symmetric(secret)) => {
+                verify_with(&Hs512, signature, &Hs512Key::new(secret), message)
+            }
+            #[cfg(feature = "rsa")]
+            (Self::Rsa(alg), AnyVerifyingKey::Rsa(key)) => {
+                verify_with(alg, signature, key, message)
+            }
+            #[cfg(feature = "p256")]
+            (Self::Es256, AnyVerifyingKey::P256(key)) => {
+                verify_with(&Es256, signature, key, message)
+            }
+            #[cfg(feature = "p384")]
+            (Self::Es384, AnyVerifyingKey::P384(key)) => {
+                verify_with(&Es384, signature, key, message)
+            }
+            #[cfg(feature = "p521")]
+            (Self::Es512, AnyVerifyingKey::P521(key)) => {
+                verify_with(&Es512, signature, key, message)
+            }
+            #[cfg(any(feature = "es256k", feature = "k256"))]
+            (Self::Es256k(alg), AnyVerifyingKey::Secp256k1(key)) => {
+                verify_with(alg, signature, key, message)
+            }
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            (Self::EdDsa, AnyVerifyingKey::Ed25519(key)) => {
+                verify_with(&Ed25519, signature, key, message)
+            }
+            #[allow(unreachable_patterns)] // reachable if any non-`HS*` algorithm is enabled
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AlgorithmExt, Claims, Empty, Header};
+
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn symmetric_keys() {
+        let jwk = JsonWebKey::Symmetric {
+            secret: SecretBytes::borrowed(b"super_secret_key_donut_steel"),
+        };
+        let signing_key = AnySigningKey::try_from(&jwk).unwrap();
+        let verifying_key = AnyVerifyingKey::try_from(&jwk).unwrap();
+        assert_matches!(&verifying_key, AnyVerifyingKey::Symmetric(_));
+        assert_eq!(format!("{verifying_key:?}"), "AnyVerifyingKey(Symmetric)");
+
+        let allowed = [AnyAlgorithm::Hs256, AnyAlgorithm::Hs384];
+        for algorithm in &allowed {
+            let token = algorithm
+                .token(&Header::empty(), &Claims::empty(), &signing_key)
+                .unwrap();
+            let token = UntrustedToken::new(&token).unwrap();
+            let selected = AnyAlgorithm::select(&allowed, &token, &verifying_key).unwrap();
+            assert_eq!(selected.name(), algorithm.name());
+            selected
+                .validator::<Empty>(&verifying_key)
+                .validate(&token)
+                .unwrap();
+        }
+
+        let token = AnyAlgorithm::Hs512
+            .token(&Header::empty(), &Claims::empty(), &signing_key)
+            .unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+        let err = AnyAlgorithm::select(&allowed, &token, &verifying_key).unwrap_err();
+        assert_matches!(err, ValidationError::AlgorithmNotAllowed(alg) if alg == "HS512");
+    }
+
+    #[cfg(feature = "p256")]
+    #[test]
+    fn incompatible_signing_key() {
+        let signing_key = AnySigningKey::Symmetric(SecretBytes::borrowed(b"test"));
+        assert!(!AnyAlgorithm::Es256.is_compatible_with_signing_key(&signing_key));
+        let err = AnyAlgorithm::Es256
+            .token(&Header::empty(), &Claims::empty(), &signing_key)
+            .unwrap_err();
+        assert_matches!(err, CreationError::IncompatibleKey { algorithm } if algorithm == "ES256");
+
+        let p256_key = <Es256 as Algorithm>::SigningKey::from_slice(&[1; 32]).unwrap();
+        let signing_key = AnySigningKey::P256(p256_key);
+        let err = AnyAlgorithm::Hs256
+            .token(&Header::empty(), &Claims::empty(), &signing_key)
+            .unwrap_err();
+        assert_matches!(err, CreationError::IncompatibleKey { algorithm } if algorithm == "HS256");
+    }
+
+    #[test]
+    fn unsupported_curve() {
+        let jwk = JsonWebKey::EllipticCurve {
+            curve: "P-192".into(),
+            x: Cow::Borrowed(&[1; 24]),
+            y: Cow::Borrowed(&[2; 24]),
+            secret: None,
+        };
+        let err = AnyVerifyingKey::try_from(&jwk).unwrap_err();
+        assert_matches!(
+            err,
+            JwkError::UnexpectedValue { field, actual, .. } if field == "crv" && actual == "P-192"
+        );
+    }
+
+    #[test]
+    fn signature_len_is_checked() {
+        let key = AnyVerifyingKey::Symmetric(SecretBytes::borrowed(b"test"));
+        let signature = AnySignature([0; 31].to_vec());
+        assert!(!AnyAlgorithm::Hs256.verify_signature(&signature, &key, b"test"));
+    }
+}
@@ -5,6 +5,11 @@ use rand_core::{CryptoRng, RngCore};
 
 use core::num::NonZeroUsize;
 
+#[cfg(feature = "pem")]
+use crate::{
+    alg::{encoding, KeyEncodingError, KeyFormat, PrivateKeyEncoding, PublicKeyEncoding},
+    alloc::Vec,
+};
 use crate::{
     alg::{SecretBytes, SigningKey, VerifyingKey},
     alloc::Cow,
@@ -148,3 +153,33 @@ impl TryFrom<&JsonWebKey<'_>> for SecretKey {
         jwk.ensure_key_match(secret_key)
     }
 }
+
+#[cfg(feature = "pem")]
+impl PublicKeyEncoding for PublicKey {
+    const KEY_TYPE: &'static str = "Ed25519 public key";
+
+    fn from_public_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ed25519::decode_public_key(format, der, |bytes| {
+            Self::from_slice(bytes).map_err(|err| anyhow::anyhow!(err))
+        })
+    }
+
+    fn to_public_der(&self, format: KeyFormat) -> Result<Vec<u8>, KeyEncodingError> {
+        encoding::ed25519::encode_public_key::<Self>(&self[..], format)
+    }
+}
+
+#[cfg(feature = "pem")]
+impl PrivateKeyEncoding for SecretKey {
+    const KEY_TYPE: &'static str = "Ed25519 private key";
+
+    fn from_private_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ed25519::decode_private_key(format, der, |seed| {
+            Ok(KeyPair::from_seed(Seed::new(*seed)).sk)
+        })
+    }
+
+    fn to_private_der(&self, format: KeyFormat) -> Result<SecretBytes<'static>, KeyEncodingError> {
+        encoding::ed25519::encode_private_key::<Self>(&self[..Seed::BYTES], format)
+    }
+}
@@ -8,6 +8,11 @@ use ed25519_dalek::{
 
 use core::num::NonZeroUsize;
 
+#[cfg(feature = "pem")]
+use crate::{
+    alg::{encoding, KeyEncodingError, KeyFormat, PrivateKeyEncoding, PublicKeyEncoding},
+    alloc::Vec,
+};
 use crate::{
     alg::{SecretBytes, SigningKey, VerifyingKey},
     alloc::Cow,
@@ -151,3 +156,32 @@ impl TryFrom<&JsonWebKey<'_>> for ed25519_dalek::SigningKey {
         jwk.ensure_key_match(signing_key)
     }
 }
+
+#[cfg(feature = "pem")]
+impl PublicKeyEncoding for ed25519_dalek::VerifyingKey {
+    const KEY_TYPE: &'static str = "Ed25519 public key";
+
+    fn from_public_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ed25519::decode_public_key(format, der, |bytes| {
+            <Self as VerifyingKey<_>>::from_slice(bytes)
+        })
+    }
+
+    fn to_public_der(&self, format: KeyFormat) -> Result<Vec<u8>, KeyEncodingError> {
+        encoding::ed25519::encode_public_key::<Self>(self.as_ref(), format)
+    }
+}
+
+#[cfg(feature = "pem")]
+impl PrivateKeyEncoding for ed25519_dalek::SigningKey {
+    const KEY_TYPE: &'static str = "Ed25519 private key";
+
+    fn from_private_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ed25519::decode_private_key(format, der, |seed| Ok(Self::from_bytes(seed)))
+    }
+
+    fn to_private_der(&self, format: KeyFormat) -> Result<SecretBytes<'static>, KeyEncodingError> {
+        let seed: &SecretKey = ed25519_dalek::SigningKey::as_bytes(self);
+        encoding::ed25519::encode_private_key::<Self>(seed, format)
+    }
+}
@@ -17,12 +17,19 @@ use sha2::{
 
 use core::{marker::PhantomData, num::NonZeroUsize};
 
+#[cfg(feature = "pem")]
+use crate::{
+    alg::{encoding, KeyEncodingError, KeyFormat, PrivateKeyEncoding, PublicKeyEncoding},
+    alloc::Vec,
+};
 use crate::{
     alg::{SecretBytes, SigningKey, VerifyingKey},
     alloc::Cow,
     jwk::{JsonWebKey, JwkError, KeyType},
     Algorithm, AlgorithmSignature,
 };
+#[cfg(feature = "pem")]
+use pkcs8::ObjectIdentifier;
 
 /// Byte size of a serialized EC coordinate.
 const COORDINATE_SIZE: usize = FIELD_SIZE.len();
@@ -211,3 +218,39 @@ impl TryFrom<&JsonWebKey<'_>> for SecretKey {
         jwk.ensure_key_match(sk)
     }
 }
+
+/// Object identifier of the secp256k1 curve.
+#[cfg(feature = "pem")]
+const CURVE_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.10");
+
+#[cfg(feature = "pem")]
+impl PublicKeyEncoding for PublicKey {
+    const KEY_TYPE: &'static str = "secp256k1 public key";
+
+    fn from_public_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ec::decode_public_key(CURVE_OID, format, der, |point| {
+            Self::from_slice(point).map_err(From::from)
+        })
+    }
+
+    fn to_public_der(&self, format: KeyFormat) -> Result<Vec<u8>, KeyEncodingError> {
+        let point = self.serialize_uncompressed();
+        encoding::ec::encode_public_key::<Self>(CURVE_OID, &point, format)
+    }
+}
+
+#[cfg(feature = "pem")]
+impl PrivateKeyEncoding for SecretKey {
+    const KEY_TYPE: &'static str = "secp256k1 private key";
+
+    fn from_private_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ec::decode_private_key(CURVE_OID, format, der, |scalar| {
+            Self::from_slice(scalar).map_err(From::from)
+        })
+    }
+
+    fn to_private_der(&self, format: KeyFormat) -> Result<SecretBytes<'static>, KeyEncodingError> {
+        let point = self.to_verifying_key().serialize_uncompressed();
+        encoding::ec::encode_private_key::<Self>(CURVE_OID, &self[..], &point, format)
+    }
+}
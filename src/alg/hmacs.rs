@@ -1,7 +1,7 @@
 //! JWT algorithms based on HMACs.
 
-use hmac::digest::generic_array::{typenum::Unsigned, GenericArray};
-use hmac::{digest::CtOutput, Hmac, Mac as _};
+use hmac::digest::{generic_array::typenum::Unsigned, CtOutput, Output};
+use hmac::{Hmac, Mac as _};
 use rand_core::{CryptoRng, RngCore};
 use sha2::{
     digest::{core_api::BlockSizeUser, OutputSizeUser},
@@ -39,8 +39,9 @@ macro_rules! define_hmac_signature {
                 NonZeroUsize::new(<$digest as OutputSizeUser>::OutputSize::USIZE);
 
             fn try_from_slice(bytes: &[u8]) -> anyhow::Result<Self> {
-                let bytes = GenericArray::clone_from_slice(bytes);
-                Ok(Self(CtOutput::new(bytes)))
+                let mut output = Output::<Hmac<$digest>>::default();
+                output.copy_from_slice(bytes);
+                Ok(Self(CtOutput::new(output)))
             }
 
             fn as_bytes(&self) -> Cow<'_, [u8]> {
@@ -18,6 +18,13 @@ use crate::{
     jwk::{JsonWebKey, JwkError, KeyType},
     Algorithm, AlgorithmSignature,
 };
+#[cfg(feature = "pem")]
+use crate::{
+    alg::{encoding, KeyEncodingError, KeyFormat, PrivateKeyEncoding, PublicKeyEncoding},
+    alloc::Vec,
+};
+#[cfg(feature = "pem")]
+use pkcs8::ObjectIdentifier;
 
 impl AlgorithmSignature for Signature {
     const LENGTH: Option<NonZeroUsize> =
@@ -187,3 +194,40 @@ impl TryFrom<&JsonWebKey<'_>> for SigningKey {
         jwk.ensure_key_match(sk)
     }
 }
+
+/// Object identifier of the secp256k1 curve.
+#[cfg(feature = "pem")]
+const CURVE_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.10");
+
+#[cfg(feature = "pem")]
+impl PublicKeyEncoding for VerifyingKey {
+    const KEY_TYPE: &'static str = "secp256k1 public key";
+
+    fn from_public_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ec::decode_public_key(CURVE_OID, format, der, |point| {
+            Self::from_sec1_bytes(point).map_err(|err| anyhow::anyhow!(err))
+        })
+    }
+
+    fn to_public_der(&self, format: KeyFormat) -> Result<Vec<u8>, KeyEncodingError> {
+        let point = self.to_encoded_point(false);
+        encoding::ec::encode_public_key::<Self>(CURVE_OID, point.as_bytes(), format)
+    }
+}
+
+#[cfg(feature = "pem")]
+impl PrivateKeyEncoding for SigningKey {
+    const KEY_TYPE: &'static str = "secp256k1 private key";
+
+    fn from_private_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ec::decode_private_key(CURVE_OID, format, der, |scalar| {
+            Self::from_slice(scalar).map_err(|err| anyhow::anyhow!(err))
+        })
+    }
+
+    fn to_private_der(&self, format: KeyFormat) -> Result<SecretBytes<'static>, KeyEncodingError> {
+        let point = self.verifying_key().to_encoded_point(false);
+        let scalar = SecretBytes::owned(self.to_bytes().to_vec());
+        encoding::ec::encode_private_key::<Self>(CURVE_OID, &scalar, point.as_bytes(), format)
+    }
+}
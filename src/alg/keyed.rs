@@ -0,0 +1,177 @@
+//! Signing keys paired with their key IDs.
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use sha2::Sha256;
+
+use crate::{
+    alloc::{Cow, String},
+    jwk::{JsonWebKey, KeyType},
+    Algorithm, CreationError,
+};
+
+/// Signing key paired with its key ID (the `kid` field in the token header).
+///
+/// The key should be used together with the [`KeyedAlg`] algorithm wrapper. When creating
+/// tokens, the wrapper sets the `kid` header field to the key ID, or refuses to sign the token
+/// if the header specifies a different key ID. This ensures that the key ID in created tokens
+/// always corresponds to the key they are signed with.
+///
+/// # Examples
+///
+/// See [`KeyedAlg`] docs for an example of usage.
+#[derive(Debug, Clone)]
+pub struct KeyedSigningKey<K> {
+    key: K,
+    key_id: String,
+}
+
+impl<K> KeyedSigningKey<K> {
+    /// Pairs the `key` with the specified `key_id`.
+    pub fn new(key: K, key_id: impl Into<String>) -> Self {
+        Self {
+            key,
+            key_id: key_id.into(),
+        }
+    }
+
+    /// Pairs the asymmetric `key` with its [RFC 7638] thumbprint used as a key ID.
+    /// The thumbprint is computed using SHA-256 over the public part of the key
+    /// and is base64url-encoded.
+    ///
+    /// [RFC 7638]: https://www.rfc-editor.org/rfc/rfc7638.html
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CreationError::SymmetricKey`] if the key is symmetric (e.g., an `HS*` key).
+    /// The thumbprint of such a key is computed over the secret, so publishing it
+    /// in the token header would allow to brute-force the secret offline.
+    pub fn with_thumbprint(key: K) -> Result<Self, CreationError>
+    where
+        for<'a> &'a K: Into<JsonWebKey<'a>>,
+    {
+        let thumbprint = {
+            let jwk: JsonWebKey<'_> = (&key).into();
+            if jwk.key_type() == KeyType::Symmetric {
+                return Err(CreationError::SymmetricKey);
+            }
+            jwk.thumbprint::<Sha256>()
+        };
+        Ok(Self {
+            key_id: Base64UrlUnpadded::encode_string(&thumbprint),
+            key,
+        })
+    }
+
+    /// Returns the key ID.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the wrapped key.
+    pub fn into_inner(self) -> K {
+        self.key
+    }
+}
+
+impl<K> AsRef<K> for KeyedSigningKey<K> {
+    fn as_ref(&self) -> &K {
+        &self.key
+    }
+}
+
+/// Wrapper around a JWT algorithm that signs tokens with [`KeyedSigningKey`]s.
+///
+/// When creating a token, the key ID of the signing key is recorded in the `kid` header field.
+/// If the header already has a `kid` field that differs from the key ID, token creation fails
+/// with [`CreationError::KeyIdMismatch`]. Verifying keys are the same as for the wrapped
+/// algorithm.
+///
+/// [`CreationError::KeyIdMismatch`]: crate::CreationError::KeyIdMismatch
+///
+/// # Examples
+///
+/// ```
+/// # use jwt_compact::{prelude::*, alg::{Hs256, Hs256Key, KeyedAlg, KeyedSigningKey}, Empty};
+/// # fn main() -> anyhow::Result<()> {
+/// let key = Hs256Key::new(b"super_secret_key_donut_steel");
+/// let keyed_key = KeyedSigningKey::new(key, "my-key");
+/// let claims = // ...
+/// #   Claims::empty();
+/// let token_string = KeyedAlg(Hs256).token(&Header::empty(), &claims, &keyed_key)?;
+///
+/// let token = UntrustedToken::new(&token_string)?;
+/// assert_eq!(token.header().key_id.as_deref(), Some("my-key"));
+/// // The token can be validated using the wrapped algorithm.
+/// Hs256.validator::<Empty>(keyed_key.as_ref()).validate(&token)?;
+///
+/// // A conflicting key ID in the header is an error.
+/// let header = Header::empty().with_key_id("other-key");
+/// assert!(KeyedAlg(Hs256).token(&header, &claims, &keyed_key).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyedAlg<A>(pub A);
+
+impl<A: Algorithm> Algorithm for KeyedAlg<A> {
+    type SigningKey = KeyedSigningKey<A::SigningKey>;
+    type VerifyingKey = A::VerifyingKey;
+    type Signature = A::Signature;
+
+    fn name(&self) -> Cow<'static, str> {
+        self.0.name()
+    }
+
+    fn key_id<'k>(&self, signing_key: &'k Self::SigningKey) -> Option<&'k str> {
+        Some(&signing_key.key_id)
+    }
+
+    fn check_signing_key(&self, signing_key: &Self::SigningKey) -> Result<(), CreationError> {
+        self.0.check_signing_key(&signing_key.key)
+    }
+
+    fn sign(&self, signing_key: &Self::SigningKey, message: &[u8]) -> Self::Signature {
+        self.0.sign(&signing_key.key, message)
+    }
+
+    fn verify_signature(
+        &self,
+        signature: &Self::Signature,
+        verifying_key: &Self::VerifyingKey,
+        message: &[u8],
+    ) -> bool {
+        self.0.verify_signature(signature, verifying_key, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alg::Hs256Key;
+
+    use assert_matches::assert_matches;
+
+    #[cfg(feature = "p256")]
+    #[test]
+    fn key_id_from_thumbprint() {
+        use crate::alg::Es256;
+
+        let key = <Es256 as Algorithm>::SigningKey::from_slice(&[1; 32]).unwrap();
+        let expected_thumbprint = JsonWebKey::from(&key)
+            .to_verifying_key()
+            .thumbprint::<Sha256>();
+        let keyed_key = KeyedSigningKey::with_thumbprint(key).unwrap();
+        assert_eq!(keyed_key.key_id().len(), 43);
+        assert_eq!(
+            Base64UrlUnpadded::decode_vec(keyed_key.key_id()).unwrap(),
+            expected_thumbprint[..]
+        );
+    }
+
+    #[test]
+    fn thumbprint_of_symmetric_key() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let err = KeyedSigningKey::with_thumbprint(key).unwrap_err();
+        assert_matches!(err, CreationError::SymmetricKey);
+    }
+}
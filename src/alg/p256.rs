@@ -14,6 +14,13 @@ use crate::{
     jwk::{JsonWebKey, JwkError, KeyType},
     Algorithm, AlgorithmSignature,
 };
+#[cfg(feature = "pem")]
+use crate::{
+    alg::{encoding, KeyEncodingError, KeyFormat, PrivateKeyEncoding, PublicKeyEncoding},
+    alloc::Vec,
+};
+#[cfg(feature = "pem")]
+use pkcs8::ObjectIdentifier;
 
 impl AlgorithmSignature for Signature {
     const LENGTH: Option<NonZeroUsize> = NonZeroUsize::new(64);
@@ -135,7 +142,7 @@ impl TryFrom<&JsonWebKey<'_>> for SigningKey {
     type Error = JwkError;
 
     fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
-        let JsonWebKey::EllipticCurve { secret, .. } = jwk  else {
+        let JsonWebKey::EllipticCurve { secret, .. } = jwk else {
             return Err(JwkError::key_type(jwk, KeyType::EllipticCurve));
         };
         let sk_bytes = secret.as_deref();
@@ -147,3 +154,40 @@ impl TryFrom<&JsonWebKey<'_>> for SigningKey {
         jwk.ensure_key_match(sk)
     }
 }
+
+/// Object identifier of the P-256 curve (aka `prime256v1`).
+#[cfg(feature = "pem")]
+const CURVE_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+
+#[cfg(feature = "pem")]
+impl PublicKeyEncoding for VerifyingKey {
+    const KEY_TYPE: &'static str = "P-256 public key";
+
+    fn from_public_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ec::decode_public_key(CURVE_OID, format, der, |point| {
+            Self::from_sec1_bytes(point).map_err(|err| anyhow::anyhow!(err))
+        })
+    }
+
+    fn to_public_der(&self, format: KeyFormat) -> Result<Vec<u8>, KeyEncodingError> {
+        let point = self.to_encoded_point(false);
+        encoding::ec::encode_public_key::<Self>(CURVE_OID, point.as_bytes(), format)
+    }
+}
+
+#[cfg(feature = "pem")]
+impl PrivateKeyEncoding for SigningKey {
+    const KEY_TYPE: &'static str = "P-256 private key";
+
+    fn from_private_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ec::decode_private_key(CURVE_OID, format, der, |scalar| {
+            Self::from_slice(scalar).map_err(|err| anyhow::anyhow!(err))
+        })
+    }
+
+    fn to_private_der(&self, format: KeyFormat) -> Result<SecretBytes<'static>, KeyEncodingError> {
+        let point = self.verifying_key().to_encoded_point(false);
+        let scalar = SecretBytes::owned(self.to_bytes().to_vec());
+        encoding::ec::encode_private_key::<Self>(CURVE_OID, &scalar, point.as_bytes(), format)
+    }
+}
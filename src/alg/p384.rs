@@ -0,0 +1,193 @@
+//! `ES384` algorithm implementation using the `p384` crate.
+
+use p384::ecdsa::{
+    signature::{DigestSigner, DigestVerifier},
+    Signature, SigningKey, VerifyingKey,
+};
+use sha2::{Digest, Sha384};
+
+use core::num::NonZeroUsize;
+
+use crate::{
+    alg::{self, SecretBytes},
+    alloc::Cow,
+    jwk::{JsonWebKey, JwkError, KeyType},
+    Algorithm, AlgorithmSignature,
+};
+#[cfg(feature = "pem")]
+use crate::{
+    alg::{encoding, KeyEncodingError, KeyFormat, PrivateKeyEncoding, PublicKeyEncoding},
+    alloc::Vec,
+};
+#[cfg(feature = "pem")]
+use pkcs8::ObjectIdentifier;
+
+impl AlgorithmSignature for Signature {
+    const LENGTH: Option<NonZeroUsize> = NonZeroUsize::new(96);
+
+    fn try_from_slice(slice: &[u8]) -> anyhow::Result<Self> {
+        Signature::try_from(slice).map_err(|err| anyhow::anyhow!(err))
+    }
+
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.to_bytes().to_vec())
+    }
+}
+
+/// `ES384` signing algorithm. Implements elliptic curve digital signatures (ECDSA)
+/// on the secp384r1 curve (aka P-384).
+#[derive(Debug, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "p384")))]
+pub struct Es384;
+
+impl Algorithm for Es384 {
+    type SigningKey = SigningKey;
+    type VerifyingKey = VerifyingKey;
+    type Signature = Signature;
+
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("ES384")
+    }
+
+    fn sign(&self, signing_key: &Self::SigningKey, message: &[u8]) -> Self::Signature {
+        let mut digest = Sha384::default();
+        digest.update(message);
+        signing_key.sign_digest(digest)
+    }
+
+    fn verify_signature(
+        &self,
+        signature: &Self::Signature,
+        verifying_key: &Self::VerifyingKey,
+        message: &[u8],
+    ) -> bool {
+        let mut digest = Sha384::default();
+        digest.update(message);
+
+        verifying_key.verify_digest(digest, signature).is_ok()
+    }
+}
+
+impl alg::SigningKey<Es384> for SigningKey {
+    fn from_slice(raw: &[u8]) -> anyhow::Result<Self> {
+        Self::from_slice(raw).map_err(|err| anyhow::anyhow!(err))
+    }
+
+    fn to_verifying_key(&self) -> VerifyingKey {
+        *self.verifying_key()
+    }
+
+    fn as_bytes(&self) -> SecretBytes<'_> {
+        SecretBytes::owned(self.to_bytes().to_vec())
+    }
+}
+
+impl alg::VerifyingKey<Es384> for VerifyingKey {
+    fn from_slice(raw: &[u8]) -> anyhow::Result<Self> {
+        Self::from_sec1_bytes(raw).map_err(|err| anyhow::anyhow!(err))
+    }
+
+    /// Serializes the key as a 49-byte compressed form.
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        let bytes = self.to_encoded_point(true).as_bytes().to_vec();
+        Cow::Owned(bytes)
+    }
+}
+
+fn create_jwk<'a>(pk: &VerifyingKey, sk: Option<&'a SigningKey>) -> JsonWebKey<'a> {
+    let uncompressed = pk.to_encoded_point(false);
+    JsonWebKey::EllipticCurve {
+        curve: "P-384".into(),
+        x: Cow::Owned(uncompressed.x().expect("x coord").to_vec()),
+        y: Cow::Owned(uncompressed.y().expect("y coord").to_vec()),
+        secret: sk.map(|sk| SecretBytes::owned(sk.to_bytes().to_vec())),
+    }
+}
+
+impl<'a> From<&'a VerifyingKey> for JsonWebKey<'a> {
+    fn from(key: &'a VerifyingKey) -> JsonWebKey<'a> {
+        create_jwk(key, None)
+    }
+}
+
+impl TryFrom<&JsonWebKey<'_>> for VerifyingKey {
+    type Error = JwkError;
+
+    fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+        const COORDINATE_SIZE: usize = 48;
+
+        let JsonWebKey::EllipticCurve { curve, x, y, .. } = jwk else {
+            return Err(JwkError::key_type(jwk, KeyType::EllipticCurve));
+        };
+        JsonWebKey::ensure_curve(curve, "P-384")?;
+        JsonWebKey::ensure_len("x", x, COORDINATE_SIZE)?;
+        JsonWebKey::ensure_len("y", y, COORDINATE_SIZE)?;
+
+        let mut key_bytes = [0_u8; 2 * COORDINATE_SIZE + 1];
+        key_bytes[0] = 4; // uncompressed key marker
+        key_bytes[1..=COORDINATE_SIZE].copy_from_slice(x);
+        key_bytes[(1 + COORDINATE_SIZE)..].copy_from_slice(y);
+        VerifyingKey::from_sec1_bytes(&key_bytes[..])
+            .map_err(|err| JwkError::custom(anyhow::anyhow!(err)))
+    }
+}
+
+impl<'a> From<&'a SigningKey> for JsonWebKey<'a> {
+    fn from(key: &'a SigningKey) -> JsonWebKey<'a> {
+        create_jwk(key.verifying_key(), Some(key))
+    }
+}
+
+impl TryFrom<&JsonWebKey<'_>> for SigningKey {
+    type Error = JwkError;
+
+    fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+        let JsonWebKey::EllipticCurve { secret, .. } = jwk else {
+            return Err(JwkError::key_type(jwk, KeyType::EllipticCurve));
+        };
+        let sk_bytes = secret.as_deref();
+        let sk_bytes = sk_bytes.ok_or_else(|| JwkError::NoField("d".into()))?;
+        JsonWebKey::ensure_len("d", sk_bytes, 48)?;
+
+        let sk =
+            Self::from_slice(sk_bytes).map_err(|err| JwkError::custom(anyhow::anyhow!(err)))?;
+        jwk.ensure_key_match(sk)
+    }
+}
+
+/// Object identifier of the P-384 curve (aka `secp384r1`).
+#[cfg(feature = "pem")]
+const CURVE_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+
+#[cfg(feature = "pem")]
+impl PublicKeyEncoding for VerifyingKey {
+    const KEY_TYPE: &'static str = "P-384 public key";
+
+    fn from_public_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ec::decode_public_key(CURVE_OID, format, der, |point| {
+            Self::from_sec1_bytes(point).map_err(|err| anyhow::anyhow!(err))
+        })
+    }
+
+    fn to_public_der(&self, format: KeyFormat) -> Result<Vec<u8>, KeyEncodingError> {
+        let point = self.to_encoded_point(false);
+        encoding::ec::encode_public_key::<Self>(CURVE_OID, point.as_bytes(), format)
+    }
+}
+
+#[cfg(feature = "pem")]
+impl PrivateKeyEncoding for SigningKey {
+    const KEY_TYPE: &'static str = "P-384 private key";
+
+    fn from_private_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        encoding::ec::decode_private_key(CURVE_OID, format, der, |scalar| {
+            Self::from_slice(scalar).map_err(|err| anyhow::anyhow!(err))
+        })
+    }
+
+    fn to_private_der(&self, format: KeyFormat) -> Result<SecretBytes<'static>, KeyEncodingError> {
+        let point = self.verifying_key().to_encoded_point(false);
+        let scalar = SecretBytes::owned(self.to_bytes().to_vec());
+        encoding::ec::encode_private_key::<Self>(CURVE_OID, &scalar, point.as_bytes(), format)
+    }
+}
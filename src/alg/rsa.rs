@@ -1,6 +1,6 @@
 //! RSA-based JWT algorithms: `RS*` and `PS*`.
 
-pub use rsa::{errors::Error as RsaError, RsaPrivateKey, RsaPublicKey};
+pub use rsa::{RsaPrivateKey, RsaPublicKey};
 
 use rand_core::{CryptoRng, RngCore};
 use rsa::{
@@ -11,12 +11,19 @@ use sha2::{Digest, Sha256, Sha384, Sha512};
 
 use core::{fmt, str::FromStr};
 
+#[cfg(feature = "pem")]
+use crate::alg::{KeyEncodingError, KeyFormat, PrivateKeyEncoding, PublicKeyEncoding};
 use crate::{
     alg::{SecretBytes, StrongKey, WeakKeyError},
     alloc::{Box, Cow, String, ToOwned, Vec},
     jwk::{JsonWebKey, JwkError, KeyType, RsaPrimeFactor, RsaPrivateParts},
     Algorithm, AlgorithmSignature,
 };
+#[cfg(feature = "pem")]
+use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey, EncodeRsaPrivateKey, EncodeRsaPublicKey},
+    pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey},
+};
 
 /// RSA signature.
 #[derive(Debug)]
@@ -369,7 +376,8 @@ impl TryFrom<&JsonWebKey<'_>> for RsaPublicKey {
             modulus,
             public_exponent,
             ..
-        } = jwk else {
+        } = jwk
+        else {
             return Err(JwkError::key_type(jwk, KeyType::Rsa));
         };
 
@@ -388,7 +396,7 @@ impl<'a> From<&'a RsaPrivateKey> for JsonWebKey<'a> {
     fn from(key: &'a RsaPrivateKey) -> JsonWebKey<'a> {
         const MSG: &str = "RsaPrivateKey must have at least 2 prime factors";
 
-        let p = key.primes().get(0).expect(MSG);
+        let p = key.primes().first().expect(MSG);
         let q = key.primes().get(1).expect(MSG);
 
         let private_parts = RsaPrivateParts {
@@ -428,7 +436,8 @@ impl TryFrom<&JsonWebKey<'_>> for RsaPrivateKey {
             modulus,
             public_exponent,
             private_parts,
-        } = jwk else {
+        } = jwk
+        else {
             return Err(JwkError::key_type(jwk, KeyType::Rsa));
         };
 
@@ -462,3 +471,87 @@ impl TryFrom<&JsonWebKey<'_>> for RsaPrivateKey {
         Ok(key)
     }
 }
+
+#[cfg(feature = "pem")]
+impl PublicKeyEncoding for RsaPublicKey {
+    const KEY_TYPE: &'static str = "RSA public key";
+
+    fn from_public_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        let key = match format {
+            KeyFormat::Spki => Self::from_public_key_der(der).map_err(anyhow::Error::msg),
+            KeyFormat::Pkcs1 => Self::from_pkcs1_der(der).map_err(anyhow::Error::msg),
+            _ => {
+                return Err(KeyEncodingError::UnsupportedFormat {
+                    key_type: Self::KEY_TYPE,
+                    format,
+                });
+            }
+        };
+        key.map_err(|error| KeyEncodingError::Malformed {
+            key_type: Self::KEY_TYPE,
+            format,
+            error,
+        })
+    }
+
+    fn to_public_der(&self, format: KeyFormat) -> Result<Vec<u8>, KeyEncodingError> {
+        let der = match format {
+            KeyFormat::Spki => self.to_public_key_der().map_err(anyhow::Error::msg),
+            KeyFormat::Pkcs1 => self.to_pkcs1_der().map_err(anyhow::Error::msg),
+            _ => {
+                return Err(KeyEncodingError::UnsupportedFormat {
+                    key_type: Self::KEY_TYPE,
+                    format,
+                });
+            }
+        };
+        let der = der.map_err(|error| KeyEncodingError::Encoding {
+            key_type: Self::KEY_TYPE,
+            format,
+            error,
+        })?;
+        Ok(der.into_vec())
+    }
+}
+
+#[cfg(feature = "pem")]
+impl PrivateKeyEncoding for RsaPrivateKey {
+    const KEY_TYPE: &'static str = "RSA private key";
+
+    fn from_private_der(format: KeyFormat, der: &[u8]) -> Result<Self, KeyEncodingError> {
+        let key = match format {
+            KeyFormat::Pkcs8 => Self::from_pkcs8_der(der).map_err(anyhow::Error::msg),
+            KeyFormat::Pkcs1 => Self::from_pkcs1_der(der).map_err(anyhow::Error::msg),
+            _ => {
+                return Err(KeyEncodingError::UnsupportedFormat {
+                    key_type: Self::KEY_TYPE,
+                    format,
+                });
+            }
+        };
+        key.map_err(|error| KeyEncodingError::Malformed {
+            key_type: Self::KEY_TYPE,
+            format,
+            error,
+        })
+    }
+
+    fn to_private_der(&self, format: KeyFormat) -> Result<SecretBytes<'static>, KeyEncodingError> {
+        let der = match format {
+            KeyFormat::Pkcs8 => self.to_pkcs8_der().map_err(anyhow::Error::msg),
+            KeyFormat::Pkcs1 => self.to_pkcs1_der().map_err(anyhow::Error::msg),
+            _ => {
+                return Err(KeyEncodingError::UnsupportedFormat {
+                    key_type: Self::KEY_TYPE,
+                    format,
+                });
+            }
+        };
+        let der = der.map_err(|error| KeyEncodingError::Encoding {
+            key_type: Self::KEY_TYPE,
+            format,
+            error,
+        })?;
+        Ok(SecretBytes::owned(der.as_bytes().to_vec()))
+    }
+}
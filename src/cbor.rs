@@ -0,0 +1,101 @@
+//! CBOR encoding backed by `ciborium`.
+//!
+//! Data is serialized using the [core deterministic encoding requirements] of RFC 8949,
+//! so that signatures over the serialized data are reproducible across implementations:
+//!
+//! - Integers, lengths and floats use the shortest possible encoding.
+//! - Arrays, maps and strings have definite lengths.
+//! - Map entries are sorted by the bytewise lexicographic order of their encoded keys.
+//!
+//! [core deterministic encoding requirements]: https://www.rfc-editor.org/rfc/rfc8949#section-4.2.1
+
+use ciborium::Value;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::alloc::{Box, Vec};
+
+/// Serializes `value` into deterministically encoded CBOR.
+pub(crate) fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, anyhow::Error> {
+    let value = Value::serialized(value).map_err(anyhow::Error::msg)?;
+    Ok(value_to_vec(value))
+}
+
+/// Deserializes a value from CBOR. The input is not required to be deterministically encoded.
+pub(crate) fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, anyhow::Error> {
+    ciborium::de::from_reader(bytes).map_err(anyhow::Error::msg)
+}
+
+/// Serializes a CBOR `value` after bringing it to the canonical form.
+pub(crate) fn value_to_vec(value: Value) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    ciborium::ser::into_writer(&canonicalize(value), &mut buffer)
+        .expect("cannot serialize CBOR value; this should never happen");
+    // ^ Writing into a `Vec` is infallible, and a `Value` can always be serialized.
+    buffer
+}
+
+/// Recursively sorts map entries in `value` by their encoded keys.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        Value::Tag(tag, inner) => Value::Tag(tag, Box::new(canonicalize(*inner))),
+        Value::Map(entries) => {
+            let mut entries: Vec<_> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = canonicalize(key);
+                    let mut encoded_key = Vec::new();
+                    ciborium::ser::into_writer(&key, &mut encoded_key)
+                        .expect("cannot serialize CBOR value; this should never happen");
+                    (encoded_key, key, canonicalize(value))
+                })
+                .collect();
+            entries.sort_unstable_by(|(x, ..), (y, ..)| x.cmp(y));
+            let entries = entries
+                .into_iter()
+                .map(|(_, key, value)| (key, value))
+                .collect();
+            Value::Map(entries)
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{alloc::ToOwned, Claims};
+
+    #[test]
+    fn maps_are_sorted_by_encoded_keys() {
+        let value = Value::Map(Vec::from([
+            (Value::Text("aa".to_owned()), Value::Integer(1.into())),
+            (Value::Text("b".to_owned()), Value::Integer(2.into())),
+            (Value::Integer((-1).into()), Value::Integer(3.into())),
+            (Value::Integer(10.into()), Value::Integer(4.into())),
+        ]));
+        let bytes = to_vec(&value).unwrap();
+        assert_eq!(
+            bytes,
+            [0xa4, 0x0a, 0x04, 0x20, 0x03, 0x61, b'b', 0x02, 0x62, b'a', b'a', 0x01]
+        );
+    }
+
+    #[test]
+    fn claims_encoding_is_deterministic() {
+        let claims = Claims::new(serde_json::json!({ "z": 1.5, "a": [1, 2] }))
+            .set_subject("john")
+            .set_issuer("example.com");
+        let bytes = to_vec(&claims).unwrap();
+        // The map has definite length, and `a` (key 0x61_61) precedes `z`, `iss` and `sub`.
+        assert_eq!(bytes[0], 0xa4);
+        assert_eq!(bytes[1..3], [0x61, b'a']);
+        // The float uses the shortest (half-precision) encoding.
+        let float_pos = bytes.windows(3).position(|w| w == [0x61, b'z', 0xf9]);
+        assert!(float_pos.is_some(), "{bytes:?}");
+
+        let restored: Claims<serde_json::Value> = from_slice(&bytes).unwrap();
+        assert_eq!(restored.custom, claims.custom);
+        assert_eq!(restored.subject, claims.subject);
+    }
+}
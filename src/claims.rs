@@ -1,7 +1,12 @@
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{Claim, ValidationError};
+use crate::{
+    alloc::{String, ToOwned, Vec},
+    Claim, ValidationError,
+};
+
+use core::slice;
 
 /// Time-related options for token creation and validation.
 ///
@@ -65,16 +70,78 @@ impl Default for TimeOptions {
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Empty {}
 
+/// Audience of a token (the `aud` claim).
+///
+/// As per [RFC 7519], the audience is either a single string, or an array of strings.
+/// The variant is preserved during (de)serialization.
+///
+/// [RFC 7519]: https://www.rfc-editor.org/rfc/rfc7519#section-4.1.3
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Audience {
+    /// Single audience.
+    Single(String),
+    /// Multiple audiences.
+    Multiple(Vec<String>),
+}
+
+impl From<&str> for Audience {
+    fn from(audience: &str) -> Self {
+        Self::Single(audience.to_owned())
+    }
+}
+
+impl From<String> for Audience {
+    fn from(audience: String) -> Self {
+        Self::Single(audience)
+    }
+}
+
+impl From<Vec<String>> for Audience {
+    fn from(audiences: Vec<String>) -> Self {
+        Self::Multiple(audiences)
+    }
+}
+
+impl Audience {
+    /// Iterates over audiences.
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        let audiences: &[String] = match self {
+            Self::Single(audience) => slice::from_ref(audience),
+            Self::Multiple(audiences) => audiences,
+        };
+        audiences.iter().map(String::as_str)
+    }
+
+    /// Checks whether the specified `audience` is contained in this claim.
+    pub fn contains(&self, audience: &str) -> bool {
+        self.iter().any(|aud| aud == audience)
+    }
+}
+
 /// Claims encoded in a token.
 ///
-/// Claims are comprised of a "standard" part (`exp`, `nbf` and `iat` claims as per [JWT spec]),
-/// and custom fields. `iss`, `sub` and `aud` claims are not in the standard part
-/// due to a variety of data types they can be reasonably represented by.
+/// Claims are comprised of a "standard" part (registered claims as per [JWT spec]:
+/// `iss`, `sub`, `aud`, `exp`, `nbf`, `iat` and `jti`), and custom fields.
+/// Since registered claims are (de)serialized as a part of `Claims`, custom claims
+/// should not contain fields with the same names.
 ///
 /// [JWT spec]: https://tools.ietf.org/html/rfc7519#section-4.1
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[non_exhaustive]
 pub struct Claims<T> {
+    /// Issuer of the token.
+    #[serde(rename = "iss", default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+
+    /// Subject of the token.
+    #[serde(rename = "sub", default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    /// Intended audience(s) of the token.
+    #[serde(rename = "aud", default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<Audience>,
+
     /// Expiration time of the token.
     #[serde(
         rename = "exp",
@@ -102,6 +169,10 @@ pub struct Claims<T> {
     )]
     pub issued_at: Option<DateTime<Utc>>,
 
+    /// Unique identifier of the token.
+    #[serde(rename = "jti", default, skip_serializing_if = "Option::is_none")]
+    pub jwt_id: Option<String>,
+
     /// Custom claims.
     #[serde(flatten)]
     pub custom: T,
@@ -110,12 +181,7 @@ pub struct Claims<T> {
 impl Claims<Empty> {
     /// Creates an empty claims instance.
     pub fn empty() -> Self {
-        Self {
-            expiration: None,
-            not_before: None,
-            issued_at: None,
-            custom: Empty {},
-        }
+        Self::new(Empty {})
     }
 }
 
@@ -123,13 +189,53 @@ impl<T> Claims<T> {
     /// Creates a new instance with the provided custom claims.
     pub fn new(custom_claims: T) -> Self {
         Self {
+            issuer: None,
+            subject: None,
+            audience: None,
             expiration: None,
             not_before: None,
             issued_at: None,
+            jwt_id: None,
             custom: custom_claims,
         }
     }
 
+    /// Sets the `iss` claim.
+    #[must_use]
+    pub fn set_issuer(self, issuer: impl Into<String>) -> Self {
+        Self {
+            issuer: Some(issuer.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `sub` claim.
+    #[must_use]
+    pub fn set_subject(self, subject: impl Into<String>) -> Self {
+        Self {
+            subject: Some(subject.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `aud` claim.
+    #[must_use]
+    pub fn set_audience(self, audience: impl Into<Audience>) -> Self {
+        Self {
+            audience: Some(audience.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `jti` claim.
+    #[must_use]
+    pub fn set_jwt_id(self, jwt_id: impl Into<String>) -> Self {
+        Self {
+            jwt_id: Some(jwt_id.into()),
+            ..self
+        }
+    }
+
     /// Sets the `expiration` claim so that the token has the specified `duration`.
     /// The current timestamp is taken from `options`.
     #[must_use]
@@ -209,9 +315,46 @@ impl<T> Claims<T> {
             },
         )
     }
+
+    /// Validates the issuer (`iss` claim).
+    ///
+    /// This method will return an error if the claims do not feature an issuer,
+    /// or if it differs from the `expected` one.
+    pub fn validate_issuer(&self, expected: &str) -> Result<&Self, ValidationError> {
+        let issuer = self
+            .issuer
+            .as_deref()
+            .ok_or(ValidationError::NoClaim(Claim::Issuer))?;
+        if issuer == expected {
+            Ok(self)
+        } else {
+            Err(ValidationError::IssuerMismatch {
+                expected: expected.to_owned(),
+                actual: issuer.to_owned(),
+            })
+        }
+    }
+
+    /// Validates the audience (`aud` claim).
+    ///
+    /// This method will return an error if the claims do not feature an audience,
+    /// or if the `expected` audience is not contained in it.
+    pub fn validate_audience(&self, expected: &str) -> Result<&Self, ValidationError> {
+        let audience = self
+            .audience
+            .as_ref()
+            .ok_or(ValidationError::NoClaim(Claim::Audience))?;
+        if audience.contains(expected) {
+            Ok(self)
+        } else {
+            Err(ValidationError::AudienceMismatch {
+                expected: expected.to_owned(),
+            })
+        }
+    }
 }
 
-mod serde_timestamp {
+pub(crate) mod serde_timestamp {
     use chrono::{offset::TimeZone, DateTime, Utc};
     use serde::{
         de::{Error as DeError, Visitor},
@@ -222,7 +365,7 @@ mod serde_timestamp {
 
     struct TimestampVisitor;
 
-    impl<'de> Visitor<'de> for TimestampVisitor {
+    impl Visitor<'_> for TimestampVisitor {
         type Value = DateTime<Utc>;
 
         fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -249,6 +392,7 @@ mod serde_timestamp {
         }
     }
 
+    #[allow(clippy::ref_option)] // the signature is required by `serde`
     pub fn serialize<S: Serializer>(
         time: &Option<DateTime<Utc>>,
         serializer: S,
@@ -280,14 +424,14 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "serde_cbor")]
+    #[cfg(feature = "cbor")]
     fn empty_claims_can_be_serialized_to_cbor() {
         let mut claims = Claims::empty();
-        assert!(serde_cbor::to_vec(&claims).is_ok());
+        assert!(crate::cbor::to_vec(&claims).is_ok());
         claims.expiration = Some(Utc::now());
-        assert!(serde_cbor::to_vec(&claims).is_ok());
+        assert!(crate::cbor::to_vec(&claims).is_ok());
         claims.not_before = Some(Utc::now());
-        assert!(serde_cbor::to_vec(&claims).is_ok());
+        assert!(crate::cbor::to_vec(&claims).is_ok());
     }
 
     #[test]
@@ -353,4 +497,72 @@ mod tests {
             ValidationError::NotMature
         );
     }
+
+    #[test]
+    fn registered_claims_serialization() {
+        let claims = Claims::empty()
+            .set_issuer("https://example.com/")
+            .set_subject("alice")
+            .set_audience("api")
+            .set_jwt_id("token-id");
+        let json = serde_json::to_value(&claims).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "iss": "https://example.com/",
+                "sub": "alice",
+                "aud": "api",
+                "jti": "token-id",
+            })
+        );
+        let restored: Claims<Empty> = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, claims);
+
+        let json = serde_json::json!({ "aud": ["api", "other-api"] });
+        let claims: Claims<Empty> = serde_json::from_value(json.clone()).unwrap();
+        let audience = claims.audience.as_ref().unwrap();
+        assert_eq!(audience.iter().collect::<Vec<_>>(), ["api", "other-api"]);
+        assert_eq!(serde_json::to_value(&claims).unwrap(), json);
+
+        let json = serde_json::json!({ "aud": 1 });
+        serde_json::from_value::<Claims<Empty>>(json).unwrap_err();
+    }
+
+    #[test]
+    fn issuer_claim() {
+        let claims = Claims::empty();
+        assert_matches!(
+            claims.validate_issuer("issuer").unwrap_err(),
+            ValidationError::NoClaim(Claim::Issuer)
+        );
+
+        let claims = claims.set_issuer("issuer");
+        assert!(claims.validate_issuer("issuer").is_ok());
+        assert_matches!(
+            claims.validate_issuer("other").unwrap_err(),
+            ValidationError::IssuerMismatch { expected, actual }
+                if expected == "other" && actual == "issuer"
+        );
+    }
+
+    #[test]
+    fn audience_claim() {
+        let claims = Claims::empty();
+        assert_matches!(
+            claims.validate_audience("api").unwrap_err(),
+            ValidationError::NoClaim(Claim::Audience)
+        );
+
+        let claims = claims.set_audience("api");
+        assert!(claims.validate_audience("api").is_ok());
+        assert_matches!(
+            claims.validate_audience("other").unwrap_err(),
+            ValidationError::AudienceMismatch { expected } if expected == "other"
+        );
+
+        let claims = claims.set_audience(vec!["api".to_owned(), "other".to_owned()]);
+        assert!(claims.validate_audience("api").is_ok());
+        assert!(claims.validate_audience("other").is_ok());
+        assert!(claims.validate_audience("third").is_err());
+    }
 }
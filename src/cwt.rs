@@ -0,0 +1,502 @@
+//! [CBOR Web Tokens] (CWTs) secured with single-signer [COSE] structures.
+//!
+//! Unlike tokens created with [`AlgorithmExt::compact_token()`], which are ordinary JWTs
+//! with CBOR-encoded claims, CWTs are CBOR structures throughout:
+//!
+//! - A token is a `COSE_Sign1` structure (for digital signature algorithms, such as `EdDSA`
+//!   or `ES256`) or a `COSE_Mac0` structure (for MAC algorithms, such as `HS256`).
+//! - The signing algorithm and the key ID are stored in the COSE header with integer labels.
+//! - [`Claims`] are encoded as a CBOR map, with registered claims using integer keys
+//!   (`iss` = 1, `sub` = 2, `aud` = 3, `exp` = 4, `nbf` = 5, `iat` = 6, `cti` = 7).
+//!   Custom claims retain their string keys. The `cti` claim is mapped to / from
+//!   [`Claims::jwt_id`] and thus must be a valid UTF-8 string.
+//!
+//! CWTs are created with [`AlgorithmExt::cwt()`] and parsed with [`UntrustedCwt`]. A parsed
+//! CWT can be validated with the [`Validator`](crate::Validator) for the corresponding
+//! algorithm in the same way as JWTs. Keys can be converted to / from the [`COSE_Key`] form
+//! via [`JsonWebKey`](crate::jwk::JsonWebKey).
+//!
+//! [CBOR Web Tokens]: https://www.rfc-editor.org/rfc/rfc8392
+//! [COSE]: https://www.rfc-editor.org/rfc/rfc9052
+//! [`COSE_Key`]: https://www.rfc-editor.org/rfc/rfc9052#section-7
+//! [`AlgorithmExt::compact_token()`]: crate::AlgorithmExt::compact_token()
+//! [`AlgorithmExt::cwt()`]: crate::AlgorithmExt::cwt()
+//!
+//! # Examples
+//!
+//! ```
+//! use jwt_compact::{alg::{Hs256, Hs256Key}, cwt::{CoseHeader, UntrustedCwt}, prelude::*};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let key = Hs256Key::new(b"super_secret_key_donut_steel");
+//! let claims = Claims::new(serde_json::json!({ "scope": "read" }))
+//!     .set_subject("device-42")
+//!     .set_jwt_id("0b71");
+//! let token_bytes = Hs256.cwt(&CoseHeader::empty().with_key_id(*b"key"), &claims, &key)?;
+//!
+//! let token = UntrustedCwt::new(&token_bytes)?;
+//! assert_eq!(token.algorithm(), "HS256");
+//! assert_eq!(token.header().key_id.as_deref(), Some(&b"key"[..]));
+//! let token = Hs256.validator::<serde_json::Value>(&key).validate(token.as_ref())?;
+//! assert_eq!(token.claims().subject.as_deref(), Some("device-42"));
+//! assert_eq!(token.claims().custom["scope"], "read");
+//! # Ok(())
+//! # }
+//! ```
+
+use ciborium::Value;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    alloc::{Box, String, ToOwned, ToString, Vec},
+    cbor, Algorithm, AlgorithmSignature, Claims, CreationError, Header, ParseError, UntrustedToken,
+};
+
+mod key;
+
+/// CBOR tag for CWTs.
+const CWT_TAG: u64 = 61;
+/// CBOR tag for `COSE_Sign1` structures.
+const COSE_SIGN1_TAG: u64 = 18;
+/// CBOR tag for `COSE_Mac0` structures.
+const COSE_MAC0_TAG: u64 = 17;
+
+/// Label of the algorithm COSE header parameter.
+const ALG_LABEL: i64 = 1;
+/// Label of the key ID COSE header parameter.
+const KID_LABEL: i64 = 4;
+
+/// Integer keys of the registered claims as per RFC 8392.
+const CLAIM_KEYS: &[(&str, i64)] = &[
+    ("iss", 1),
+    ("sub", 2),
+    ("aud", 3),
+    ("exp", 4),
+    ("nbf", 5),
+    ("iat", 6),
+    ("jti", 7),
+];
+/// Integer key of the `cti` claim, which is a byte string rather than a text one.
+const CTI_KEY: i64 = 7;
+
+/// Identifiers of the supported algorithms in the [COSE algorithms registry].
+///
+/// [COSE algorithms registry]: https://www.iana.org/assignments/cose/cose.xhtml#algorithms
+const COSE_ALGORITHMS: &[(&str, i64)] = &[
+    ("HS256", 5),
+    ("HS384", 6),
+    ("HS512", 7),
+    ("EdDSA", -8),
+    ("ES256", -7),
+    ("ES384", -35),
+    ("ES512", -36),
+    ("ES256K", -47),
+    ("RS256", -257),
+    ("RS384", -258),
+    ("RS512", -259),
+    ("PS256", -37),
+    ("PS384", -38),
+    ("PS512", -39),
+];
+
+fn cose_algorithm_id(name: &str) -> Option<i64> {
+    COSE_ALGORITHMS
+        .iter()
+        .find_map(|&(alg_name, id)| (alg_name == name).then_some(id))
+}
+
+fn algorithm_name(id: i128) -> String {
+    COSE_ALGORITHMS
+        .iter()
+        .find_map(|&(name, alg_id)| (i128::from(alg_id) == id).then(|| name.to_owned()))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Kind of a single-signer COSE structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CoseStructure {
+    Sign1,
+    Mac0,
+}
+
+impl CoseStructure {
+    fn for_algorithm(name: &str) -> Self {
+        if name.starts_with("HS") {
+            Self::Mac0
+        } else {
+            Self::Sign1
+        }
+    }
+
+    fn from_tag(tag: u64) -> Option<Self> {
+        match tag {
+            COSE_SIGN1_TAG => Some(Self::Sign1),
+            COSE_MAC0_TAG => Some(Self::Mac0),
+            _ => None,
+        }
+    }
+
+    fn tag(self) -> u64 {
+        match self {
+            Self::Sign1 => COSE_SIGN1_TAG,
+            Self::Mac0 => COSE_MAC0_TAG,
+        }
+    }
+
+    /// Returns the data to be signed or authenticated, i.e., `Sig_structure` or `MAC_structure`
+    /// with empty external additional authenticated data.
+    fn signed_data(self, protected_header: &[u8], payload: &[u8]) -> Vec<u8> {
+        let context = match self {
+            Self::Sign1 => "Signature1",
+            Self::Mac0 => "MAC0",
+        };
+        let structure = Value::Array(Vec::from([
+            Value::Text(context.to_owned()),
+            Value::Bytes(protected_header.to_vec()),
+            Value::Bytes(Vec::new()),
+            Value::Bytes(payload.to_vec()),
+        ]));
+        cbor::value_to_vec(structure)
+    }
+}
+
+/// Gets a value from a CBOR map by its integer label.
+fn get_by_label(map: &[(Value, Value)], label: i64) -> Option<&Value> {
+    map.iter().find_map(|(key, value)| {
+        let key = key.as_integer()?;
+        (i128::from(key) == i128::from(label)).then_some(value)
+    })
+}
+
+/// COSE header parameters of a [CWT](self) supported by the crate.
+///
+/// When creating a token, all parameters are placed into the protected header.
+/// When parsing a token, parameters are read from both the protected and unprotected headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CoseHeader {
+    /// Identifier of the key that has signed the token (the `kid` parameter, label 4).
+    pub key_id: Option<Vec<u8>>,
+}
+
+impl CoseHeader {
+    /// Creates an empty header.
+    pub const fn empty() -> Self {
+        Self { key_id: None }
+    }
+
+    /// Sets the `key_id` field for this header.
+    #[must_use]
+    pub fn with_key_id(mut self, key_id: impl Into<Vec<u8>>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+}
+
+pub(crate) fn create_cwt<A, T>(
+    algorithm: &A,
+    header: &CoseHeader,
+    claims: &Claims<T>,
+    signing_key: &A::SigningKey,
+) -> Result<Vec<u8>, CreationError>
+where
+    A: Algorithm + ?Sized,
+    T: Serialize,
+{
+    let name = algorithm.name();
+    let algorithm_id = cose_algorithm_id(&name)
+        .ok_or_else(|| CreationError::UnsupportedAlgorithm(name.clone().into_owned()))?;
+    let structure = CoseStructure::for_algorithm(&name);
+
+    let key_id = match (&header.key_id, algorithm.key_id(signing_key)) {
+        (Some(key_id), Some(expected_key_id)) if key_id != expected_key_id.as_bytes() => {
+            return Err(CreationError::KeyIdMismatch {
+                expected: expected_key_id.to_owned(),
+                actual: String::from_utf8_lossy(key_id).into_owned(),
+            });
+        }
+        (Some(key_id), _) => Some(key_id.clone()),
+        (None, expected_key_id) => expected_key_id.map(|id| id.as_bytes().to_vec()),
+    };
+
+    let mut protected_header = Vec::from([(ALG_LABEL.into(), algorithm_id.into())]);
+    if let Some(key_id) = key_id {
+        protected_header.push((KID_LABEL.into(), Value::Bytes(key_id)));
+    }
+    let protected_header = cbor::value_to_vec(Value::Map(protected_header));
+    let payload = serialize_claims(claims).map_err(CreationError::CborClaims)?;
+
+    let signed_data = structure.signed_data(&protected_header, &payload);
+    let signature = algorithm.sign(signing_key, &signed_data);
+    let token = Value::Array(Vec::from([
+        Value::Bytes(protected_header),
+        Value::Map(Vec::new()),
+        Value::Bytes(payload),
+        Value::Bytes(signature.as_bytes().into_owned()),
+    ]));
+    Ok(cbor::value_to_vec(Value::Tag(
+        structure.tag(),
+        Box::new(token),
+    )))
+}
+
+fn serialize_claims<T: Serialize>(claims: &Claims<T>) -> Result<Vec<u8>, anyhow::Error> {
+    let Value::Map(claims) = Value::serialized(claims).map_err(anyhow::Error::msg)? else {
+        return Err(anyhow::anyhow!("claims must serialize to a map"));
+    };
+    let claims = claims
+        .into_iter()
+        .map(|(key, value)| {
+            let Value::Text(name) = &key else {
+                return (key, value);
+            };
+            let Some(&(_, claim_key)) = CLAIM_KEYS.iter().find(|(claim, _)| claim == name) else {
+                return (key, value);
+            };
+            let value = match value {
+                Value::Text(s) if claim_key == CTI_KEY => Value::Bytes(s.into_bytes()),
+                other => other,
+            };
+            (claim_key.into(), value)
+        })
+        .collect();
+    Ok(cbor::value_to_vec(Value::Map(claims)))
+}
+
+pub(crate) fn deserialize_claims<T: DeserializeOwned>(
+    serialized_claims: &[u8],
+) -> Result<Claims<T>, anyhow::Error> {
+    let Value::Map(claims) = cbor::from_slice(serialized_claims)? else {
+        return Err(anyhow::anyhow!("claims must be a CBOR map"));
+    };
+    let claims = claims
+        .into_iter()
+        .map(|(key, value)| {
+            let Some(claim_key) = key.as_integer().map(i128::from) else {
+                return Ok((key, value));
+            };
+            let claim = CLAIM_KEYS
+                .iter()
+                .find(|&&(_, key)| i128::from(key) == claim_key);
+            let Some(&(name, key)) = claim else {
+                return Ok((key, value));
+            };
+            let value = match value {
+                Value::Bytes(bytes) if key == CTI_KEY => {
+                    let cti = String::from_utf8(bytes)
+                        .map_err(|_| anyhow::anyhow!("`cti` claim is not a valid UTF-8 string"))?;
+                    Value::Text(cti)
+                }
+                other => other,
+            };
+            Ok((Value::Text(name.to_owned()), value))
+        })
+        .collect::<Result<_, anyhow::Error>>()?;
+    Value::Map(claims)
+        .deserialized()
+        .map_err(anyhow::Error::msg)
+}
+
+/// Parsed, but unvalidated [CWT](self).
+///
+/// A CWT can be validated using [`Validator`](crate::Validator)s in the same way
+/// as a JWT, by obtaining a reference to the underlying [`UntrustedToken`] via [`AsRef`].
+/// The header of this token only contains the key ID, provided that the COSE key ID
+/// is a valid UTF-8 string.
+#[derive(Debug, Clone)]
+pub struct UntrustedCwt {
+    header: CoseHeader,
+    token: UntrustedToken<'static>,
+}
+
+impl TryFrom<&[u8]> for UntrustedCwt {
+    type Error = ParseError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let value: Value =
+            cbor::from_slice(bytes).map_err(|_| ParseError::InvalidTokenStructure)?;
+        let value = match value {
+            Value::Tag(CWT_TAG, inner) => *inner,
+            other => other,
+        };
+        let (structure, value) = match value {
+            Value::Tag(tag, inner) => {
+                let structure =
+                    CoseStructure::from_tag(tag).ok_or(ParseError::InvalidTokenStructure)?;
+                (Some(structure), *inner)
+            }
+            other => (None, other),
+        };
+
+        let Value::Array(parts) = value else {
+            return Err(ParseError::InvalidTokenStructure);
+        };
+        let parts = <[Value; 4]>::try_from(parts).map_err(|_| ParseError::InvalidTokenStructure)?;
+        let [Value::Bytes(protected_header), Value::Map(unprotected_header), Value::Bytes(payload), Value::Bytes(signature)] =
+            parts
+        else {
+            return Err(ParseError::InvalidTokenStructure);
+        };
+
+        let parsed_header = if protected_header.is_empty() {
+            Vec::new()
+        } else {
+            match cbor::from_slice(&protected_header) {
+                Ok(Value::Map(header)) => header,
+                _ => return Err(ParseError::InvalidTokenStructure),
+            }
+        };
+        let algorithm = match get_by_label(&parsed_header, ALG_LABEL) {
+            Some(Value::Integer(id)) => algorithm_name((*id).into()),
+            Some(Value::Text(name)) => name.clone(),
+            _ => return Err(ParseError::InvalidTokenStructure),
+        };
+        let key_id = get_by_label(&parsed_header, KID_LABEL)
+            .or_else(|| get_by_label(&unprotected_header, KID_LABEL));
+        let key_id = match key_id {
+            None => None,
+            Some(Value::Bytes(key_id)) => Some(key_id.clone()),
+            Some(_) => return Err(ParseError::InvalidTokenStructure),
+        };
+        let header = CoseHeader { key_id };
+
+        let structure = structure.unwrap_or_else(|| CoseStructure::for_algorithm(&algorithm));
+        let signed_data = structure.signed_data(&protected_header, &payload);
+        let mut token_header = Header::empty();
+        token_header.key_id = header
+            .key_id
+            .as_ref()
+            .and_then(|key_id| String::from_utf8(key_id.clone()).ok());
+        let token = UntrustedToken::from_cwt_parts(
+            signed_data,
+            token_header,
+            algorithm,
+            payload,
+            signature,
+        );
+        Ok(Self { header, token })
+    }
+}
+
+impl UntrustedCwt {
+    /// Creates an untrusted CWT from bytes. This is a shortcut for calling the [`TryFrom`]
+    /// conversion.
+    pub fn new(bytes: &[u8]) -> Result<Self, ParseError> {
+        Self::try_from(bytes)
+    }
+
+    /// Gets the COSE header of this token.
+    pub fn header(&self) -> &CoseHeader {
+        &self.header
+    }
+
+    /// Gets the integrity algorithm used to secure the token. For algorithms not supported
+    /// by the crate, this is the decimal presentation of the COSE algorithm identifier.
+    pub fn algorithm(&self) -> &str {
+        self.token.algorithm()
+    }
+}
+
+impl AsRef<UntrustedToken<'static>> for UntrustedCwt {
+    fn as_ref(&self) -> &UntrustedToken<'static> {
+        &self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key, Hs384, Hs384Key},
+        AlgorithmExt, Empty, ValidationError,
+    };
+
+    fn create_token() -> (Vec<u8>, Hs256Key) {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let claims = Claims::empty().set_issuer("coap://as.example.com");
+        let header = CoseHeader::empty().with_key_id(*b"key");
+        (Hs256.cwt(&header, &claims, &key).unwrap(), key)
+    }
+
+    #[test]
+    fn claims_use_integer_keys() {
+        let claims = Claims::new(serde_json::json!({ "custom": 1 }))
+            .set_issuer("coap://as.example.com")
+            .set_jwt_id("\u{b}q");
+        let serialized = serialize_claims(&claims).unwrap();
+        let map: Value = cbor::from_slice(&serialized).unwrap();
+        let expected_map = Value::Map(Vec::from([
+            (1.into(), "coap://as.example.com".into()),
+            (7.into(), Value::Bytes(Vec::from([0x0b, 0x71]))),
+            ("custom".into(), 1.into()),
+        ]));
+        assert_eq!(map, expected_map);
+
+        let restored: Claims<serde_json::Value> = deserialize_claims(&serialized).unwrap();
+        assert_eq!(restored.issuer, claims.issuer);
+        assert_eq!(restored.jwt_id, claims.jwt_id);
+        assert_eq!(restored.custom, claims.custom);
+    }
+
+    #[test]
+    fn mac0_structure() {
+        let (token_bytes, key) = create_token();
+        // Tag 17 (`COSE_Mac0`) followed by a 4-element array.
+        assert_eq!(token_bytes[..2], [0xd1, 0x84]);
+
+        let token = UntrustedCwt::new(&token_bytes).unwrap();
+        assert_eq!(token.algorithm(), "HS256");
+        assert_eq!(token.header().key_id.as_deref(), Some(&b"key"[..]));
+        assert_eq!(token.as_ref().header().key_id.as_deref(), Some("key"));
+        let token = Hs256
+            .validator::<Empty>(&key)
+            .validate(token.as_ref())
+            .unwrap();
+        assert_eq!(
+            token.claims().issuer.as_deref(),
+            Some("coap://as.example.com")
+        );
+    }
+
+    #[test]
+    fn tampered_token() {
+        let (mut token_bytes, key) = create_token();
+        let last_byte = token_bytes.last_mut().unwrap();
+        *last_byte ^= 1;
+        let token = UntrustedCwt::new(&token_bytes).unwrap();
+        let err = Hs256
+            .validator::<Empty>(&key)
+            .validate(token.as_ref())
+            .unwrap_err();
+        assert_matches!(err, ValidationError::InvalidSignature);
+
+        let (token_bytes, key) = create_token();
+        let token = UntrustedCwt::new(&token_bytes).unwrap();
+        let err = Hs384
+            .validator::<Empty>(&Hs384Key::new(key.as_ref()))
+            .validate(token.as_ref())
+            .unwrap_err();
+        assert_matches!(err, ValidationError::AlgorithmMismatch { .. });
+    }
+
+    #[test]
+    fn parsing_errors() {
+        let (token_bytes, _) = create_token();
+        let err = UntrustedCwt::new(&token_bytes[..token_bytes.len() - 1]).unwrap_err();
+        assert_matches!(err, ParseError::InvalidTokenStructure);
+
+        let mut mangled = token_bytes.clone();
+        mangled[0] = 0xd2; // Tag 18 instead of 17
+        let token = UntrustedCwt::new(&mangled).unwrap();
+        assert_eq!(token.algorithm(), "HS256");
+
+        mangled[0] = 0xd3; // unknown tag
+        let err = UntrustedCwt::new(&mangled).unwrap_err();
+        assert_matches!(err, ParseError::InvalidTokenStructure);
+
+        let err = UntrustedCwt::new(&[0x80]).unwrap_err();
+        assert_matches!(err, ParseError::InvalidTokenStructure);
+    }
+}
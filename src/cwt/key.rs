@@ -0,0 +1,349 @@
+//! Conversions between `JsonWebKey`s and the `COSE_Key` presentation.
+
+use ciborium::Value;
+
+use crate::{
+    alg::SecretBytes,
+    alloc::{Cow, String, ToOwned, ToString, Vec},
+    cbor,
+    jwk::{JsonWebKey, JwkError, RsaPrivateParts},
+};
+
+/// Label of the `kty` COSE key parameter.
+const KTY_LABEL: i64 = 1;
+
+const KTY_OKP: i64 = 1;
+const KTY_EC2: i64 = 2;
+const KTY_RSA: i64 = 3;
+const KTY_SYMMETRIC: i64 = 4;
+
+/// COSE identifiers of elliptic curves used in `EC2` keys.
+const EC2_CURVES: &[(&str, i64)] = &[("P-256", 1), ("P-384", 2), ("P-521", 3), ("secp256k1", 8)];
+/// COSE identifiers of elliptic curves used in `OKP` keys.
+const OKP_CURVES: &[(&str, i64)] = &[("X25519", 4), ("X448", 5), ("Ed25519", 6), ("Ed448", 7)];
+
+/// Labels of `EC2` / `OKP` key parameters.
+const CRV_LABEL: i64 = -1;
+const X_LABEL: i64 = -2;
+const Y_LABEL: i64 = -3;
+const D_LABEL: i64 = -4;
+/// Label of the symmetric key value.
+const K_LABEL: i64 = -1;
+/// Labels of RSA key parameters, as per RFC 8230.
+const RSA_LABELS: [(&str, i64); 8] = [
+    ("n", -1),
+    ("e", -2),
+    ("d", -3),
+    ("p", -4),
+    ("q", -5),
+    ("dp", -6),
+    ("dq", -7),
+    ("qi", -8),
+];
+
+fn curve_id(curves: &[(&str, i64)], curve: &str) -> Result<i64, JwkError> {
+    curves
+        .iter()
+        .find_map(|&(name, id)| (name == curve).then_some(id))
+        .ok_or_else(|| JwkError::UnexpectedValue {
+            field: "crv".to_owned(),
+            expected: curve_names(curves),
+            actual: curve.to_owned(),
+        })
+}
+
+fn curve_name(curves: &[(&'static str, i64)], id: i64) -> Result<&'static str, JwkError> {
+    curves
+        .iter()
+        .find_map(|&(name, curve_id)| (curve_id == id).then_some(name))
+        .ok_or_else(|| JwkError::UnexpectedValue {
+            field: "crv".to_owned(),
+            expected: curve_names(curves),
+            actual: id.to_string(),
+        })
+}
+
+fn curve_names(curves: &[(&str, i64)]) -> String {
+    let names: Vec<_> = curves.iter().map(|(name, _)| *name).collect();
+    names.join(", ")
+}
+
+/// `COSE_Key` being built or parsed.
+struct CoseKey(Vec<(Value, Value)>);
+
+impl CoseKey {
+    fn new(key_type: i64) -> Self {
+        Self(Vec::from([(KTY_LABEL.into(), key_type.into())]))
+    }
+
+    fn insert(&mut self, label: i64, value: Value) {
+        self.0.push((label.into(), value));
+    }
+
+    fn insert_bytes(&mut self, label: i64, bytes: &[u8]) {
+        self.insert(label, Value::Bytes(bytes.to_vec()));
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        cbor::value_to_vec(Value::Map(self.0))
+    }
+
+    fn integer(&self, label: i64, field: &str) -> Result<i64, JwkError> {
+        match super::get_by_label(&self.0, label) {
+            Some(Value::Integer(value)) => i64::try_from(*value).map_err(JwkError::custom),
+            Some(_) => Err(JwkError::custom(anyhow::anyhow!(
+                "field `{field}` is not an integer"
+            ))),
+            None => Err(JwkError::NoField(field.to_owned())),
+        }
+    }
+
+    fn optional_bytes(&self, label: i64, field: &str) -> Result<Option<Vec<u8>>, JwkError> {
+        match super::get_by_label(&self.0, label) {
+            Some(Value::Bytes(bytes)) => Ok(Some(bytes.clone())),
+            Some(_) => Err(JwkError::custom(anyhow::anyhow!(
+                "field `{field}` is not a byte string"
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    fn bytes(&self, label: i64, field: &str) -> Result<Vec<u8>, JwkError> {
+        self.optional_bytes(label, field)?
+            .ok_or_else(|| JwkError::NoField(field.to_owned()))
+    }
+
+    fn secret(&self, label: i64, field: &str) -> Result<Option<SecretBytes<'static>>, JwkError> {
+        Ok(self.optional_bytes(label, field)?.map(SecretBytes::owned))
+    }
+}
+
+impl JsonWebKey<'_> {
+    /// Serializes this key into the [`COSE_Key`] presentation. Key parameters are mapped
+    /// to their COSE labels, e.g., the curve name `P-256` is encoded as `1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be represented as a `COSE_Key`, e.g.,
+    /// if it uses an unknown elliptic curve, or if it is an RSA key with more than 2 prime factors.
+    ///
+    /// [`COSE_Key`]: https://www.rfc-editor.org/rfc/rfc9052#section-7
+    #[cfg_attr(docsrs, doc(cfg(feature = "cwt")))]
+    pub fn to_cose_key(&self) -> Result<Vec<u8>, JwkError> {
+        let key = match self {
+            Self::Rsa {
+                modulus,
+                public_exponent,
+                private_parts,
+            } => {
+                let mut key = CoseKey::new(KTY_RSA);
+                key.insert_bytes(RSA_LABELS[0].1, modulus);
+                key.insert_bytes(RSA_LABELS[1].1, public_exponent);
+                if let Some(parts) = private_parts {
+                    if !parts.other_prime_factors.is_empty() {
+                        let message = "RSA keys with more than 2 prime factors are not supported";
+                        return Err(JwkError::custom(anyhow::anyhow!(message)));
+                    }
+                    let secrets = [
+                        Some(&parts.private_exponent),
+                        Some(&parts.prime_factor_p),
+                        Some(&parts.prime_factor_q),
+                        parts.p_crt_exponent.as_ref(),
+                        parts.q_crt_exponent.as_ref(),
+                        parts.q_crt_coefficient.as_ref(),
+                    ];
+                    for ((_, label), secret) in RSA_LABELS[2..].iter().zip(secrets) {
+                        if let Some(secret) = secret {
+                            key.insert_bytes(*label, secret);
+                        }
+                    }
+                }
+                key
+            }
+
+            Self::EllipticCurve {
+                curve,
+                x,
+                y,
+                secret,
+            } => {
+                let mut key = CoseKey::new(KTY_EC2);
+                key.insert(CRV_LABEL, curve_id(EC2_CURVES, curve)?.into());
+                key.insert_bytes(X_LABEL, x);
+                key.insert_bytes(Y_LABEL, y);
+                if let Some(secret) = secret {
+                    key.insert_bytes(D_LABEL, secret);
+                }
+                key
+            }
+
+            Self::Symmetric { secret } => {
+                let mut key = CoseKey::new(KTY_SYMMETRIC);
+                key.insert_bytes(K_LABEL, secret);
+                key
+            }
+
+            Self::KeyPair { curve, x, secret } => {
+                let mut key = CoseKey::new(KTY_OKP);
+                key.insert(CRV_LABEL, curve_id(OKP_CURVES, curve)?.into());
+                key.insert_bytes(X_LABEL, x);
+                if let Some(secret) = secret {
+                    key.insert_bytes(D_LABEL, secret);
+                }
+                key
+            }
+        };
+        Ok(key.into_bytes())
+    }
+}
+
+impl JsonWebKey<'static> {
+    /// Parses a key from the [`COSE_Key`] presentation. COSE key parameters not having
+    /// a JWK equivalent (e.g., `kid` or `alg`) are ignored.
+    ///
+    /// [`COSE_Key`]: https://www.rfc-editor.org/rfc/rfc9052#section-7
+    #[cfg_attr(docsrs, doc(cfg(feature = "cwt")))]
+    pub fn from_cose_key(bytes: &[u8]) -> Result<Self, JwkError> {
+        let key = match cbor::from_slice(bytes).map_err(JwkError::custom)? {
+            Value::Map(map) => CoseKey(map),
+            _ => return Err(JwkError::custom(anyhow::anyhow!("COSE_Key is not a map"))),
+        };
+
+        match key.integer(KTY_LABEL, "kty")? {
+            KTY_RSA => {
+                let [modulus, public_exponent, private_exponent, p, q, dp, dq, qi] =
+                    RSA_LABELS.map(|(field, label)| key.optional_bytes(label, field));
+                let modulus = modulus?.ok_or_else(|| JwkError::NoField("n".to_owned()))?;
+                let public_exponent =
+                    public_exponent?.ok_or_else(|| JwkError::NoField("e".to_owned()))?;
+                let private_parts = if let Some(private_exponent) = private_exponent? {
+                    let p = p?.ok_or_else(|| JwkError::NoField("p".to_owned()))?;
+                    let q = q?.ok_or_else(|| JwkError::NoField("q".to_owned()))?;
+                    Some(RsaPrivateParts {
+                        private_exponent: SecretBytes::owned(private_exponent),
+                        prime_factor_p: SecretBytes::owned(p),
+                        prime_factor_q: SecretBytes::owned(q),
+                        p_crt_exponent: dp?.map(SecretBytes::owned),
+                        q_crt_exponent: dq?.map(SecretBytes::owned),
+                        q_crt_coefficient: qi?.map(SecretBytes::owned),
+                        other_prime_factors: Vec::new(),
+                    })
+                } else {
+                    None
+                };
+                Ok(Self::Rsa {
+                    modulus: Cow::Owned(modulus),
+                    public_exponent: Cow::Owned(public_exponent),
+                    private_parts,
+                })
+            }
+
+            KTY_EC2 => Ok(Self::EllipticCurve {
+                curve: Cow::Borrowed(curve_name(EC2_CURVES, key.integer(CRV_LABEL, "crv")?)?),
+                x: Cow::Owned(key.bytes(X_LABEL, "x")?),
+                y: Cow::Owned(key.bytes(Y_LABEL, "y")?),
+                secret: key.secret(D_LABEL, "d")?,
+            }),
+
+            KTY_SYMMETRIC => Ok(Self::Symmetric {
+                secret: SecretBytes::owned(key.bytes(K_LABEL, "k")?),
+            }),
+
+            KTY_OKP => Ok(Self::KeyPair {
+                curve: Cow::Borrowed(curve_name(OKP_CURVES, key.integer(CRV_LABEL, "crv")?)?),
+                x: Cow::Owned(key.bytes(X_LABEL, "x")?),
+                secret: key.secret(D_LABEL, "d")?,
+            }),
+
+            other => Err(JwkError::UnexpectedValue {
+                field: "kty".to_owned(),
+                expected: "1, 2, 3 or 4".to_owned(),
+                actual: other.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    fn assert_roundtrip(jwk: &JsonWebKey<'_>) {
+        let cose_key = jwk.to_cose_key().unwrap();
+        let restored = JsonWebKey::from_cose_key(&cose_key).unwrap();
+        assert_eq!(restored, *jwk);
+    }
+
+    #[test]
+    fn symmetric_key() {
+        let jwk = JsonWebKey::Symmetric {
+            secret: SecretBytes::borrowed(b"super_secret_key_donut_steel"),
+        };
+        let cose_key = jwk.to_cose_key().unwrap();
+        // {1: 4, -1: h'...'}
+        assert_eq!(cose_key[..5], [0xa2, 0x01, 0x04, 0x20, 0x58]);
+        assert_roundtrip(&jwk);
+    }
+
+    #[test]
+    fn elliptic_curve_keys() {
+        let jwk = JsonWebKey::EllipticCurve {
+            curve: Cow::Borrowed("P-256"),
+            x: Cow::Borrowed(&[1; 32]),
+            y: Cow::Borrowed(&[2; 32]),
+            secret: Some(SecretBytes::borrowed(&[3; 32])),
+        };
+        assert_roundtrip(&jwk);
+        assert_roundtrip(&jwk.to_verifying_key());
+
+        let jwk = JsonWebKey::KeyPair {
+            curve: Cow::Borrowed("Ed25519"),
+            x: Cow::Borrowed(&[1; 32]),
+            secret: None,
+        };
+        assert_roundtrip(&jwk);
+
+        let jwk = JsonWebKey::KeyPair {
+            curve: Cow::Borrowed("Curve448"),
+            x: Cow::Borrowed(&[1; 32]),
+            secret: None,
+        };
+        let err = jwk.to_cose_key().unwrap_err();
+        assert_matches!(err, JwkError::UnexpectedValue { field, .. } if field == "crv");
+    }
+
+    #[test]
+    fn rsa_key() {
+        let jwk = JsonWebKey::Rsa {
+            modulus: Cow::Borrowed(&[0xc5; 64]),
+            public_exponent: Cow::Borrowed(&[1, 0, 1]),
+            private_parts: Some(RsaPrivateParts {
+                private_exponent: SecretBytes::borrowed(&[1; 64]),
+                prime_factor_p: SecretBytes::borrowed(&[2; 32]),
+                prime_factor_q: SecretBytes::borrowed(&[3; 32]),
+                p_crt_exponent: None,
+                q_crt_exponent: None,
+                q_crt_coefficient: Some(SecretBytes::borrowed(&[4; 32])),
+                other_prime_factors: Vec::new(),
+            }),
+        };
+        assert_roundtrip(&jwk);
+        assert_roundtrip(&jwk.to_verifying_key());
+    }
+
+    #[test]
+    fn parsing_errors() {
+        let err = JsonWebKey::from_cose_key(&[0x80]).unwrap_err();
+        assert_matches!(err, JwkError::Custom(_));
+
+        // {1: 4}
+        let err = JsonWebKey::from_cose_key(&[0xa1, 0x01, 0x04]).unwrap_err();
+        assert_matches!(err, JwkError::NoField(field) if field == "k");
+
+        // {1: 9}
+        let err = JsonWebKey::from_cose_key(&[0xa1, 0x01, 0x09]).unwrap_err();
+        assert_matches!(err, JwkError::UnexpectedValue { field, .. } if field == "kty");
+    }
+}
@@ -0,0 +1,876 @@
+//! [Demonstrating Proof of Possession][DPoP] (`DPoP`) proofs.
+//!
+//! A `DPoP` proof is a token signed by the client with an asymmetric key, which public part
+//! is embedded into the proof header (the `jwk` field). The proof is bound to an HTTP request
+//! via the [`DpopClaims`], and optionally to an access token issued for the key
+//! (the access token then contains the key thumbprint in the [confirmation](Confirmation)
+//! claim).
+//!
+//! - [`create_proof()`] creates a proof from an asymmetric signing key.
+//! - [`DpopValidator`] validates proofs as per [RFC 9449][validation], using
+//!   a [`ReplayStore`] to detect reused proofs.
+//!
+//! [DPoP]: https://www.rfc-editor.org/rfc/rfc9449.html
+//! [validation]: https://www.rfc-editor.org/rfc/rfc9449.html#section-4.3
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "p256")]
+//! # fn main() -> anyhow::Result<()> {
+//! # use chrono::Duration;
+//! use jwt_compact::{
+//!     alg::Es256,
+//!     dpop::{self, DpopClaims, DpopValidator, MemoryReplayStore},
+//!     prelude::*,
+//! };
+//!
+//! let time_options = TimeOptions::default();
+//! let access_token = "Kz~8mXK1EalYznwH-LC-1fBAo.4Ljp~zsPE_NeO.gxU";
+//! // Proof creation is performed by the client.
+//! let signing_key = // ...
+//! #   p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+//! let claims = DpopClaims::new("GET", "https://resource.example.org/protected")
+//!     .set_access_token(access_token);
+//! let claims = Claims::new(claims)
+//!     .set_jwt_id("e1j3V_bKic8-LAEB")
+//!     .set_duration_and_issuance(&time_options, Duration::minutes(1));
+//! let proof = dpop::create_proof(&Es256, &claims, &signing_key)?;
+//!
+//! // Validation is performed by the resource server. The key thumbprint
+//! // is obtained from the `cnf` claim of the access token.
+//! let key_thumbprint = dpop::key_thumbprint(&(&signing_key).into());
+//! let validator =
+//!     DpopValidator::new("GET", "https://resource.example.org/protected", time_options);
+//! let validator = validator.with_access_token(access_token, key_thumbprint);
+//! let replay_store = MemoryReplayStore::default();
+//! let proof = UntrustedToken::try_from(proof.as_str())?;
+//! validator.validate(&Es256, &proof, &replay_store)?;
+//! // The proof cannot be reused.
+//! assert!(validator.validate(&Es256, &proof, &replay_store).is_err());
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "p256"))]
+//! # fn main() {}
+//! ```
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use core::fmt;
+
+use crate::{
+    alloc::{String, ToOwned},
+    jwk::{JsonWebKey, JwkError, KeyType},
+    Algorithm, AlgorithmExt, Claim, Claims, CreationError, Header, TimeOptions, Token,
+    UntrustedToken, ValidationError,
+};
+
+/// Value of the `typ` header field for `DPoP` proofs.
+pub const DPOP_TOKEN_TYPE: &str = "dpop+jwt";
+
+/// Header fields of `DPoP` proofs, used as custom fields in [`Header`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct DpopHeaderFields<'a> {
+    /// Public key of the client, which the proof is signed with.
+    pub jwk: JsonWebKey<'a>,
+}
+
+/// Claims specific to `DPoP` proofs, used as custom claims in [`Claims`]. Proofs must also
+/// contain the `jti` and `iat` registered claims.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct DpopClaims {
+    /// HTTP method of the request to which the proof is attached.
+    #[serde(rename = "htm")]
+    pub http_method: String,
+
+    /// HTTP URI of the request to which the proof is attached, without query and fragment parts.
+    #[serde(rename = "htu")]
+    pub http_uri: String,
+
+    /// Hash of the access token presented together with the proof;
+    /// see [`access_token_hash()`].
+    #[serde(rename = "ath", default, skip_serializing_if = "Option::is_none")]
+    pub access_token_hash: Option<String>,
+
+    /// Nonce provided by the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+impl DpopClaims {
+    /// Creates claims for the request with the specified HTTP method and URI.
+    pub fn new(http_method: impl Into<String>, http_uri: impl Into<String>) -> Self {
+        Self {
+            http_method: http_method.into(),
+            http_uri: http_uri.into(),
+            access_token_hash: None,
+            nonce: None,
+        }
+    }
+
+    /// Sets the `ath` claim by hashing the provided `access_token`.
+    #[must_use]
+    pub fn set_access_token(self, access_token: &str) -> Self {
+        Self {
+            access_token_hash: Some(access_token_hash(access_token)),
+            ..self
+        }
+    }
+
+    /// Sets the `nonce` claim.
+    #[must_use]
+    pub fn set_nonce(self, nonce: impl Into<String>) -> Self {
+        Self {
+            nonce: Some(nonce.into()),
+            ..self
+        }
+    }
+}
+
+/// Confirmation claim (`cnf`) binding an access token to the `DPoP` key as per [RFC 9449].
+///
+/// [RFC 9449]: https://www.rfc-editor.org/rfc/rfc9449.html#section-6.1
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Confirmation {
+    /// SHA-256 thumbprint of the key; see [`key_thumbprint()`].
+    #[serde(rename = "jkt", default, skip_serializing_if = "Option::is_none")]
+    pub key_thumbprint: Option<String>,
+}
+
+impl Confirmation {
+    /// Creates a confirmation for the specified key.
+    pub fn for_key(key: &JsonWebKey<'_>) -> Self {
+        Self {
+            key_thumbprint: Some(key_thumbprint(key)),
+        }
+    }
+}
+
+/// Computes the hash of an access token as used in the `ath` claim: the SHA-256 digest
+/// of the token is base64url-encoded.
+pub fn access_token_hash(access_token: &str) -> String {
+    Base64UrlUnpadded::encode_string(&Sha256::digest(access_token.as_bytes()))
+}
+
+/// Computes the [RFC 7638] thumbprint of the key as used in the `jkt` confirmation claim:
+/// the SHA-256 thumbprint is base64url-encoded.
+///
+/// [RFC 7638]: https://www.rfc-editor.org/rfc/rfc7638.html
+pub fn key_thumbprint(key: &JsonWebKey<'_>) -> String {
+    Base64UrlUnpadded::encode_string(&key.thumbprint::<Sha256>())
+}
+
+/// Creates a `DPoP` proof with the specified `claims`. The public part of the signing key
+/// is embedded into the proof header, and the `typ` header field is set
+/// to [`DPOP_TOKEN_TYPE`].
+///
+/// # Errors
+///
+/// Returns [`CreationError::SymmetricKey`] if the signing key is symmetric. Other errors
+/// are the same as for [`AlgorithmExt::token()`].
+pub fn create_proof<A>(
+    algorithm: &A,
+    claims: &Claims<DpopClaims>,
+    signing_key: &A::SigningKey,
+) -> Result<String, CreationError>
+where
+    A: Algorithm,
+    for<'a> &'a A::SigningKey: Into<JsonWebKey<'a>>,
+{
+    let jwk: JsonWebKey<'_> = signing_key.into();
+    if jwk.key_type() == KeyType::Symmetric {
+        return Err(CreationError::SymmetricKey);
+    }
+    let header = Header::new(DpopHeaderFields {
+        jwk: jwk.to_verifying_key(),
+    });
+    let header = header.with_token_type(DPOP_TOKEN_TYPE);
+    algorithm.token(&header, claims, signing_key)
+}
+
+/// Storage of `DPoP` proof IDs (`jti` claims) used to detect replayed proofs.
+///
+/// [`MemoryReplayStore`] provides a simple in-memory implementation. Applications
+/// with multiple server instances may implement the trait for a shared storage.
+pub trait ReplayStore {
+    /// Records the proof ID. The record needs to be retained until `expires_at`; after that,
+    /// the proof is rejected by [`DpopValidator`] as too old. `now` is the current time
+    /// according to the validator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the storage cannot be accessed.
+    ///
+    /// Returns `Ok(false)` if the ID was already recorded (i.e., the proof is replayed),
+    /// and `Ok(true)` otherwise.
+    fn record(
+        &self,
+        jwt_id: &str,
+        expires_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<bool, anyhow::Error>;
+}
+
+/// In-memory [`ReplayStore`]. Expired records are removed when recording new IDs.
+///
+/// This type is supported on **crate feature `std`** only.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Default)]
+pub struct MemoryReplayStore {
+    records: std::sync::Mutex<std::collections::HashMap<String, DateTime<Utc>>>,
+}
+
+#[cfg(feature = "std")]
+impl MemoryReplayStore {
+    /// Returns the number of retained records.
+    pub fn len(&self) -> usize {
+        self.records
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .len()
+    }
+
+    /// Checks whether this store has no retained records.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(feature = "std")]
+impl ReplayStore for MemoryReplayStore {
+    fn record(
+        &self,
+        jwt_id: &str,
+        expires_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<bool, anyhow::Error> {
+        let mut records = self
+            .records
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        records.retain(|_, record_expiration| *record_expiration >= now);
+        if records.contains_key(jwt_id) {
+            Ok(false)
+        } else {
+            records.insert(jwt_id.to_owned(), expires_at);
+            Ok(true)
+        }
+    }
+}
+
+/// Identifier of a `DPoP` claim in [`DpopClaims`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DpopClaim {
+    /// `ath` claim (access token hash).
+    AccessTokenHash,
+    /// `nonce` claim.
+    Nonce,
+}
+
+impl fmt::Display for DpopClaim {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::AccessTokenHash => "ath",
+            Self::Nonce => "nonce",
+        })
+    }
+}
+
+/// Errors that can occur during [`DPoP` proof validation](DpopValidator).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DpopError {
+    /// Proof integrity, the proof type or one of the registered claims (`iat` or `jti`)
+    /// has failed validation.
+    Validation(ValidationError),
+    /// Key embedded into the proof header is symmetric.
+    SymmetricKey,
+    /// Key embedded into the proof header contains private key parts.
+    PrivateKey,
+    /// Key embedded into the proof header cannot be converted into a verifying key
+    /// for the algorithm.
+    Key(JwkError),
+    /// Claim required for validation is not present in the proof.
+    NoClaim(DpopClaim),
+    /// HTTP method in the proof (`htm` claim) differs from the method of the request.
+    HttpMethodMismatch {
+        /// Method of the request.
+        expected: String,
+        /// Method in the proof.
+        actual: String,
+    },
+    /// HTTP URI in the proof (`htu` claim) differs from the URI of the request.
+    HttpUriMismatch {
+        /// URI of the request.
+        expected: String,
+        /// URI in the proof.
+        actual: String,
+    },
+    /// Server nonce in the proof (`nonce` claim) differs from the expected one.
+    NonceMismatch,
+    /// Proof issuance time (`iat` claim) is in the future.
+    IssuedInFuture,
+    /// Access token hash in the proof (`ath` claim) does not match the access token.
+    AccessTokenHashMismatch,
+    /// Thumbprint of the key embedded into the proof header does not match
+    /// the thumbprint bound to the access token.
+    KeyThumbprintMismatch,
+    /// Proof with the same ID (`jti` claim) was already used.
+    Replayed,
+    /// [`ReplayStore`] has failed to record the proof ID.
+    ReplayStore(anyhow::Error),
+}
+
+impl From<ValidationError> for DpopError {
+    fn from(err: ValidationError) -> Self {
+        Self::Validation(err)
+    }
+}
+
+impl fmt::Display for DpopError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Validation(err) => fmt::Display::fmt(err, formatter),
+            Self::SymmetricKey => formatter.write_str("key in proof header is symmetric"),
+            Self::PrivateKey => formatter.write_str("key in proof header contains private parts"),
+            Self::Key(err) => write!(formatter, "invalid key in proof header: {err}"),
+            Self::NoClaim(claim) => write!(
+                formatter,
+                "claim `{claim}` requested during validation is not present in the proof"
+            ),
+            Self::HttpMethodMismatch { expected, actual } => write!(
+                formatter,
+                "HTTP method in proof ({actual}) differs from expected ({expected})"
+            ),
+            Self::HttpUriMismatch { expected, actual } => write!(
+                formatter,
+                "HTTP URI in proof ({actual}) differs from expected ({expected})"
+            ),
+            Self::NonceMismatch => formatter.write_str("proof nonce differs from expected"),
+            Self::IssuedInFuture => formatter.write_str("proof is issued in the future"),
+            Self::AccessTokenHashMismatch => {
+                formatter.write_str("access token hash in proof does not match access token")
+            }
+            Self::KeyThumbprintMismatch => {
+                formatter.write_str("proof key does not match key bound to access token")
+            }
+            Self::Replayed => formatter.write_str("proof has already been used"),
+            Self::ReplayStore(err) => write!(formatter, "cannot record proof ID: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DpopError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            // `Display` delegates to the error, so we need to do the same with `source()`.
+            Self::Validation(err) => err.source(),
+            Self::Key(err) => Some(err),
+            Self::ReplayStore(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Validator of `DPoP` proofs for a specific HTTP request performing checks
+/// from [RFC 9449][validation].
+///
+/// The following checks are performed:
+///
+/// - The proof type (the `typ` header field) is [`DPOP_TOKEN_TYPE`].
+/// - The key in the proof header (the `jwk` field) is an asymmetric public key,
+///   and the proof signature is valid for this key.
+/// - The proof HTTP method and URI (`htm` and `htu` claims) match the request. Query
+///   and fragment parts of URIs are ignored.
+/// - If the [server nonce](Self::with_nonce()) is set, the `nonce` claim is present
+///   and is equal to it.
+/// - The proof contains the `iat` claim, and is not older than the
+///   [maximum age](Self::with_max_age()) (subject to the leeway and the clock
+///   from [`TimeOptions`]).
+/// - If the [access token](Self::with_access_token()) is set, the `ath` claim matches it,
+///   and the key in the proof header matches the key thumbprint bound to the access token.
+/// - The proof contains the `jti` claim, and the [`ReplayStore`] has not seen it before.
+///
+/// Checks are performed in the specified order; the first failed check is reported
+/// as a [`DpopError`].
+///
+/// See the [module-level docs](self) for an example of usage.
+///
+/// [validation]: https://www.rfc-editor.org/rfc/rfc9449.html#section-4.3
+#[derive(Debug, Clone)]
+pub struct DpopValidator<F = fn() -> DateTime<Utc>> {
+    time_options: TimeOptions<F>,
+    max_age: Duration,
+    http_method: String,
+    http_uri: String,
+    nonce: Option<String>,
+    access_token: Option<(String, String)>,
+}
+
+impl<F: Fn() -> DateTime<Utc>> DpopValidator<F> {
+    /// Creates a validator for proofs attached to the request with the specified HTTP
+    /// method and URI. The maximum proof age is set to 5 minutes.
+    pub fn new(
+        http_method: impl Into<String>,
+        http_uri: impl Into<String>,
+        time_options: TimeOptions<F>,
+    ) -> Self {
+        Self {
+            time_options,
+            max_age: Duration::minutes(5),
+            http_method: http_method.into(),
+            http_uri: http_uri.into(),
+            nonce: None,
+            access_token: None,
+        }
+    }
+
+    /// Sets the maximum age of proofs.
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Sets the nonce provided by the server. If set, the proof must contain the `nonce` claim
+    /// equal to it.
+    #[must_use]
+    pub fn with_nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Sets the access token presented together with the proof, and the key thumbprint bound
+    /// to it (e.g., the `jkt` field of the [confirmation](Confirmation) claim). If set,
+    /// the proof must contain the `ath` claim matching the access token, and must be signed
+    /// by the key with the specified thumbprint.
+    #[must_use]
+    pub fn with_access_token(
+        mut self,
+        access_token: impl Into<String>,
+        key_thumbprint: impl Into<String>,
+    ) -> Self {
+        self.access_token = Some((access_token.into(), key_thumbprint.into()));
+        self
+    }
+
+    /// Validates the `proof` signed with the specified `algorithm`. If all other checks pass,
+    /// the proof ID is recorded in the `replay_store`.
+    pub fn validate<A, R>(
+        &self,
+        algorithm: &A,
+        proof: &UntrustedToken<'_, DpopHeaderFields<'static>>,
+        replay_store: &R,
+    ) -> Result<Token<DpopClaims, DpopHeaderFields<'static>>, DpopError>
+    where
+        A: Algorithm,
+        A::VerifyingKey: for<'jwk> TryFrom<&'jwk JsonWebKey<'jwk>, Error = JwkError>,
+        R: ReplayStore + ?Sized,
+    {
+        let header = proof.header();
+        check_token_type(header)?;
+        let jwk = &header.other_fields.jwk;
+        if jwk.key_type() == KeyType::Symmetric {
+            return Err(DpopError::SymmetricKey);
+        }
+        if jwk.is_signing_key() {
+            return Err(DpopError::PrivateKey);
+        }
+        let verifying_key = A::VerifyingKey::try_from(jwk).map_err(DpopError::Key)?;
+        let proof = algorithm
+            .validator::<DpopClaims>(&verifying_key)
+            .validate(proof)?;
+
+        let claims = proof.claims();
+        self.check_request(&claims.custom)?;
+        let expires_at = self.check_issuance(claims)?;
+        if let Some((access_token, key_thumbprint)) = &self.access_token {
+            let hash = claims
+                .custom
+                .access_token_hash
+                .as_ref()
+                .ok_or(DpopError::NoClaim(DpopClaim::AccessTokenHash))?;
+            if *hash != access_token_hash(access_token) {
+                return Err(DpopError::AccessTokenHashMismatch);
+            }
+            if *key_thumbprint != self::key_thumbprint(&proof.header().other_fields.jwk) {
+                return Err(DpopError::KeyThumbprintMismatch);
+            }
+        }
+
+        let jwt_id = claims
+            .jwt_id
+            .as_deref()
+            .ok_or(ValidationError::NoClaim(Claim::JwtId))?;
+        let now = (self.time_options.clock_fn)();
+        let is_new = replay_store
+            .record(jwt_id, expires_at, now)
+            .map_err(DpopError::ReplayStore)?;
+        if is_new {
+            Ok(proof)
+        } else {
+            Err(DpopError::Replayed)
+        }
+    }
+
+    fn check_request(&self, claims: &DpopClaims) -> Result<(), DpopError> {
+        if claims.http_method != self.http_method {
+            return Err(DpopError::HttpMethodMismatch {
+                expected: self.http_method.clone(),
+                actual: claims.http_method.clone(),
+            });
+        }
+        if strip_query(&claims.http_uri) != strip_query(&self.http_uri) {
+            return Err(DpopError::HttpUriMismatch {
+                expected: self.http_uri.clone(),
+                actual: claims.http_uri.clone(),
+            });
+        }
+
+        if let Some(expected) = &self.nonce {
+            let nonce = claims
+                .nonce
+                .as_ref()
+                .ok_or(DpopError::NoClaim(DpopClaim::Nonce))?;
+            if nonce != expected {
+                return Err(DpopError::NonceMismatch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the proof issuance time and returns the time until which the proof is valid.
+    fn check_issuance(&self, claims: &Claims<DpopClaims>) -> Result<DateTime<Utc>, DpopError> {
+        let issued_at = claims
+            .issued_at
+            .ok_or(ValidationError::NoClaim(Claim::IssuedAt))?;
+        let now = (self.time_options.clock_fn)();
+        let leeway = self.time_options.leeway;
+        // Overflow can only happen for the timestamps in the distant past, which are not
+        // in the future by definition.
+        let is_in_future = issued_at
+            .checked_sub_signed(leeway)
+            .map_or(false, |issued_at| issued_at > now);
+        if is_in_future {
+            return Err(DpopError::IssuedInFuture);
+        }
+        let expires_at = self
+            .max_age
+            .checked_add(&leeway)
+            .and_then(|max_age_with_leeway| issued_at.checked_add_signed(max_age_with_leeway))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        if now > expires_at {
+            Err(ValidationError::TooOld.into())
+        } else {
+            Ok(expires_at)
+        }
+    }
+}
+
+fn check_token_type<H>(header: &Header<H>) -> Result<(), ValidationError> {
+    let token_type = header.token_type.as_deref();
+    if token_type.map_or(false, |ty| ty.eq_ignore_ascii_case(DPOP_TOKEN_TYPE)) {
+        Ok(())
+    } else {
+        Err(ValidationError::UnexpectedTokenType {
+            expected: DPOP_TOKEN_TYPE.to_owned(),
+            actual: token_type.map(ToOwned::to_owned),
+        })
+    }
+}
+
+fn strip_query(uri: &str) -> &str {
+    uri.split(['?', '#']).next().unwrap_or(uri)
+}
+
+#[cfg(all(test, feature = "std", feature = "clock"))]
+mod tests {
+    use super::*;
+    use crate::alg::{Hs256, Hs256Key};
+
+    use assert_matches::assert_matches;
+
+    const ACCESS_TOKEN: &str = "Kz~8mXK1EalYznwH-LC-1fBAo.4Ljp~zsPE_NeO.gxU";
+    const URI: &str = "https://resource.example.org/protected";
+
+    #[test]
+    fn computing_hashes() {
+        // Example from RFC 9449, Section 7.1.
+        assert_eq!(
+            access_token_hash(ACCESS_TOKEN),
+            "fUHyO2r2Z3DZ53EsNrWBb0xWXoaNy59IiKCAqksmQEo"
+        );
+        // Example from RFC 7638, Section 3.1.
+        let key = serde_json::json!({
+            "kty": "RSA",
+            "n": concat!(
+                "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aP",
+                "FFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl9",
+                "3lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdA",
+                "ZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3",
+                "XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+            ),
+            "e": "AQAB",
+            "alg": "RS256",
+            "kid": "2011-04-29",
+        });
+        let key: JsonWebKey<'_> = serde_json::from_value(key).unwrap();
+        assert_eq!(
+            key_thumbprint(&key),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+        assert_eq!(
+            Confirmation::for_key(&key).key_thumbprint.as_deref(),
+            Some("NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs")
+        );
+    }
+
+    #[test]
+    fn stripping_query() {
+        assert_eq!(strip_query(URI), URI);
+        assert_eq!(
+            strip_query("https://example.com/path?query=1#frag"),
+            "https://example.com/path"
+        );
+        assert_eq!(
+            strip_query("https://example.com/path#frag"),
+            "https://example.com/path"
+        );
+    }
+
+    #[test]
+    fn symmetric_keys_are_rejected() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let claims = Claims::new(DpopClaims::new("GET", URI));
+        let err = create_proof(&Hs256, &claims, &key).unwrap_err();
+        assert_matches!(err, CreationError::SymmetricKey);
+
+        // Craft a proof with the symmetric key manually.
+        let header = Header::new(DpopHeaderFields {
+            jwk: JsonWebKey::from(&key),
+        })
+        .with_token_type(DPOP_TOKEN_TYPE);
+        let proof = Hs256.token(&header, &claims, &key).unwrap();
+        let proof = UntrustedToken::try_from(proof.as_str()).unwrap();
+        let validator = DpopValidator::new("GET", URI, TimeOptions::default());
+        let err = validator
+            .validate(&Hs256, &proof, &MemoryReplayStore::default())
+            .unwrap_err();
+        assert_matches!(err, DpopError::SymmetricKey);
+    }
+
+    #[test]
+    fn memory_replay_store() {
+        let store = MemoryReplayStore::default();
+        let now = Utc::now();
+        assert!(store.record("1", now + Duration::minutes(1), now).unwrap());
+        assert!(store.record("2", now + Duration::minutes(5), now).unwrap());
+        assert!(!store.record("1", now + Duration::minutes(1), now).unwrap());
+        assert_eq!(store.len(), 2);
+
+        let now = now + Duration::minutes(2);
+        assert!(store.record("3", now + Duration::minutes(1), now).unwrap());
+        assert_eq!(store.len(), 2);
+        assert!(store.record("1", now + Duration::minutes(1), now).unwrap());
+    }
+
+    #[cfg(feature = "p256")]
+    mod es256 {
+        use super::*;
+        use crate::{
+            alg::Es256,
+            alloc::{format, ToString},
+        };
+
+        type SigningKey = <Es256 as Algorithm>::SigningKey;
+
+        fn signing_key() -> SigningKey {
+            SigningKey::from_slice(&[1; 32]).unwrap()
+        }
+
+        fn create_claims(claims: DpopClaims) -> Claims<DpopClaims> {
+            Claims::new(claims)
+                .set_jwt_id("e1j3V_bKic8-LAEB")
+                .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(1))
+        }
+
+        fn validate(
+            validator: &DpopValidator,
+            claims: &Claims<DpopClaims>,
+            replay_store: &MemoryReplayStore,
+        ) -> Result<Token<DpopClaims, DpopHeaderFields<'static>>, DpopError> {
+            let proof = create_proof(&Es256, claims, &signing_key()).unwrap();
+            let proof = UntrustedToken::try_from(proof.as_str()).unwrap();
+            validator.validate(&Es256, &proof, replay_store)
+        }
+
+        fn thumbprint() -> String {
+            key_thumbprint(&JsonWebKey::from(&signing_key()))
+        }
+
+        #[test]
+        fn creating_proof() {
+            let claims = create_claims(DpopClaims::new("GET", URI).set_nonce("nonce"));
+            let proof = create_proof(&Es256, &claims, &signing_key()).unwrap();
+            let proof =
+                UntrustedToken::<DpopHeaderFields<'static>>::try_from(proof.as_str()).unwrap();
+
+            let header = proof.header();
+            assert_eq!(header.token_type.as_deref(), Some(DPOP_TOKEN_TYPE));
+            let jwk = &header.other_fields.jwk;
+            // The private key must not be included into the proof.
+            assert!(!jwk.is_signing_key());
+            assert_eq!(key_thumbprint(jwk), thumbprint());
+
+            let claims = proof.deserialize_claims_unchecked::<DpopClaims>().unwrap();
+            assert_eq!(claims.custom.http_method, "GET");
+            assert_eq!(claims.custom.nonce.as_deref(), Some("nonce"));
+        }
+
+        #[test]
+        fn successful_validation() {
+            let claims = create_claims(
+                DpopClaims::new("POST", URI)
+                    .set_access_token(ACCESS_TOKEN)
+                    .set_nonce("nonce"),
+            );
+            let validator =
+                DpopValidator::new("POST", format!("{URI}?query=1"), TimeOptions::default())
+                    .with_nonce("nonce")
+                    .with_access_token(ACCESS_TOKEN, thumbprint());
+            let store = MemoryReplayStore::default();
+            let proof = validate(&validator, &claims, &store).unwrap();
+            assert_eq!(proof.claims().jwt_id.as_deref(), Some("e1j3V_bKic8-LAEB"));
+
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::Replayed);
+            assert_eq!(err.to_string(), "proof has already been used");
+        }
+
+        #[test]
+        fn request_errors() {
+            let store = MemoryReplayStore::default();
+            let validator = DpopValidator::new("POST", URI, TimeOptions::default());
+            let claims = create_claims(DpopClaims::new("GET", URI));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(
+                err,
+                DpopError::HttpMethodMismatch { actual, .. } if actual == "GET"
+            );
+
+            let claims = create_claims(DpopClaims::new("POST", "https://example.com/"));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::HttpUriMismatch { .. });
+
+            let validator = validator.with_nonce("nonce");
+            let claims = create_claims(DpopClaims::new("POST", URI));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::NoClaim(DpopClaim::Nonce));
+            let claims = create_claims(DpopClaims::new("POST", URI).set_nonce("other"));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::NonceMismatch);
+            // Failed proofs are not recorded.
+            assert!(store.is_empty());
+        }
+
+        #[test]
+        fn issuance_errors() {
+            let store = MemoryReplayStore::default();
+            let validator = DpopValidator::new("GET", URI, TimeOptions::default());
+            let mut claims = create_claims(DpopClaims::new("GET", URI));
+            claims.issued_at = Some(Utc::now() - Duration::hours(1));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::Validation(ValidationError::TooOld));
+
+            claims.issued_at = Some(Utc::now() + Duration::hours(1));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::IssuedInFuture);
+
+            // Extreme timestamps must not lead to a panic.
+            claims.issued_at = Some(DateTime::<Utc>::MIN_UTC);
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::Validation(ValidationError::TooOld));
+            claims.issued_at = Some(DateTime::<Utc>::MAX_UTC);
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::IssuedInFuture);
+
+            claims.issued_at = None;
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(
+                err,
+                DpopError::Validation(ValidationError::NoClaim(Claim::IssuedAt))
+            );
+        }
+
+        #[test]
+        fn access_token_binding_errors() {
+            let store = MemoryReplayStore::default();
+            let validator = DpopValidator::new("GET", URI, TimeOptions::default())
+                .with_access_token(ACCESS_TOKEN, thumbprint());
+            let claims = create_claims(DpopClaims::new("GET", URI));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::NoClaim(DpopClaim::AccessTokenHash));
+
+            let claims = create_claims(DpopClaims::new("GET", URI).set_access_token("other"));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::AccessTokenHashMismatch);
+
+            let validator = DpopValidator::new("GET", URI, TimeOptions::default())
+                .with_access_token(ACCESS_TOKEN, "other_thumbprint");
+            let claims = create_claims(DpopClaims::new("GET", URI).set_access_token(ACCESS_TOKEN));
+            let err = validate(&validator, &claims, &store).unwrap_err();
+            assert_matches!(err, DpopError::KeyThumbprintMismatch);
+        }
+
+        #[test]
+        fn header_errors() {
+            let key = signing_key();
+            let claims = create_claims(DpopClaims::new("GET", URI));
+            let validator = DpopValidator::new("GET", URI, TimeOptions::default());
+            let store = MemoryReplayStore::default();
+
+            let header = Header::new(DpopHeaderFields {
+                jwk: JsonWebKey::from(&key).to_verifying_key(),
+            });
+            let proof = Es256.token(&header, &claims, &key).unwrap();
+            let proof = UntrustedToken::try_from(proof.as_str()).unwrap();
+            let err = validator.validate(&Es256, &proof, &store).unwrap_err();
+            assert_matches!(
+                err,
+                DpopError::Validation(ValidationError::UnexpectedTokenType { actual: None, .. })
+            );
+
+            let header = Header::new(DpopHeaderFields {
+                jwk: JsonWebKey::from(&key),
+            })
+            .with_token_type(DPOP_TOKEN_TYPE);
+            let proof = Es256.token(&header, &claims, &key).unwrap();
+            let proof = UntrustedToken::try_from(proof.as_str()).unwrap();
+            let err = validator.validate(&Es256, &proof, &store).unwrap_err();
+            assert_matches!(err, DpopError::PrivateKey);
+
+            // Proof signed by a key different from the one in the header.
+            let other_key = SigningKey::from_slice(&[2; 32]).unwrap();
+            let header = Header::new(DpopHeaderFields {
+                jwk: JsonWebKey::from(&key).to_verifying_key(),
+            })
+            .with_token_type(DPOP_TOKEN_TYPE);
+            let proof = Es256.token(&header, &claims, &other_key).unwrap();
+            let proof = UntrustedToken::try_from(proof.as_str()).unwrap();
+            let err = validator.validate(&Es256, &proof, &store).unwrap_err();
+            assert_matches!(
+                err,
+                DpopError::Validation(ValidationError::InvalidSignature)
+            );
+        }
+    }
+}
@@ -2,7 +2,7 @@
 
 use core::fmt;
 
-use crate::alloc::String;
+use crate::alloc::{String, Vec};
 
 /// Errors that may occur during token parsing.
 #[derive(Debug)]
@@ -19,11 +19,13 @@ pub enum ParseError {
     MalformedHeader(serde_json::Error),
     /// [Content type][cty] mentioned in the token header is not supported.
     ///
-    /// Supported content types are JSON (used by default) and CBOR (only if the `serde_cbor`
+    /// Supported content types are JSON (used by default) and CBOR (only if the `cbor`
     /// crate feature is enabled, which it is by default).
     ///
     /// [cty]: https://tools.ietf.org/html/rfc7515#section-4.1.10
     UnsupportedContentType(String),
+    /// Token in the [JWS JSON serialization](crate::UntrustedJsonToken) cannot be parsed.
+    MalformedJson(serde_json::Error),
 }
 
 impl fmt::Display for ParseError {
@@ -35,6 +37,7 @@ impl fmt::Display for ParseError {
             Self::UnsupportedContentType(ty) => {
                 write!(formatter, "unsupported content type: {ty}")
             }
+            Self::MalformedJson(err) => write!(formatter, "malformed JSON token: {err}"),
         }
     }
 }
@@ -43,7 +46,7 @@ impl fmt::Display for ParseError {
 impl std::error::Error for ParseError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::MalformedHeader(err) => Some(err),
+            Self::MalformedHeader(err) | Self::MalformedJson(err) => Some(err),
             _ => None,
         }
     }
@@ -60,6 +63,17 @@ pub enum ValidationError {
         /// Actual algorithm in the token.
         actual: String,
     },
+    /// Algorithm mentioned in the token header is not in the list of allowed algorithms.
+    AlgorithmNotAllowed(String),
+    /// Algorithm mentioned in the token header is incompatible with the verifying key.
+    IncompatibleKey {
+        /// Algorithm name.
+        algorithm: String,
+    },
+    /// Token header lists a [critical extension][crit] that is not understood by the application.
+    ///
+    /// [crit]: https://www.rfc-editor.org/rfc/rfc7515#section-4.1.11
+    UnknownCriticalExtension(String),
     /// Token signature has invalid byte length.
     InvalidSignatureLen {
         /// Expected signature length.
@@ -74,15 +88,63 @@ pub enum ValidationError {
     /// Token claims cannot be deserialized from JSON.
     MalformedClaims(serde_json::Error),
     /// Token claims cannot be deserialized from CBOR.
-    #[cfg(feature = "serde_cbor")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "serde_cbor")))]
-    MalformedCborClaims(serde_cbor::error::Error),
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    MalformedCborClaims(anyhow::Error),
     /// Claim requested during validation is not present in the token.
     NoClaim(Claim),
     /// Token has expired.
     Expired,
     /// Token is not yet valid as per `nbf` claim.
     NotMature,
+    /// Token issuer (`iss` claim) differs from the expected one.
+    IssuerMismatch {
+        /// Expected issuer.
+        expected: String,
+        /// Actual issuer in the token.
+        actual: String,
+    },
+    /// Token audience (`aud` claim) does not contain the expected value.
+    AudienceMismatch {
+        /// Expected audience.
+        expected: String,
+    },
+    /// Token type (`typ` field in the token header) differs from the expected one.
+    UnexpectedTokenType {
+        /// Expected token type.
+        expected: String,
+        /// Actual token type, or `None` if the header does not specify it.
+        actual: Option<String>,
+    },
+    /// Token is too old as per `iat` claim.
+    TooOld,
+    /// Token issuance time (`iat` claim) is in the future.
+    IssuedInFuture,
+    /// Token header contains no X.509 certificate thumbprints (`x5t` or `x5t#S256`)
+    /// to compare with a certificate.
+    NoCertificateThumbprint,
+    /// X.509 certificate thumbprint in the token header does not match the certificate.
+    CertificateThumbprintMismatch,
+    /// No verifying key was found for the token by a [`KeyResolver`](crate::KeyResolver).
+    KeyNotFound,
+    /// Verifying key for the token cannot be resolved by a [`KeyResolver`](crate::KeyResolver)
+    /// for a reason other than the key being absent (e.g., the key cannot be loaded).
+    KeyResolution(anyhow::Error),
+    /// Token content type (the `cty` field in the token header) is not appropriate
+    /// for the validation method. For example, this error is returned when deserializing claims
+    /// from a nested token (i.e., one with the `JWT` content type), or when validating a token
+    /// without the `JWT` content type as a nested one.
+    UnexpectedContentType(String),
+    /// Encrypted token cannot be decrypted. For security reasons, this error does not
+    /// specify the cause of the failure (e.g., an invalid key or authentication tag mismatch).
+    #[cfg(feature = "jwe")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jwe")))]
+    DecryptionFailed,
+    /// Encrypted token specifies a compression algorithm (the `zip` header field).
+    /// Compressed tokens are not supported.
+    #[cfg(feature = "jwe")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jwe")))]
+    UnsupportedCompression(String),
 }
 
 /// Identifier of a claim in `Claims`.
@@ -93,6 +155,16 @@ pub enum Claim {
     Expiration,
     /// `nbf` claim (valid not before).
     NotBefore,
+    /// `iat` claim (issued at).
+    IssuedAt,
+    /// `iss` claim (issuer).
+    Issuer,
+    /// `sub` claim (subject).
+    Subject,
+    /// `aud` claim (audience).
+    Audience,
+    /// `jti` claim (JWT ID).
+    JwtId,
 }
 
 impl fmt::Display for Claim {
@@ -100,6 +172,11 @@ impl fmt::Display for Claim {
         formatter.write_str(match self {
             Self::Expiration => "exp",
             Self::NotBefore => "nbf",
+            Self::IssuedAt => "iat",
+            Self::Issuer => "iss",
+            Self::Subject => "sub",
+            Self::Audience => "aud",
+            Self::JwtId => "jti",
         })
     }
 }
@@ -111,6 +188,16 @@ impl fmt::Display for ValidationError {
                 formatter,
                 "token algorithm ({actual}) differs from expected ({expected})"
             ),
+            Self::AlgorithmNotAllowed(alg) => {
+                write!(formatter, "token algorithm ({alg}) is not allowed")
+            }
+            Self::IncompatibleKey { algorithm } => write!(
+                formatter,
+                "token algorithm ({algorithm}) is incompatible with the verifying key"
+            ),
+            Self::UnknownCriticalExtension(name) => {
+                write!(formatter, "unknown critical header extension: {name}")
+            }
             Self::InvalidSignatureLen { expected, actual } => write!(
                 formatter,
                 "invalid signature length: expected {expected} bytes, got {actual} bytes"
@@ -118,7 +205,7 @@ impl fmt::Display for ValidationError {
             Self::MalformedSignature(err) => write!(formatter, "malformed token signature: {err}"),
             Self::InvalidSignature => formatter.write_str("signature has failed verification"),
             Self::MalformedClaims(err) => write!(formatter, "cannot deserialize claims: {err}"),
-            #[cfg(feature = "serde_cbor")]
+            #[cfg(feature = "cbor")]
             Self::MalformedCborClaims(err) => write!(formatter, "cannot deserialize claims: {err}"),
             Self::NoClaim(claim) => write!(
                 formatter,
@@ -126,6 +213,47 @@ impl fmt::Display for ValidationError {
             ),
             Self::Expired => formatter.write_str("token has expired"),
             Self::NotMature => formatter.write_str("token is not yet ready"),
+            Self::IssuerMismatch { expected, actual } => write!(
+                formatter,
+                "token issuer ({actual}) differs from expected ({expected})"
+            ),
+            Self::AudienceMismatch { expected } => write!(
+                formatter,
+                "token audience does not contain expected value ({expected})"
+            ),
+            Self::UnexpectedTokenType {
+                expected,
+                actual: Some(actual),
+            } => write!(
+                formatter,
+                "token type ({actual}) differs from expected ({expected})"
+            ),
+            Self::UnexpectedTokenType {
+                expected,
+                actual: None,
+            } => write!(
+                formatter,
+                "token type is not specified, while expected type is {expected}"
+            ),
+            Self::TooOld => formatter.write_str("token is too old"),
+            Self::IssuedInFuture => formatter.write_str("token is issued in the future"),
+            Self::NoCertificateThumbprint => {
+                formatter.write_str("token header contains no certificate thumbprints")
+            }
+            Self::CertificateThumbprintMismatch => {
+                formatter.write_str("certificate thumbprint in token header does not match")
+            }
+            Self::KeyNotFound => formatter.write_str("no verifying key found for token"),
+            Self::KeyResolution(err) => write!(formatter, "cannot resolve verifying key: {err}"),
+            Self::UnexpectedContentType(ty) => {
+                write!(formatter, "unexpected token content type: {ty}")
+            }
+            #[cfg(feature = "jwe")]
+            Self::DecryptionFailed => formatter.write_str("token cannot be decrypted"),
+            #[cfg(feature = "jwe")]
+            Self::UnsupportedCompression(alg) => {
+                write!(formatter, "unsupported token compression algorithm: {alg}")
+            }
         }
     }
 }
@@ -134,10 +262,77 @@ impl fmt::Display for ValidationError {
 impl std::error::Error for ValidationError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::MalformedSignature(err) => Some(err.as_ref()),
+            Self::MalformedSignature(err) | Self::KeyResolution(err) => Some(err.as_ref()),
             Self::MalformedClaims(err) => Some(err),
-            #[cfg(feature = "serde_cbor")]
-            Self::MalformedCborClaims(err) => Some(err),
+            #[cfg(feature = "cbor")]
+            Self::MalformedCborClaims(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Error returned by [`ValidationPolicy`](crate::ValidationPolicy) containing one or more
+/// failed validation checks.
+#[derive(Debug)]
+pub struct PolicyError {
+    errors: Vec<ValidationError>,
+}
+
+impl From<ValidationError> for PolicyError {
+    fn from(err: ValidationError) -> Self {
+        Self {
+            errors: Vec::from([err]),
+        }
+    }
+}
+
+impl PolicyError {
+    pub(crate) fn from_errors(errors: Vec<ValidationError>) -> Result<(), Self> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Self { errors })
+        }
+    }
+
+    /// Returns failed checks. The returned slice is never empty.
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /// Converts this error into the list of failed checks.
+    pub fn into_errors(self) -> Vec<ValidationError> {
+        self.errors
+    }
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [err] = self.errors.as_slice() {
+            return fmt::Display::fmt(err, formatter);
+        }
+
+        write!(
+            formatter,
+            "{} validation checks failed: ",
+            self.errors.len()
+        )?;
+        for (i, err) in self.errors.iter().enumerate() {
+            if i > 0 {
+                formatter.write_str("; ")?;
+            }
+            fmt::Display::fmt(err, formatter)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PolicyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.errors.as_slice() {
+            // `Display` delegates to the error, so we need to do the same with `source()`.
+            [err] => err.source(),
             _ => None,
         }
     }
@@ -152,9 +347,36 @@ pub enum CreationError {
     /// Token claims cannot be serialized into JSON.
     Claims(serde_json::Error),
     /// Token claims cannot be serialized into CBOR.
-    #[cfg(feature = "serde_cbor")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "serde_cbor")))]
-    CborClaims(serde_cbor::error::Error),
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    CborClaims(anyhow::Error),
+    /// Key ID (`kid` field) in the token header differs from the key ID associated
+    /// with the signing key.
+    KeyIdMismatch {
+        /// Key ID associated with the signing key.
+        expected: String,
+        /// Key ID in the token header.
+        actual: String,
+    },
+    /// Signing key is symmetric, while an asymmetric key is required (e.g., to embed
+    /// the public part of the key into the token header).
+    SymmetricKey,
+    /// Signing key is incompatible with the signing algorithm.
+    IncompatibleKey {
+        /// Name of the signing algorithm.
+        algorithm: String,
+    },
+    /// Signing algorithm has no registered [COSE] identifier, and thus cannot be used
+    /// to create a [CWT](crate::cwt).
+    ///
+    /// [COSE]: https://www.iana.org/assignments/cose/cose.xhtml#algorithms
+    #[cfg(feature = "cwt")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cwt")))]
+    UnsupportedAlgorithm(String),
+    /// Token cannot be encrypted.
+    #[cfg(feature = "jwe")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jwe")))]
+    Encryption(anyhow::Error),
 }
 
 impl fmt::Display for CreationError {
@@ -162,8 +384,23 @@ impl fmt::Display for CreationError {
         match self {
             Self::Header(err) => write!(formatter, "cannot serialize header: {err}"),
             Self::Claims(err) => write!(formatter, "cannot serialize claims: {err}"),
-            #[cfg(feature = "serde_cbor")]
+            #[cfg(feature = "cbor")]
             Self::CborClaims(err) => write!(formatter, "cannot serialize claims into CBOR: {err}"),
+            Self::KeyIdMismatch { expected, actual } => write!(
+                formatter,
+                "key ID in token header ({actual}) differs from the signing key ID ({expected})"
+            ),
+            Self::SymmetricKey => formatter.write_str("signing key must be asymmetric"),
+            Self::IncompatibleKey { algorithm } => write!(
+                formatter,
+                "signing key is incompatible with the {algorithm} algorithm"
+            ),
+            #[cfg(feature = "cwt")]
+            Self::UnsupportedAlgorithm(alg) => {
+                write!(formatter, "algorithm {alg} is not supported for CWTs")
+            }
+            #[cfg(feature = "jwe")]
+            Self::Encryption(err) => write!(formatter, "cannot encrypt token: {err}"),
         }
     }
 }
@@ -173,8 +410,13 @@ impl std::error::Error for CreationError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::Header(err) | Self::Claims(err) => Some(err),
-            #[cfg(feature = "serde_cbor")]
-            Self::CborClaims(err) => Some(err),
+            #[cfg(feature = "cbor")]
+            Self::CborClaims(err) => Some(err.as_ref()),
+            #[cfg(feature = "jwe")]
+            Self::Encryption(err) => Some(err.as_ref()),
+            #[cfg(feature = "cwt")]
+            Self::UnsupportedAlgorithm(_) => None,
+            Self::KeyIdMismatch { .. } | Self::SymmetricKey | Self::IncompatibleKey { .. } => None,
         }
     }
 }
@@ -0,0 +1,664 @@
+//! JWS JSON serialization (general and flattened) as per [RFC 7515, section 7.2].
+//!
+//! [RFC 7515, section 7.2]: https://www.rfc-editor.org/rfc/rfc7515#section-7.2
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use serde::{de::DeserializeOwned, de::Error as _, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use smallvec::SmallVec;
+
+use crate::{
+    alloc::{format, Cow, String, Vec},
+    token::{check_critical_params, CompleteHeader, SIGNATURE_SIZE},
+    Algorithm, AlgorithmSignature, Claims, CreationError, Empty, Header, ParseError,
+    UntrustedToken,
+};
+
+type JsonObject = Map<String, Value>;
+
+/// Serialized signature in the JWS JSON serialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JsonSignature {
+    /// Base64url-encoded protected header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    protected: Option<String>,
+    /// Unprotected header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    header: Option<JsonObject>,
+    /// Base64url-encoded signature.
+    signature: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeneralJsonToken<'a> {
+    payload: Cow<'a, str>,
+    signatures: Cow<'a, [JsonSignature]>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FlattenedJsonToken<'a> {
+    payload: Cow<'a, str>,
+    #[serde(flatten)]
+    signature: Cow<'a, JsonSignature>,
+}
+
+fn header_error(message: &str) -> serde_json::Error {
+    serde_json::Error::custom(message)
+}
+
+fn to_json_string(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("cannot serialize JSON token; this should never happen")
+    // ^ Serialization cannot fail: the token consists of strings and JSON objects.
+}
+
+/// Header fields that must be integrity-protected, i.e., cannot be present in the unprotected
+/// header. For `b64` and `crit`, this is required by RFC 7797 and RFC 7515 respectively;
+/// other fields are used by the crate during validation (e.g., `alg` to prevent algorithm
+/// substitution, or `typ` / `cty` to distinguish token kinds), so tampering with them must
+/// invalidate the signature.
+const PROTECTED_ONLY_FIELDS: &[&str] = &[
+    "alg", "b64", "crit", "cty", "typ", "jku", "jwk", "x5u", "x5c", "x5t", "x5t#S256",
+];
+
+fn unprotected_field_message(field: &str) -> String {
+    format!("`{field}` must be a part of the protected header")
+}
+
+fn overlapping_field_message(field: &str) -> String {
+    format!("field `{field}` is present in both protected and unprotected headers")
+}
+
+/// Builder of tokens in the [JWS JSON serialization], which allows to sign the same claims
+/// by several parties, possibly using different algorithms.
+///
+/// Each signature has its own protected [`Header`] (which always includes the signing algorithm)
+/// and an optional unprotected header. The unprotected header is not covered
+/// by the signature; it may be used e.g. to specify the key ID. Header fields used
+/// during validation (such as `alg`, `typ`, `cty` or `jwk`) are only accepted
+/// in the protected header.
+///
+/// [JWS JSON serialization]: https://www.rfc-editor.org/rfc/rfc7515#section-7.2
+///
+/// # Examples
+///
+/// ```
+/// # use serde_json::json;
+/// use jwt_compact::{alg::{Hs256, Hs256Key, Hs512, Hs512Key}, prelude::*};
+/// use jwt_compact::{Empty, JsonTokenBuilder, UntrustedJsonToken};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let first_key = Hs256Key::new(b"super_secret_key_donut_steel");
+/// let second_key = Hs512Key::new(b"another_super_secret_key");
+/// let claims = Claims::empty().set_subject("release-manifest");
+///
+/// let token_string = JsonTokenBuilder::new(&claims)?
+///     .sign(&Hs256, &Header::empty().with_key_id("first"), &first_key)?
+///     .sign_with_unprotected_header(
+///         &Hs512,
+///         &Header::empty(),
+///         &json!({ "kid": "second" }),
+///         &second_key,
+///     )?
+///     .to_general_json();
+///
+/// let token = UntrustedJsonToken::new(&token_string)?;
+/// assert_eq!(token.signatures().len(), 2);
+/// // Validate a specific signature...
+/// let signature = token.signature_by_key_id("second").unwrap();
+/// let validated = Hs512.validator::<Empty>(&second_key).validate(signature)?;
+/// assert_eq!(validated.claims().subject.as_deref(), Some("release-manifest"));
+/// // ...or all of them.
+/// Hs256.validator::<Empty>(&first_key).validate(&token.signatures()[0])?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonTokenBuilder {
+    payload: String,
+    signatures: Vec<JsonSignature>,
+}
+
+impl JsonTokenBuilder {
+    /// Creates a builder for a token with the specified claims and no signatures.
+    pub fn new<T: Serialize>(claims: &Claims<T>) -> Result<Self, CreationError> {
+        let claims = serde_json::to_vec(claims).map_err(CreationError::Claims)?;
+        Ok(Self {
+            payload: Base64UrlUnpadded::encode_string(&claims),
+            signatures: Vec::new(),
+        })
+    }
+
+    /// Returns the number of signatures added to this builder.
+    pub fn signature_count(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Adds a signature with the specified protected `header`.
+    pub fn sign<A: Algorithm + ?Sized>(
+        self,
+        algorithm: &A,
+        header: &Header<impl Serialize>,
+        signing_key: &A::SigningKey,
+    ) -> Result<Self, CreationError> {
+        self.sign_inner(algorithm, header, None, signing_key)
+    }
+
+    /// Adds a signature with the specified protected `header` and unprotected header.
+    /// The unprotected header must serialize to a JSON object, and its fields must not overlap
+    /// with the fields of the protected header. The unprotected header must not contain
+    /// registered fields other than `kid` (e.g., `alg`, `crit`, `typ`, `cty` or `jwk`).
+    pub fn sign_with_unprotected_header<A: Algorithm + ?Sized>(
+        self,
+        algorithm: &A,
+        header: &Header<impl Serialize>,
+        unprotected_header: &impl Serialize,
+        signing_key: &A::SigningKey,
+    ) -> Result<Self, CreationError> {
+        let unprotected_header =
+            serde_json::to_value(unprotected_header).map_err(CreationError::Header)?;
+        let Value::Object(unprotected_header) = unprotected_header else {
+            let err = header_error("unprotected header must be a JSON object");
+            return Err(CreationError::Header(err));
+        };
+        if let Some(field) = PROTECTED_ONLY_FIELDS
+            .iter()
+            .find(|&&field| unprotected_header.contains_key(field))
+        {
+            let message = unprotected_field_message(field);
+            return Err(CreationError::Header(header_error(&message)));
+        }
+        self.sign_inner(algorithm, header, Some(unprotected_header), signing_key)
+    }
+
+    fn sign_inner<A: Algorithm + ?Sized>(
+        mut self,
+        algorithm: &A,
+        header: &Header<impl Serialize>,
+        unprotected_header: Option<JsonObject>,
+        signing_key: &A::SigningKey,
+    ) -> Result<Self, CreationError> {
+        let complete_header = CompleteHeader::for_signing(algorithm, header, signing_key)?;
+        if let Some(unprotected_header) = &unprotected_header {
+            let header = serde_json::to_value(&complete_header).map_err(CreationError::Header)?;
+            let Value::Object(header) = header else {
+                let err = header_error("header must be a JSON object");
+                return Err(CreationError::Header(err));
+            };
+            if let Some(field) = unprotected_header
+                .keys()
+                .find(|&key| header.contains_key(key))
+            {
+                let message = overlapping_field_message(field);
+                return Err(CreationError::Header(header_error(&message)));
+            }
+        }
+
+        let header = serde_json::to_string(&complete_header).map_err(CreationError::Header)?;
+        let protected = Base64UrlUnpadded::encode_string(header.as_bytes());
+        let signing_input = format!("{protected}.{}", self.payload);
+        let signature = algorithm.sign(signing_key, signing_input.as_bytes());
+
+        self.signatures.push(JsonSignature {
+            protected: Some(protected),
+            header: unprotected_header,
+            signature: Base64UrlUnpadded::encode_string(&signature.as_bytes()),
+        });
+        Ok(self)
+    }
+
+    /// Serializes the token using the general JWS JSON serialization.
+    pub fn to_general_json(&self) -> String {
+        let token = GeneralJsonToken {
+            payload: Cow::Borrowed(&self.payload),
+            signatures: Cow::Borrowed(&self.signatures),
+        };
+        to_json_string(&token)
+    }
+
+    /// Serializes the token using the flattened JWS JSON serialization. Returns `None`
+    /// if the builder does not contain exactly one signature.
+    pub fn to_flattened_json(&self) -> Option<String> {
+        let [signature] = self.signatures.as_slice() else {
+            return None;
+        };
+        let token = FlattenedJsonToken {
+            payload: Cow::Borrowed(&self.payload),
+            signature: Cow::Borrowed(signature),
+        };
+        Some(to_json_string(&token))
+    }
+}
+
+/// Parsed, but unvalidated token in the [JWS JSON serialization] (either general or flattened).
+///
+/// Each signature in the token is represented as an [`UntrustedToken`], which can be validated
+/// with a [`Validator`](crate::Validator) in the same way as compact tokens.
+/// The header of each such token is the protected header of the corresponding signature;
+/// the unprotected header (which is not covered by the signature) is available separately
+/// via [`UntrustedToken::unprotected_header()`].
+///
+/// See [`JsonTokenBuilder`] for an example of usage.
+///
+/// [JWS JSON serialization]: https://www.rfc-editor.org/rfc/rfc7515#section-7.2
+#[derive(Debug, Clone)]
+pub struct UntrustedJsonToken<H = Empty> {
+    signatures: Vec<UntrustedToken<'static, H>>,
+}
+
+impl<H: DeserializeOwned> TryFrom<&str> for UntrustedJsonToken<H> {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let token: JsonObject = serde_json::from_str(s).map_err(ParseError::MalformedJson)?;
+        let (payload, signatures) = if token.contains_key("signatures") {
+            let token: GeneralJsonToken<'_> =
+                serde_json::from_value(Value::Object(token)).map_err(ParseError::MalformedJson)?;
+            (token.payload, token.signatures.into_owned())
+        } else {
+            let token: FlattenedJsonToken<'_> =
+                serde_json::from_value(Value::Object(token)).map_err(ParseError::MalformedJson)?;
+            (
+                token.payload,
+                core::iter::once(token.signature.into_owned()).collect(),
+            )
+        };
+        if signatures.is_empty() {
+            return Err(ParseError::InvalidTokenStructure);
+        }
+
+        let signatures = signatures
+            .into_iter()
+            .map(|signature| parse_signature(&payload, signature))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { signatures })
+    }
+}
+
+fn parse_signature<H: DeserializeOwned>(
+    payload: &str,
+    signature: JsonSignature,
+) -> Result<UntrustedToken<'static, H>, ParseError> {
+    let protected = signature.protected.unwrap_or_default();
+    let header = if protected.is_empty() {
+        JsonObject::new()
+    } else {
+        let header = Base64UrlUnpadded::decode_vec(&protected)
+            .map_err(|_| ParseError::InvalidBase64Encoding)?;
+        serde_json::from_slice(&header).map_err(ParseError::MalformedHeader)?
+    };
+    let unprotected_header = signature.header;
+    for field in unprotected_header.iter().flat_map(JsonObject::keys) {
+        if PROTECTED_ONLY_FIELDS.contains(&field.as_str()) {
+            let message = unprotected_field_message(field);
+            return Err(ParseError::MalformedHeader(header_error(&message)));
+        }
+        if header.contains_key(field) {
+            let message = overlapping_field_message(field);
+            return Err(ParseError::MalformedHeader(header_error(&message)));
+        }
+    }
+    let header = Value::Object(header);
+    let complete_header =
+        CompleteHeader::<Header<H>>::deserialize(&header).map_err(ParseError::MalformedHeader)?;
+    if let (Some(critical), Value::Object(header)) = (&complete_header.critical, &header) {
+        check_critical_params(critical, header)?;
+    }
+    let header = complete_header;
+    let serialized_claims = if header.is_payload_encoded()? {
+        Base64UrlUnpadded::decode_vec(payload).map_err(|_| ParseError::InvalidBase64Encoding)?
+    } else {
+        payload.as_bytes().to_vec()
+    };
+
+    let signature = Base64UrlUnpadded::decode_vec(&signature.signature)
+        .map_err(|_| ParseError::InvalidBase64Encoding)?;
+    let signed_data = format!("{protected}.{payload}");
+    let mut token = UntrustedToken::from_parts(
+        Cow::Owned(signed_data.into_bytes()),
+        header,
+        serialized_claims,
+        SmallVec::<[u8; SIGNATURE_SIZE]>::from_vec(signature),
+    )?;
+    token.unprotected_header = unprotected_header;
+    Ok(token)
+}
+
+impl UntrustedJsonToken {
+    /// Creates an untrusted token from a string. This is a shortcut for calling the [`TryFrom`]
+    /// conversion.
+    pub fn new<S: AsRef<str> + ?Sized>(s: &S) -> Result<Self, ParseError> {
+        Self::try_from(s.as_ref())
+    }
+}
+
+impl<H> UntrustedJsonToken<H> {
+    /// Returns signatures in this token. The returned slice is always non-empty.
+    pub fn signatures(&self) -> &[UntrustedToken<'static, H>] {
+        &self.signatures
+    }
+
+    /// Finds the first signature with the specified key ID (the `kid` field of the protected
+    /// or unprotected header).
+    pub fn signature_by_key_id(&self, key_id: &str) -> Option<&UntrustedToken<'static, H>> {
+        self.signatures.iter().find(|signature| {
+            let unprotected_key_id = signature
+                .unprotected_header()
+                .and_then(|header| header.get("kid")?.as_str());
+            let signature_key_id = signature.header().key_id.as_deref().or(unprotected_key_id);
+            signature_key_id == Some(key_id)
+        })
+    }
+
+    /// Converts this token into the contained signatures.
+    pub fn into_signatures(self) -> Vec<UntrustedToken<'static, H>> {
+        self.signatures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use chrono::{Duration, Utc};
+
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key, Hs384, Hs384Key},
+        alloc::ToString,
+        oauth::{AccessTokenClaims, AccessTokenError, AccessTokenValidator},
+        AlgorithmExt, TimeOptions, ValidationError, ValidationPolicy,
+    };
+
+    fn create_builder() -> JsonTokenBuilder {
+        let claims = Claims::empty().set_issuer("me");
+        JsonTokenBuilder::new(&claims).unwrap()
+    }
+
+    #[test]
+    fn general_serialization_roundtrip() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let other_key = Hs384Key::new(b"another_super_secret_key");
+        let unprotected_header = serde_json::json!({ "kid": "other" });
+        let builder = create_builder()
+            .sign(&Hs256, &Header::empty().with_key_id("first"), &key)
+            .unwrap()
+            .sign_with_unprotected_header(&Hs384, &Header::empty(), &unprotected_header, &other_key)
+            .unwrap();
+        assert_eq!(builder.signature_count(), 2);
+        assert!(builder.to_flattened_json().is_none());
+
+        let token_string = builder.to_general_json();
+        let token_json: Value = serde_json::from_str(&token_string).unwrap();
+        assert_eq!(token_json["signatures"][1]["header"], unprotected_header);
+        assert!(token_json["signatures"][0].get("header").is_none());
+
+        let token = UntrustedJsonToken::new(&token_string).unwrap();
+        let [first, second] = token.signatures() else {
+            panic!("unexpected signatures: {:?}", token.signatures());
+        };
+        assert_eq!(first.algorithm(), "HS256");
+        assert_eq!(first.header().key_id.as_deref(), Some("first"));
+        assert_eq!(second.algorithm(), "HS384");
+        assert_eq!(second.header().key_id, None);
+        assert_eq!(second.unprotected_header(), unprotected_header.as_object());
+
+        let validated = Hs256.validator::<Empty>(&key).validate(first).unwrap();
+        assert_eq!(validated.claims().issuer.as_deref(), Some("me"));
+        Hs384
+            .validator::<Empty>(&other_key)
+            .validate(second)
+            .unwrap();
+
+        let err = Hs256.validator::<Empty>(&key).validate(second).unwrap_err();
+        assert_matches!(err, ValidationError::AlgorithmMismatch { .. });
+        let signature = token.signature_by_key_id("other").unwrap();
+        assert_eq!(signature.algorithm(), "HS384");
+        assert!(token.signature_by_key_id("missing").is_none());
+    }
+
+    #[test]
+    fn flattened_serialization_roundtrip() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let builder = create_builder()
+            .sign(&Hs256, &Header::empty().with_key_id("first"), &key)
+            .unwrap();
+        let token_string = builder.to_flattened_json().unwrap();
+        let token_json: JsonObject = serde_json::from_str(&token_string).unwrap();
+        assert!(token_json.contains_key("signature"));
+        assert!(!token_json.contains_key("signatures"));
+
+        let token = UntrustedJsonToken::new(&token_string).unwrap();
+        assert_eq!(token.signatures().len(), 1);
+        let validated = Hs256
+            .validator::<Empty>(&key)
+            .validate(&token.signatures()[0]);
+        assert_eq!(validated.unwrap().claims().issuer.as_deref(), Some("me"));
+    }
+
+    #[test]
+    fn signatures_are_compatible_with_compact_serialization() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let header = Header::empty().with_key_id("first");
+        let claims = Claims::empty().set_issuer("me");
+        let compact_token = Hs256.token(&header, &claims, &key).unwrap();
+
+        let json_token = JsonTokenBuilder::new(&claims)
+            .unwrap()
+            .sign(&Hs256, &header, &key)
+            .unwrap()
+            .to_flattened_json()
+            .unwrap();
+        let json_token: Value = serde_json::from_str(&json_token).unwrap();
+        let reassembled = format!(
+            "{}.{}.{}",
+            json_token["protected"].as_str().unwrap(),
+            json_token["payload"].as_str().unwrap(),
+            json_token["signature"].as_str().unwrap()
+        );
+        assert_eq!(reassembled, compact_token);
+    }
+
+    #[test]
+    fn tampered_unprotected_header_does_not_affect_signature() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let token_string = create_builder()
+            .sign_with_unprotected_header(
+                &Hs256,
+                &Header::empty(),
+                &serde_json::json!({ "kid": "a" }),
+                &key,
+            )
+            .unwrap()
+            .to_flattened_json()
+            .unwrap();
+        let token_string = token_string.replace(r#""kid":"a""#, r#""kid":"b""#);
+        let token = UntrustedJsonToken::new(&token_string).unwrap();
+        assert!(token.signature_by_key_id("b").is_some());
+        Hs256
+            .validator::<Empty>(&key)
+            .validate(&token.signatures()[0])
+            .unwrap();
+    }
+
+    #[test]
+    fn unprotected_token_type_and_content_type_are_not_trusted() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let token_string = create_builder()
+            .sign_with_unprotected_header(
+                &Hs256,
+                &Header::empty(),
+                &serde_json::json!({ "kid": "a" }),
+                &key,
+            )
+            .unwrap()
+            .to_flattened_json()
+            .unwrap();
+
+        for (field, value) in [("typ", "at+jwt"), ("cty", "JWT")] {
+            let err = create_builder()
+                .sign_with_unprotected_header(
+                    &Hs256,
+                    &Header::empty(),
+                    &serde_json::json!({ field: value }),
+                    &key,
+                )
+                .unwrap_err();
+            assert_matches!(
+                err,
+                CreationError::Header(err) if err.to_string().contains("must be a part of")
+            );
+
+            let mut token_json: Value = serde_json::from_str(&token_string).unwrap();
+            token_json["header"][field] = value.into();
+            let tampered_string = serde_json::to_string(&token_json).unwrap();
+            let err = UntrustedJsonToken::new(&tampered_string).unwrap_err();
+            assert_matches!(
+                err,
+                ParseError::MalformedHeader(err)
+                    if err.to_string() == unprotected_field_message(field)
+            );
+        }
+
+        // Validation only considers the protected header.
+        let token = UntrustedJsonToken::new(&token_string).unwrap();
+        let token = &token.signatures()[0];
+        assert_eq!(token.header().key_id, None);
+        let unprotected_header = token.unprotected_header().unwrap();
+        assert_eq!(unprotected_header["kid"], "a");
+
+        let time_options = TimeOptions::new(Duration::seconds(60), Utc::now);
+        let err = ValidationPolicy::new(time_options)
+            .with_token_type("at+jwt")
+            .validate(Hs256.validator::<Empty>(&key), token)
+            .unwrap_err();
+        assert_matches!(
+            err.errors(),
+            [
+                ValidationError::UnexpectedTokenType { actual: None, .. },
+                ..
+            ]
+        );
+
+        let time_options = TimeOptions::new(Duration::seconds(60), Utc::now);
+        let err = AccessTokenValidator::new("me", "api", time_options)
+            .validate(Hs256.validator::<AccessTokenClaims>(&key), token)
+            .unwrap_err();
+        assert_matches!(
+            err,
+            AccessTokenError::Validation(ValidationError::UnexpectedTokenType { actual: None, .. })
+        );
+
+        let err = Hs256
+            .validator::<Empty>(&key)
+            .validate_nested(token)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::UnexpectedContentType(_));
+    }
+
+    #[test]
+    fn tampered_payload() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let token_string = create_builder()
+            .sign(&Hs256, &Header::empty(), &key)
+            .unwrap()
+            .to_general_json();
+        let mut token_json: Value = serde_json::from_str(&token_string).unwrap();
+        let other_claims = Claims::empty().set_issuer("you");
+        let other_claims = serde_json::to_vec(&other_claims).unwrap();
+        token_json["payload"] = Base64UrlUnpadded::encode_string(&other_claims).into();
+
+        let token_string = serde_json::to_string(&token_json).unwrap();
+        let token = UntrustedJsonToken::new(&token_string).unwrap();
+        let err = Hs256
+            .validator::<Empty>(&key)
+            .validate(&token.signatures()[0])
+            .unwrap_err();
+        assert_matches!(err, ValidationError::InvalidSignature);
+    }
+
+    #[test]
+    fn overlapping_headers() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let err = create_builder()
+            .sign_with_unprotected_header(
+                &Hs256,
+                &Header::empty().with_key_id("a"),
+                &serde_json::json!({ "kid": "b" }),
+                &key,
+            )
+            .unwrap_err();
+        assert_matches!(err, CreationError::Header(err) if err.to_string().contains("`kid`"));
+
+        let err = create_builder()
+            .sign_with_unprotected_header(&Hs256, &Header::empty(), &"kid", &key)
+            .unwrap_err();
+        assert_matches!(err, CreationError::Header(err) if err.to_string().contains("JSON object"));
+
+        let err = create_builder()
+            .sign_with_unprotected_header(
+                &Hs256,
+                &Header::empty(),
+                &serde_json::json!({ "crit": [] }),
+                &key,
+            )
+            .unwrap_err();
+        assert_matches!(err, CreationError::Header(err) if err.to_string().contains("`crit`"));
+        let err = create_builder()
+            .sign_with_unprotected_header(
+                &Hs256,
+                &Header::empty(),
+                &serde_json::json!({ "b64": false }),
+                &key,
+            )
+            .unwrap_err();
+        assert_matches!(
+            err,
+            CreationError::Header(err) if err.to_string().contains("`b64` must be a part of")
+        );
+
+        let token_string = create_builder()
+            .sign(&Hs256, &Header::empty(), &key)
+            .unwrap()
+            .to_flattened_json()
+            .unwrap();
+        let mut token_json: Value = serde_json::from_str(&token_string).unwrap();
+        token_json["header"] = serde_json::json!({ "alg": "none" });
+        let token_string = serde_json::to_string(&token_json).unwrap();
+        let err = UntrustedJsonToken::new(&token_string).unwrap_err();
+        assert_matches!(err, ParseError::MalformedHeader(err) if err.to_string().contains("`alg`"));
+
+        token_json["header"] = serde_json::json!({ "b64": true });
+        let token_string = serde_json::to_string(&token_json).unwrap();
+        let err = UntrustedJsonToken::new(&token_string).unwrap_err();
+        assert_matches!(err, ParseError::MalformedHeader(err) if err.to_string().contains("`b64`"));
+
+        // `alg` must not be in the unprotected header even if the protected header is empty.
+        token_json.as_object_mut().unwrap().remove("protected");
+        token_json["header"] = serde_json::json!({ "alg": "HS256" });
+        let token_string = serde_json::to_string(&token_json).unwrap();
+        let err = UntrustedJsonToken::new(&token_string).unwrap_err();
+        assert_matches!(
+            err,
+            ParseError::MalformedHeader(err) if err.to_string().contains("`alg` must be a part of")
+        );
+
+        token_json["header"] = serde_json::json!({ "crit": ["exp"], "exp": 1 });
+        let token_string = serde_json::to_string(&token_json).unwrap();
+        let err = UntrustedJsonToken::new(&token_string).unwrap_err();
+        assert_matches!(err, ParseError::MalformedHeader(err) if err.to_string().contains("`crit`"));
+    }
+
+    #[test]
+    fn parsing_errors() {
+        let err = UntrustedJsonToken::new("eyJ0eXAiOiJKV1QiLA0K").unwrap_err();
+        assert_matches!(err, ParseError::MalformedJson(_));
+        let err = UntrustedJsonToken::new(r#"{ "payload": "", "signatures": [] }"#).unwrap_err();
+        assert_matches!(err, ParseError::InvalidTokenStructure);
+        let err = UntrustedJsonToken::new(r#"{ "payload": "" }"#).unwrap_err();
+        assert_matches!(err, ParseError::MalformedJson(err) if err.to_string().contains("signature"));
+        let err = UntrustedJsonToken::new(r#"{ "payload": "?", "signature": "" }"#).unwrap_err();
+        assert_matches!(err, ParseError::MalformedHeader(err) if err.to_string().contains("alg"));
+        let token = r#"{ "payload": "?", "protected": "eyJhbGciOiJIUzI1NiJ9", "signature": "" }"#;
+        let err = UntrustedJsonToken::new(token).unwrap_err();
+        assert_matches!(err, ParseError::InvalidBase64Encoding);
+    }
+}
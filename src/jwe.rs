@@ -0,0 +1,811 @@
+//! Basic support of [JSON Web Encryption](https://www.rfc-editor.org/rfc/rfc7516.html) (JWE).
+//!
+//! JWE allows to protect both integrity and confidentiality of token claims. Similar to signed
+//! tokens, encrypted tokens are created and decrypted using a [`JweAlgorithm`], which
+//! combines two algorithms:
+//!
+//! - [Key management algorithm](KeyManagement) (the `alg` header field) determining
+//!   the content encryption key.
+//! - [Content encryption algorithm](ContentEncryption) (the `enc` header field)
+//!   encrypting token claims using the content encryption key. AES-GCM ([`A128Gcm`],
+//!   [`A256Gcm`]) and AES-CBC-HMAC-SHA2 ([`A128CbcHs256`], [`A256CbcHs512`]) algorithms
+//!   are supported.
+//!
+//! ## Key management algorithms
+//!
+//! | Algorithm(s) | Type | Feature | Keys |
+//! |--------------|------|---------|------|
+//! | `dir` | [`Direct`] | - | [`SymmetricKey`] |
+//! | `A128KW`, `A256KW` | [`A128Kw`], [`A256Kw`] | - | [`SymmetricKey`] |
+//! | `RSA-OAEP-256` | `RsaOaep256` | `rsa` | [`RsaPublicKey`] / [`RsaPrivateKey`] |
+//! | `ECDH-ES`, `ECDH-ES+A128KW`, `ECDH-ES+A256KW` | [`EcdhEs`]`<P256>` | `p256` | [`p256::PublicKey`] / [`p256::SecretKey`] |
+//! | `ECDH-ES`, `ECDH-ES+A128KW`, `ECDH-ES+A256KW` | [`EcdhEs`]`<X25519>` | `x25519-dalek` | [`x25519_dalek::PublicKey`] / [`x25519_dalek::StaticSecret`] |
+//!
+//! Keys can be converted from / to [`JsonWebKey`]s. For ECDH-ES algorithms, the ephemeral public
+//! key generated during encryption is stored in the token header (the `epk` field) as a JWK
+//! and is available via [`EncryptedToken::key_management_header()`].
+//!
+//! [`RsaPublicKey`]: https://docs.rs/rsa/0.9/rsa/struct.RsaPublicKey.html
+//! [`RsaPrivateKey`]: https://docs.rs/rsa/0.9/rsa/struct.RsaPrivateKey.html
+//! [`p256::PublicKey`]: https://docs.rs/p256/0.13/p256/type.PublicKey.html
+//! [`p256::SecretKey`]: https://docs.rs/p256/0.13/p256/type.SecretKey.html
+//! [`x25519_dalek::PublicKey`]: https://docs.rs/x25519-dalek/2/x25519_dalek/struct.PublicKey.html
+//! [`x25519_dalek::StaticSecret`]: https://docs.rs/x25519-dalek/2/x25519_dalek/struct.StaticSecret.html
+//!
+//! Only compact serialization of encrypted tokens is supported. Encrypted tokens are parsed
+//! into [`EncryptedToken`]s, which expose the protected header before decryption.
+//! Decryption produces an ordinary [`Token`], so that its claims can be validated
+//! in the same way as for signed tokens. The payload must be either JSON claims,
+//! or a nested JWT (the `cty` header field set to `JWT`); tokens with other content types
+//! are rejected during parsing.
+//!
+//! # Examples
+//!
+//! ```
+//! # use chrono::Duration;
+//! use jwt_compact::{jwe::*, prelude::*, Empty};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let jwe = JweAlgorithm::new(Direct, A256Gcm);
+//! let key = SymmetricKey::new([7_u8; 32]);
+//! let claims = Claims::empty()
+//!     .set_subject("alice")
+//!     .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(10));
+//! let token_string = jwe.encrypt(&Header::empty().with_key_id("my-key"), &claims, &key)?;
+//!
+//! let token = EncryptedToken::new(&token_string)?;
+//! // The protected header is available before decryption.
+//! assert_eq!(token.header().key_id.as_deref(), Some("my-key"));
+//! assert_eq!(token.algorithm(), "dir");
+//! assert_eq!(token.content_encryption(), "A256GCM");
+//! let token: Token<Empty> = jwe.decrypt(&token, &key)?;
+//! assert_eq!(token.claims().subject.as_deref(), Some("alice"));
+//! # Ok(())
+//! # }
+//! ```
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use core::fmt;
+
+use crate::{
+    alg::SecretBytes,
+    alloc::{Cow, String, ToOwned, Vec},
+    jwk::JsonWebKey,
+    token::check_critical_params,
+    traits::encode_base64_buf,
+    Claims, CreationError, Empty, Header, NestedToken, ParseError, Token, ValidationError,
+};
+
+mod cbc;
+mod direct;
+mod ecdh;
+mod gcm;
+mod kw;
+#[cfg(feature = "rsa")]
+mod rsa;
+
+#[cfg(feature = "p256")]
+pub use self::ecdh::P256;
+#[cfg(feature = "x25519-dalek")]
+pub use self::ecdh::X25519;
+#[cfg(feature = "rsa")]
+pub use self::rsa::RsaOaep256;
+pub use self::{
+    cbc::{A128CbcHs256, A256CbcHs512},
+    direct::Direct,
+    ecdh::{EcdhCurve, EcdhEs},
+    gcm::{A128Gcm, A256Gcm},
+    kw::{A128Kw, A256Kw, SymmetricKey},
+};
+
+/// Content encrypted with a [`ContentEncryption`] algorithm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedContent {
+    /// Initialization vector.
+    pub iv: Vec<u8>,
+    /// Ciphertext.
+    pub ciphertext: Vec<u8>,
+    /// Authentication tag.
+    pub tag: Vec<u8>,
+}
+
+/// Content encryption algorithm, i.e., an authenticated encryption with associated data (AEAD)
+/// used to encrypt token claims (the `enc` header field).
+pub trait ContentEncryption {
+    /// Returns the name of this algorithm as mentioned in the `enc` field of the JWE header.
+    fn name(&self) -> Cow<'static, str>;
+
+    /// Returns the byte length of the content encryption key.
+    fn key_len(&self) -> usize;
+
+    /// Encrypts the `plaintext` with the provided `key` and `aad` (additional authenticated data).
+    /// The initialization vector is generated by the implementation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key length is invalid.
+    fn encrypt(&self, key: &[u8], plaintext: &[u8], aad: &[u8])
+        -> anyhow::Result<EncryptedContent>;
+
+    /// Decrypts the `content` with the provided `key` and `aad` (additional authenticated data).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the decryption fails, e.g. because of the authentication tag mismatch.
+    fn decrypt(
+        &self,
+        key: &[u8],
+        content: &EncryptedContent,
+        aad: &[u8],
+    ) -> anyhow::Result<Vec<u8>>;
+}
+
+/// JWE header fields specific to [`KeyManagement`] algorithms.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyManagementHeader {
+    /// Ephemeral public key (`epk`) generated by the token producer for ECDH-ES key agreement.
+    #[serde(rename = "epk", default, skip_serializing_if = "Option::is_none")]
+    pub ephemeral_key: Option<JsonWebKey<'static>>,
+    /// Agreement `PartyUInfo` (`apu`) used in ECDH-ES key derivation. Usually contains
+    /// information about the token producer.
+    #[serde(
+        rename = "apu",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "optional_base64url"
+    )]
+    pub agreement_party_u_info: Option<Vec<u8>>,
+    /// Agreement `PartyVInfo` (`apv`) used in ECDH-ES key derivation. Usually contains
+    /// information about the token recipient.
+    #[serde(
+        rename = "apv",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "optional_base64url"
+    )]
+    pub agreement_party_v_info: Option<Vec<u8>>,
+}
+
+mod optional_base64url {
+    use serde::{Deserializer, Serializer};
+
+    use crate::{alloc::Vec, jwk::base64url};
+
+    #[allow(clippy::ref_option)] // signature is required by `serde`
+    pub fn serialize<S: Serializer>(
+        value: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => base64url::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        base64url::deserialize(deserializer).map(|bytes| Some(bytes.into_owned()))
+    }
+}
+
+/// Content encryption key together with its encrypted form produced by a [`KeyManagement`]
+/// algorithm.
+#[derive(Debug)]
+pub struct ContentKey {
+    /// Content encryption key.
+    pub key: SecretBytes<'static>,
+    /// Encrypted key as included into the token. May be empty (e.g., for the `dir` algorithm).
+    pub encrypted_key: Vec<u8>,
+    /// Header fields to include into the token (e.g., the ephemeral public key
+    /// for ECDH-ES key agreement).
+    pub header: KeyManagementHeader,
+}
+
+/// Key management algorithm determining the content encryption key (the `alg` header field).
+pub trait KeyManagement {
+    /// Key used to determine the content encryption key during token encryption.
+    type EncryptionKey;
+    /// Key used to determine the content encryption key during token decryption.
+    type DecryptionKey;
+
+    /// Returns the name of this algorithm as mentioned in the `alg` field of the JWE header.
+    fn name(&self) -> Cow<'static, str>;
+
+    /// Determines the content encryption key with the specified byte length for the content
+    /// encryption algorithm with the specified name (the `enc` header field).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is not appropriate for the content encryption algorithm
+    /// (e.g., has an invalid length).
+    fn encrypt_key(
+        &self,
+        key: &Self::EncryptionKey,
+        content_encryption: &str,
+        content_key_len: usize,
+    ) -> anyhow::Result<ContentKey>;
+
+    /// Recovers the content encryption key with the specified byte length
+    /// from the `encrypted_key` and the key management `header` fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content encryption key cannot be recovered.
+    fn decrypt_key(
+        &self,
+        key: &Self::DecryptionKey,
+        header: &KeyManagementHeader,
+        encrypted_key: &[u8],
+        content_encryption: &str,
+        content_key_len: usize,
+    ) -> anyhow::Result<SecretBytes<'static>>;
+}
+
+/// Full JWE header including the algorithm fields.
+#[derive(Debug, Serialize, Deserialize)]
+struct CompleteJweHeader<'a, T> {
+    #[serde(rename = "alg")]
+    algorithm: Cow<'a, str>,
+    #[serde(rename = "enc")]
+    content_encryption: Cow<'a, str>,
+    #[serde(rename = "cty", default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde(rename = "zip", default, skip_serializing)]
+    compression: Option<String>,
+    #[serde(rename = "crit", default, skip_serializing)]
+    critical: Option<Vec<String>>,
+    #[serde(flatten)]
+    key_management: KeyManagementHeader,
+    #[serde(flatten)]
+    inner: T,
+}
+
+/// Combination of a [key management](KeyManagement) and a [content encryption](ContentEncryption)
+/// algorithm used to encrypt and decrypt tokens.
+///
+/// See [module docs](crate::jwe) for an example of usage.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JweAlgorithm<K, E> {
+    key_management: K,
+    content_encryption: E,
+}
+
+impl<K: KeyManagement, E: ContentEncryption> JweAlgorithm<K, E> {
+    /// Combines the provided algorithms.
+    pub const fn new(key_management: K, content_encryption: E) -> Self {
+        Self {
+            key_management,
+            content_encryption,
+        }
+    }
+
+    /// Returns the key management algorithm.
+    pub fn key_management(&self) -> &K {
+        &self.key_management
+    }
+
+    /// Returns the content encryption algorithm.
+    pub fn content_encryption(&self) -> &E {
+        &self.content_encryption
+    }
+
+    /// Creates an encrypted token with the specified header and claims, and serializes it
+    /// to string using compact serialization.
+    pub fn encrypt<T: Serialize>(
+        &self,
+        header: &Header<impl Serialize>,
+        claims: &Claims<T>,
+        key: &K::EncryptionKey,
+    ) -> Result<String, CreationError> {
+        let claims = serde_json::to_vec(claims).map_err(CreationError::Claims)?;
+        self.encrypt_payload(header, None, &claims, key)
+    }
+
+    /// Creates an encrypted token with another token (usually, a signed one) as the payload,
+    /// as per [RFC 7519, section 5.2]. The created token has the `cty` header field set to `JWT`.
+    /// Such a token can be decrypted with [`Self::decrypt_nested()`].
+    ///
+    /// [RFC 7519, section 5.2]: https://www.rfc-editor.org/rfc/rfc7519#section-5.2
+    pub fn encrypt_nested(
+        &self,
+        header: &Header<impl Serialize>,
+        inner_token: &str,
+        key: &K::EncryptionKey,
+    ) -> Result<String, CreationError> {
+        let content_type = Some("JWT".to_owned());
+        self.encrypt_payload(header, content_type, inner_token.as_bytes(), key)
+    }
+
+    fn encrypt_payload(
+        &self,
+        header: &Header<impl Serialize>,
+        content_type: Option<String>,
+        payload: &[u8],
+        key: &K::EncryptionKey,
+    ) -> Result<String, CreationError> {
+        let content_encryption = self.content_encryption.name();
+        let content_key_len = self.content_encryption.key_len();
+        let content_key = self
+            .key_management
+            .encrypt_key(key, &content_encryption, content_key_len)
+            .map_err(CreationError::Encryption)?;
+
+        let complete_header = CompleteJweHeader {
+            algorithm: self.key_management.name(),
+            content_encryption,
+            content_type,
+            compression: None,
+            critical: None,
+            key_management: content_key.header,
+            inner: header,
+        };
+        let header = serde_json::to_string(&complete_header).map_err(CreationError::Header)?;
+        let mut buffer = Vec::new();
+        encode_base64_buf(&header, &mut buffer);
+        let content = self
+            .content_encryption
+            .encrypt(&content_key.key, payload, &buffer)
+            .map_err(CreationError::Encryption)?;
+
+        for part in [
+            &content_key.encrypted_key,
+            &content.iv,
+            &content.ciphertext,
+            &content.tag,
+        ] {
+            buffer.push(b'.');
+            encode_base64_buf(part, &mut buffer);
+        }
+        // SAFETY: safe by construction: base64 alphabet and `.` char are valid UTF-8.
+        Ok(unsafe { String::from_utf8_unchecked(buffer) })
+    }
+
+    /// Decrypts the provided `token` and deserializes its claims.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`ValidationError::AlgorithmMismatch`] if the key management or content
+    ///   encryption algorithm of the token differs from the algorithm used by this instance.
+    /// - Returns [`ValidationError::UnsupportedCompression`] if the token is compressed
+    ///   (i.e., has the `zip` header field).
+    /// - Returns [`ValidationError::UnknownCriticalExtension`] if the token header lists
+    ///   any critical extensions (the `crit` header field); no extensions are supported
+    ///   for encrypted tokens.
+    /// - Returns [`ValidationError::DecryptionFailed`] if the token cannot be decrypted.
+    ///   For security reasons, this error does not provide the cause of the failure.
+    /// - Returns [`ValidationError::UnexpectedContentType`] if the token is nested; such tokens
+    ///   should be decrypted with [`Self::decrypt_nested()`].
+    /// - Returns [`ValidationError::MalformedClaims`] if decrypted claims cannot be deserialized.
+    pub fn decrypt<T, H>(
+        &self,
+        token: &EncryptedToken<'_, H>,
+        key: &K::DecryptionKey,
+    ) -> Result<Token<T, H>, ValidationError>
+    where
+        T: DeserializeOwned,
+        H: Clone,
+    {
+        if token.is_nested() {
+            return Err(ValidationError::UnexpectedContentType("JWT".into()));
+        }
+        let payload = self.decrypt_payload(token, key)?;
+        let claims = serde_json::from_slice(&payload).map_err(ValidationError::MalformedClaims)?;
+        Ok(Token::new(token.header.clone(), claims))
+    }
+
+    /// Decrypts the provided nested `token` (i.e., one with the `cty` header field set to `JWT`).
+    /// The inner token should be parsed and validated as the next step.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Self::decrypt()`], except that
+    /// [`ValidationError::UnexpectedContentType`] is returned if the token is *not* nested.
+    pub fn decrypt_nested<H: Clone>(
+        &self,
+        token: &EncryptedToken<'_, H>,
+        key: &K::DecryptionKey,
+    ) -> Result<NestedToken<H>, ValidationError> {
+        if !token.is_nested() {
+            let content_type = token.content_type().unwrap_or("JSON");
+            return Err(ValidationError::UnexpectedContentType(content_type.into()));
+        }
+        let payload = self.decrypt_payload(token, key)?;
+        Ok(NestedToken::new(token.header.clone(), payload.to_vec()))
+    }
+
+    fn decrypt_payload<H>(
+        &self,
+        token: &EncryptedToken<'_, H>,
+        key: &K::DecryptionKey,
+    ) -> Result<SecretBytes<'static>, ValidationError> {
+        let expected_alg = self.key_management.name();
+        if expected_alg != token.algorithm {
+            return Err(ValidationError::AlgorithmMismatch {
+                expected: expected_alg.into_owned(),
+                actual: token.algorithm.clone(),
+            });
+        }
+        let expected_enc = self.content_encryption.name();
+        if expected_enc != token.content_encryption {
+            return Err(ValidationError::AlgorithmMismatch {
+                expected: expected_enc.into_owned(),
+                actual: token.content_encryption.clone(),
+            });
+        }
+        if let Some(compression) = &token.compression {
+            return Err(ValidationError::UnsupportedCompression(compression.clone()));
+        }
+        if let Some(extension) = token.critical_extensions.first() {
+            return Err(ValidationError::UnknownCriticalExtension(extension.clone()));
+        }
+
+        let content_key_len = self.content_encryption.key_len();
+        let content_key = self
+            .key_management
+            .decrypt_key(
+                key,
+                &token.key_management,
+                &token.encrypted_key,
+                &token.content_encryption,
+                content_key_len,
+            )
+            .map_err(|_| ValidationError::DecryptionFailed)?;
+        let payload = self
+            .content_encryption
+            .decrypt(
+                &content_key,
+                &token.content,
+                token.protected_header.as_bytes(),
+            )
+            .map_err(|_| ValidationError::DecryptionFailed)?;
+        Ok(SecretBytes::owned(payload))
+    }
+}
+
+/// Parsed, but not decrypted token encrypted using [compact JWE serialization].
+///
+/// Similar to [`UntrustedToken`](crate::UntrustedToken), the type param ([`Empty`] by default)
+/// corresponds to the [additional information] enclosed in the token [`Header`].
+/// The protected header can be inspected before decryption (e.g., to select a decryption key),
+/// but it is authenticated only during decryption.
+///
+/// An `EncryptedToken` can be parsed from a string using the [`TryFrom`] implementation.
+/// As a shortcut, a token without additional header info can be created using [`Self::new()`].
+///
+/// [compact JWE serialization]: https://www.rfc-editor.org/rfc/rfc7516#section-7.1
+/// [additional information]: Header#other_fields
+#[derive(Clone)]
+pub struct EncryptedToken<'a, H = Empty> {
+    protected_header: Cow<'a, str>,
+    header: Header<H>,
+    algorithm: String,
+    content_encryption: String,
+    content_type: Option<String>,
+    compression: Option<String>,
+    critical_extensions: Vec<String>,
+    key_management: KeyManagementHeader,
+    encrypted_key: Vec<u8>,
+    content: EncryptedContent,
+}
+
+impl<H: fmt::Debug> fmt::Debug for EncryptedToken<'_, H> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("EncryptedToken")
+            .field("header", &self.header)
+            .field("algorithm", &self.algorithm)
+            .field("content_encryption", &self.content_encryption)
+            .field("content_type", &self.content_type)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, H: DeserializeOwned> TryFrom<&'a str> for EncryptedToken<'a, H> {
+    type Error = ParseError;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        let token_parts: Vec<_> = s.splitn(6, '.').collect();
+        let [protected_header, encrypted_key, iv, ciphertext, tag] = token_parts[..] else {
+            return Err(ParseError::InvalidTokenStructure);
+        };
+
+        let decode = |part: &str| {
+            Base64UrlUnpadded::decode_vec(part).map_err(|_| ParseError::InvalidBase64Encoding)
+        };
+        let raw_header = decode(protected_header)?;
+        let header: CompleteJweHeader<'_, Header<H>> =
+            serde_json::from_slice(&raw_header).map_err(ParseError::MalformedHeader)?;
+        if let Some(critical) = &header.critical {
+            let raw_header =
+                serde_json::from_slice(&raw_header).map_err(ParseError::MalformedHeader)?;
+            check_critical_params(critical, &raw_header)?;
+        }
+        // Only JSON claims and nested JWTs are supported as the payload.
+        if let Some(content_type) = &header.content_type {
+            let is_supported = content_type.eq_ignore_ascii_case("json")
+                || content_type.eq_ignore_ascii_case("jwt");
+            if !is_supported {
+                return Err(ParseError::UnsupportedContentType(content_type.clone()));
+            }
+        }
+        Ok(Self {
+            protected_header: Cow::Borrowed(protected_header),
+            header: header.inner,
+            algorithm: header.algorithm.into_owned(),
+            content_encryption: header.content_encryption.into_owned(),
+            content_type: header.content_type,
+            compression: header.compression,
+            critical_extensions: header.critical.unwrap_or_default(),
+            key_management: header.key_management,
+            encrypted_key: decode(encrypted_key)?,
+            content: EncryptedContent {
+                iv: decode(iv)?,
+                ciphertext: decode(ciphertext)?,
+                tag: decode(tag)?,
+            },
+        })
+    }
+}
+
+impl<'a> EncryptedToken<'a> {
+    /// Creates an encrypted token from a string. This is a shortcut for calling the [`TryFrom`]
+    /// conversion.
+    pub fn new<S: AsRef<str> + ?Sized>(s: &'a S) -> Result<Self, ParseError> {
+        Self::try_from(s.as_ref())
+    }
+}
+
+impl<H> EncryptedToken<'_, H> {
+    /// Converts this token to an owned form.
+    pub fn into_owned(self) -> EncryptedToken<'static, H> {
+        EncryptedToken {
+            protected_header: Cow::Owned(self.protected_header.into_owned()),
+            header: self.header,
+            algorithm: self.algorithm,
+            content_encryption: self.content_encryption,
+            content_type: self.content_type,
+            compression: self.compression,
+            critical_extensions: self.critical_extensions,
+            key_management: self.key_management,
+            encrypted_key: self.encrypted_key,
+            content: self.content,
+        }
+    }
+
+    /// Gets the token header.
+    pub fn header(&self) -> &Header<H> {
+        &self.header
+    }
+
+    /// Gets the key management algorithm (the `alg` header field).
+    pub fn algorithm(&self) -> &str {
+        &self.algorithm
+    }
+
+    /// Gets the content encryption algorithm (the `enc` header field).
+    pub fn content_encryption(&self) -> &str {
+        &self.content_encryption
+    }
+
+    /// Gets the content type (the `cty` header field), if specified.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Gets the compression algorithm (the `zip` header field), if specified. Compressed tokens
+    /// cannot be decrypted.
+    pub fn compression(&self) -> Option<&str> {
+        self.compression.as_deref()
+    }
+
+    /// Returns header extensions that are marked as critical in the token header
+    /// (the `crit` header parameter). Tokens with critical extensions cannot be decrypted.
+    pub fn critical_extensions(&self) -> &[String] {
+        &self.critical_extensions
+    }
+
+    fn is_nested(&self) -> bool {
+        self.content_type()
+            .map_or(false, |ty| ty.eq_ignore_ascii_case("JWT"))
+    }
+
+    /// Gets the header fields specific to the key management algorithm (e.g., the ephemeral
+    /// public key for ECDH-ES key agreement).
+    pub fn key_management_header(&self) -> &KeyManagementHeader {
+        &self.key_management
+    }
+
+    /// Returns the encrypted content encryption key. May be empty (e.g., for the `dir`
+    /// key management algorithm).
+    pub fn encrypted_key(&self) -> &[u8] {
+        &self.encrypted_key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::{format, ToOwned, ToString};
+
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn roundtrip_with_custom_header() {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        struct Extensions {
+            custom: String,
+        }
+
+        let jwe = JweAlgorithm::new(Direct, A128Gcm);
+        let key = SymmetricKey::new([1_u8; 16]);
+        let header = Header::new(Extensions {
+            custom: "value".to_owned(),
+        });
+        let claims = Claims::empty().set_issuer("me");
+        let token = jwe.encrypt(&header, &claims, &key).unwrap();
+        assert_eq!(token.split('.').count(), 5);
+        // The encrypted key must be empty for the `dir` algorithm.
+        assert_eq!(token.split('.').nth(1), Some(""));
+
+        let token: EncryptedToken<'_, Extensions> = token.as_str().try_into().unwrap();
+        assert_eq!(token.header().other_fields.custom, "value");
+        assert_eq!(token.algorithm(), "dir");
+        assert_eq!(token.content_encryption(), "A128GCM");
+        assert_eq!(token.content_type(), None);
+
+        let token = jwe.decrypt::<Empty, _>(&token, &key).unwrap();
+        assert_eq!(token.claims().issuer.as_deref(), Some("me"));
+        assert_eq!(token.header().other_fields.custom, "value");
+    }
+
+    #[test]
+    fn algorithm_mismatch() {
+        let key = SymmetricKey::new([1_u8; 32]);
+        let token = JweAlgorithm::new(Direct, A256Gcm)
+            .encrypt(&Header::empty(), &Claims::empty(), &key)
+            .unwrap();
+        let token = EncryptedToken::new(&token).unwrap();
+
+        let key = SymmetricKey::new([1_u8; 16]);
+        let err = JweAlgorithm::new(Direct, A128Gcm)
+            .decrypt::<Empty, _>(&token, &key)
+            .unwrap_err();
+        assert_matches!(
+            err,
+            ValidationError::AlgorithmMismatch { expected, actual }
+                if expected == "A128GCM" && actual == "A256GCM"
+        );
+    }
+
+    #[test]
+    fn tampered_token() {
+        let jwe = JweAlgorithm::new(Direct, A256Gcm);
+        let key = SymmetricKey::new([1_u8; 32]);
+        let token = jwe
+            .encrypt(&Header::empty(), &Claims::empty(), &key)
+            .unwrap();
+
+        // Change the header (which is authenticated as AAD).
+        let (_, rest) = token.split_once('.').unwrap();
+        let header = r#"{"alg":"dir","enc":"A256GCM","kid":"other"}"#;
+        let mut tampered = Base64UrlUnpadded::encode_string(header.as_bytes());
+        tampered.push('.');
+        tampered.push_str(rest);
+        let tampered = EncryptedToken::new(&tampered).unwrap();
+        assert_eq!(tampered.header().key_id.as_deref(), Some("other"));
+        let err = jwe.decrypt::<Empty, _>(&tampered, &key).unwrap_err();
+        assert_matches!(err, ValidationError::DecryptionFailed);
+
+        // Use a wrong key.
+        let token = EncryptedToken::new(&token).unwrap();
+        let wrong_key = SymmetricKey::new([2_u8; 32]);
+        let err = jwe.decrypt::<Empty, _>(&token, &wrong_key).unwrap_err();
+        assert_matches!(err, ValidationError::DecryptionFailed);
+        // Use a key with an invalid length.
+        let wrong_key = SymmetricKey::new([1_u8; 16]);
+        let err = jwe.decrypt::<Empty, _>(&token, &wrong_key).unwrap_err();
+        assert_matches!(err, ValidationError::DecryptionFailed);
+    }
+
+    #[test]
+    fn unsupported_content_type() {
+        let jwe = JweAlgorithm::new(Direct, A256Gcm);
+        let key = SymmetricKey::new([1_u8; 32]);
+        let token = jwe
+            .encrypt(&Header::empty(), &Claims::empty(), &key)
+            .unwrap();
+        let (_, rest) = token.split_once('.').unwrap();
+
+        for content_type in ["CBOR", "text/plain"] {
+            let header = format!(r#"{{"alg":"dir","enc":"A256GCM","cty":"{content_type}"}}"#);
+            let mut token = Base64UrlUnpadded::encode_string(header.as_bytes());
+            token.push('.');
+            token.push_str(rest);
+            let err = EncryptedToken::new(&token).unwrap_err();
+            assert_matches!(err, ParseError::UnsupportedContentType(ty) if ty == content_type);
+        }
+    }
+
+    #[test]
+    fn compressed_or_critical_token() {
+        let jwe = JweAlgorithm::new(Direct, A256Gcm);
+        let key = SymmetricKey::new([1_u8; 32]);
+        let token = jwe
+            .encrypt(&Header::empty(), &Claims::empty(), &key)
+            .unwrap();
+        let (_, rest) = token.split_once('.').unwrap();
+        let with_header = |header: &str| {
+            let mut token = Base64UrlUnpadded::encode_string(header.as_bytes());
+            token.push('.');
+            token.push_str(rest);
+            token
+        };
+
+        let compressed = with_header(r#"{"alg":"dir","enc":"A256GCM","zip":"DEF"}"#);
+        let compressed = EncryptedToken::new(&compressed).unwrap();
+        assert_eq!(compressed.compression(), Some("DEF"));
+        let err = jwe.decrypt::<Empty, _>(&compressed, &key).unwrap_err();
+        assert_matches!(err, ValidationError::UnsupportedCompression(alg) if alg == "DEF");
+
+        let critical = with_header(r#"{"alg":"dir","enc":"A256GCM","crit":["exp"],"exp":1}"#);
+        let critical = EncryptedToken::new(&critical).unwrap();
+        assert_eq!(critical.critical_extensions(), ["exp"]);
+        let err = jwe.decrypt::<Empty, _>(&critical, &key).unwrap_err();
+        assert_matches!(err, ValidationError::UnknownCriticalExtension(ext) if ext == "exp");
+
+        let malformed = with_header(r#"{"alg":"dir","enc":"A256GCM","crit":["exp"]}"#);
+        let err = EncryptedToken::new(&malformed).unwrap_err();
+        assert_matches!(err, ParseError::MalformedHeader(err) if err.to_string().contains("exp"));
+    }
+
+    #[test]
+    fn invalid_key_len_on_encryption() {
+        let key = SymmetricKey::new([1_u8; 16]);
+        let err = JweAlgorithm::new(Direct, A256Gcm)
+            .encrypt(&Header::empty(), &Claims::empty(), &key)
+            .unwrap_err();
+        assert_matches!(err, CreationError::Encryption(_));
+        assert!(err.to_string().contains("key length"), "{err}");
+    }
+
+    #[test]
+    fn parsing_errors() {
+        let err = EncryptedToken::new("a.b.c").unwrap_err();
+        assert_matches!(err, ParseError::InvalidTokenStructure);
+        let err = EncryptedToken::new("a.b.c.d.e.f").unwrap_err();
+        assert_matches!(err, ParseError::InvalidTokenStructure);
+        let err = EncryptedToken::new("eyJ.b.c.d.!").unwrap_err();
+        assert_matches!(err, ParseError::InvalidBase64Encoding);
+
+        let header = Base64UrlUnpadded::encode_string(br#"{"alg":"dir"}"#);
+        let err = EncryptedToken::new(&format!("{header}....")).unwrap_err();
+        assert_matches!(err, ParseError::MalformedHeader(err) if err.to_string().contains("enc"));
+    }
+
+    #[test]
+    fn decrypting_rfc7516_example() {
+        // Example from RFC 7516, Appendix A.3.
+        const TOKEN: &str = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.\
+             6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.\
+             AxY8DCtDaGlsbGljb3RoZQ.\
+             KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.\
+             U0m_YmjN04DJvceFICbCVQ";
+        const KEY: &str = "GawgguFyGrWKav7AX4VKUg";
+
+        let key = SymmetricKey::new(Base64UrlUnpadded::decode_vec(KEY).unwrap());
+        let token = EncryptedToken::new(TOKEN).unwrap();
+        assert_eq!(token.algorithm(), "A128KW");
+        assert_eq!(token.content_encryption(), "A128CBC-HS256");
+
+        let jwe = JweAlgorithm::new(A128Kw, A128CbcHs256);
+        let payload = jwe.decrypt_payload(&token, &key).unwrap();
+        assert_eq!(payload.as_ref(), b"Live long and prosper.");
+
+        let wrong_key = SymmetricKey::new([0; 16]);
+        let err = jwe.decrypt_payload(&token, &wrong_key).unwrap_err();
+        assert_matches!(err, ValidationError::DecryptionFailed);
+    }
+}
@@ -0,0 +1,126 @@
+//! AES-CBC-HMAC-SHA2 content encryption algorithms (`A128CBC-HS256` and `A256CBC-HS512`)
+//! as per [RFC 7518, section 5.2](https://www.rfc-editor.org/rfc/rfc7518#section-5.2).
+
+use aes_gcm::aes::{Aes128, Aes256};
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use hmac::{Hmac, Mac as _};
+use rand_core::{OsRng, RngCore};
+use sha2::{Sha256, Sha512};
+
+use crate::{
+    alloc::{Cow, Vec},
+    jwe::{ContentEncryption, EncryptedContent},
+};
+
+/// Byte length of the initialization vector (equal to the AES block size).
+const IV_LEN: usize = 16;
+
+macro_rules! define_cbc_algorithm {
+    (
+        $(#[$($attr:meta)+])*
+        struct $name:ident<$cipher:ident, $digest:ident>($alg_name:tt, key: $key_len:expr, tag: $tag_len:expr);
+    ) => {
+        $(#[$($attr)+])*
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $name;
+
+        impl $name {
+            const KEY_LEN: usize = $key_len;
+            const TAG_LEN: usize = $tag_len;
+
+            /// Splits the content encryption key into MAC and encryption keys.
+            fn split_key(key: &[u8]) -> anyhow::Result<(&[u8], &[u8])> {
+                anyhow::ensure!(
+                    key.len() == Self::KEY_LEN,
+                    "invalid key length: expected {} bytes, got {} bytes",
+                    Self::KEY_LEN,
+                    key.len()
+                );
+                Ok(key.split_at(Self::KEY_LEN / 2))
+            }
+
+            fn mac(mac_key: &[u8], aad: &[u8], iv: &[u8], ciphertext: &[u8]) -> Hmac<$digest> {
+                let aad_bit_len = u64::try_from(aad.len())
+                    .ok()
+                    .and_then(|len| len.checked_mul(8))
+                    .expect("additional authenticated data is too long");
+
+                let mut mac = Hmac::<$digest>::new_from_slice(mac_key)
+                    .expect("HMACs work with any key size");
+                mac.update(aad);
+                mac.update(iv);
+                mac.update(ciphertext);
+                mac.update(&aad_bit_len.to_be_bytes());
+                mac
+            }
+        }
+
+        impl ContentEncryption for $name {
+            fn name(&self) -> Cow<'static, str> {
+                Cow::Borrowed($alg_name)
+            }
+
+            fn key_len(&self) -> usize {
+                Self::KEY_LEN
+            }
+
+            fn encrypt(
+                &self,
+                key: &[u8],
+                plaintext: &[u8],
+                aad: &[u8],
+            ) -> anyhow::Result<EncryptedContent> {
+                let (mac_key, enc_key) = Self::split_key(key)?;
+                let mut iv = [0_u8; IV_LEN];
+                OsRng.fill_bytes(&mut iv);
+
+                let ciphertext = cbc::Encryptor::<$cipher>::new_from_slices(enc_key, &iv)
+                    .expect("key and IV lengths are checked")
+                    .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+                let tag = Self::mac(mac_key, aad, &iv, &ciphertext).finalize().into_bytes();
+                Ok(EncryptedContent {
+                    iv: iv.to_vec(),
+                    ciphertext,
+                    tag: tag[..Self::TAG_LEN].to_vec(),
+                })
+            }
+
+            fn decrypt(
+                &self,
+                key: &[u8],
+                content: &EncryptedContent,
+                aad: &[u8],
+            ) -> anyhow::Result<Vec<u8>> {
+                let (mac_key, enc_key) = Self::split_key(key)?;
+                anyhow::ensure!(
+                    content.iv.len() == IV_LEN,
+                    "invalid initialization vector length"
+                );
+                anyhow::ensure!(
+                    content.tag.len() == Self::TAG_LEN,
+                    "invalid authentication tag length"
+                );
+
+                // The tag is checked before decryption in constant time.
+                Self::mac(mac_key, aad, &content.iv, &content.ciphertext)
+                    .verify_truncated_left(&content.tag)
+                    .map_err(|_| anyhow::anyhow!("authentication tag mismatch"))?;
+                cbc::Decryptor::<$cipher>::new_from_slices(enc_key, &content.iv)
+                    .expect("key and IV lengths are checked")
+                    .decrypt_padded_vec_mut::<Pkcs7>(&content.ciphertext)
+                    .map_err(|_| anyhow::anyhow!("invalid padding"))
+            }
+        }
+    };
+}
+
+define_cbc_algorithm!(
+    /// `A128CBC-HS256` content encryption algorithm: AES-128 in the CBC mode
+    /// with HMAC-SHA-256 authentication. Uses a 256-bit content encryption key.
+    struct A128CbcHs256<Aes128, Sha256>("A128CBC-HS256", key: 32, tag: 16);
+);
+define_cbc_algorithm!(
+    /// `A256CBC-HS512` content encryption algorithm: AES-256 in the CBC mode
+    /// with HMAC-SHA-512 authentication. Uses a 512-bit content encryption key.
+    struct A256CbcHs512<Aes256, Sha512>("A256CBC-HS512", key: 64, tag: 32);
+);
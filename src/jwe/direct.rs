@@ -0,0 +1,51 @@
+//! Direct use of a shared symmetric key as the content encryption key (`dir`).
+
+use crate::{
+    alg::SecretBytes,
+    alloc::{Cow, Vec},
+    jwe::{ContentKey, KeyManagement, KeyManagementHeader, SymmetricKey},
+};
+
+/// `dir` key management algorithm: a [`SymmetricKey`] shared in advance is used
+/// as the content encryption key. The encrypted key in the token is empty.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Direct;
+
+impl KeyManagement for Direct {
+    type EncryptionKey = SymmetricKey;
+    type DecryptionKey = SymmetricKey;
+
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("dir")
+    }
+
+    fn encrypt_key(
+        &self,
+        key: &SymmetricKey,
+        _content_encryption: &str,
+        content_key_len: usize,
+    ) -> anyhow::Result<ContentKey> {
+        key.ensure_len(content_key_len)?;
+        Ok(ContentKey {
+            key: key.0.clone(),
+            encrypted_key: Vec::new(),
+            header: KeyManagementHeader::default(),
+        })
+    }
+
+    fn decrypt_key(
+        &self,
+        key: &SymmetricKey,
+        _header: &KeyManagementHeader,
+        encrypted_key: &[u8],
+        _content_encryption: &str,
+        content_key_len: usize,
+    ) -> anyhow::Result<SecretBytes<'static>> {
+        anyhow::ensure!(
+            encrypted_key.is_empty(),
+            "encrypted key must be empty for `dir` algorithm"
+        );
+        key.ensure_len(content_key_len)?;
+        Ok(key.0.clone())
+    }
+}
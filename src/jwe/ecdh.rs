@@ -0,0 +1,477 @@
+//! ECDH-ES key agreement (`ECDH-ES`, `ECDH-ES+A128KW` and `ECDH-ES+A256KW`).
+
+use sha2::{Digest, Sha256};
+
+use core::marker::PhantomData;
+
+use crate::{
+    alg::SecretBytes,
+    alloc::{Cow, Vec},
+    jwe::{
+        kw::{generate_content_key, KeyWrap},
+        ContentKey, KeyManagement, KeyManagementHeader,
+    },
+    jwk::JsonWebKey,
+};
+
+/// Elliptic curve that can be used for [`EcdhEs`] key agreement.
+pub trait EcdhCurve {
+    /// Public key on the curve.
+    type PublicKey;
+    /// Secret key on the curve.
+    type SecretKey;
+
+    /// Generates an ephemeral keypair and computes a shared secret with the specified
+    /// `public_key`. Returns the ephemeral public key in the JWK form together with
+    /// the shared secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key agreement fails.
+    fn ephemeral_agreement(
+        public_key: &Self::PublicKey,
+    ) -> anyhow::Result<(JsonWebKey<'static>, SecretBytes<'static>)>;
+
+    /// Computes a shared secret from the `secret_key` and the `ephemeral_key` in the JWK form.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ephemeral key is invalid, or if the key agreement fails.
+    fn agreement(
+        secret_key: &Self::SecretKey,
+        ephemeral_key: &JsonWebKey<'_>,
+    ) -> anyhow::Result<SecretBytes<'static>>;
+}
+
+/// Derives a key from the shared secret using the Concat KDF with SHA-256 as specified
+/// in [RFC 7518, section 4.6.2](https://www.rfc-editor.org/rfc/rfc7518#section-4.6.2).
+fn concat_kdf(
+    shared_secret: &[u8],
+    algorithm_id: &str,
+    producer_info: &[u8],
+    recipient_info: &[u8],
+    key_len: usize,
+) -> SecretBytes<'static> {
+    fn len_prefix(bytes: &[u8]) -> [u8; 4] {
+        u32::try_from(bytes.len())
+            .expect("KDF input is too long")
+            .to_be_bytes()
+    }
+
+    let key_bit_len = u32::try_from(key_len * 8).expect("derived key is too long");
+    let mut output = Vec::with_capacity(key_len + 32);
+    let mut counter = 1_u32;
+    while output.len() < key_len {
+        let mut digest = Sha256::new();
+        digest.update(counter.to_be_bytes());
+        digest.update(shared_secret);
+        for part in [algorithm_id.as_bytes(), producer_info, recipient_info] {
+            digest.update(len_prefix(part));
+            digest.update(part);
+        }
+        digest.update(key_bit_len.to_be_bytes());
+        output.extend_from_slice(&digest.finalize());
+        counter += 1;
+    }
+    output.truncate(key_len);
+    SecretBytes::owned(output)
+}
+
+/// ECDH-ES key management algorithm using the elliptic curve `C`.
+///
+/// The algorithm can be used either for [direct key agreement](Self::direct()),
+/// in which the content encryption key is derived from the shared secret, or together with
+/// AES key wrap ([`Self::a128kw()`], [`Self::a256kw()`]), in which the derived key
+/// is used to wrap a random content encryption key. In both cases, an ephemeral public key
+/// is included into the token header (the `epk` field).
+#[derive(Debug, Clone, Copy)]
+pub struct EcdhEs<C> {
+    key_wrap: Option<KeyWrap>,
+    _curve: PhantomData<C>,
+}
+
+impl<C> Default for EcdhEs<C> {
+    fn default() -> Self {
+        Self::direct()
+    }
+}
+
+impl<C> EcdhEs<C> {
+    /// Creates an `ECDH-ES` algorithm with direct key agreement.
+    pub const fn direct() -> Self {
+        Self {
+            key_wrap: None,
+            _curve: PhantomData,
+        }
+    }
+
+    /// Creates an `ECDH-ES+A128KW` algorithm.
+    pub const fn a128kw() -> Self {
+        Self {
+            key_wrap: Some(KeyWrap::A128),
+            _curve: PhantomData,
+        }
+    }
+
+    /// Creates an `ECDH-ES+A256KW` algorithm.
+    pub const fn a256kw() -> Self {
+        Self {
+            key_wrap: Some(KeyWrap::A256),
+            _curve: PhantomData,
+        }
+    }
+}
+
+impl<C: EcdhCurve> KeyManagement for EcdhEs<C> {
+    type EncryptionKey = C::PublicKey;
+    type DecryptionKey = C::SecretKey;
+
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self.key_wrap {
+            None => "ECDH-ES",
+            Some(KeyWrap::A128) => "ECDH-ES+A128KW",
+            Some(KeyWrap::A256) => "ECDH-ES+A256KW",
+        })
+    }
+
+    fn encrypt_key(
+        &self,
+        key: &C::PublicKey,
+        content_encryption: &str,
+        content_key_len: usize,
+    ) -> anyhow::Result<ContentKey> {
+        let (ephemeral_key, shared_secret) = C::ephemeral_agreement(key)?;
+        let header = KeyManagementHeader {
+            ephemeral_key: Some(ephemeral_key),
+            ..KeyManagementHeader::default()
+        };
+
+        Ok(if let Some(key_wrap) = self.key_wrap {
+            let kek = concat_kdf(&shared_secret, &self.name(), &[], &[], key_wrap.key_len());
+            let content_key = generate_content_key(content_key_len);
+            ContentKey {
+                encrypted_key: key_wrap.wrap(&kek, &content_key)?,
+                key: content_key,
+                header,
+            }
+        } else {
+            ContentKey {
+                key: concat_kdf(
+                    &shared_secret,
+                    content_encryption,
+                    &[],
+                    &[],
+                    content_key_len,
+                ),
+                encrypted_key: Vec::new(),
+                header,
+            }
+        })
+    }
+
+    fn decrypt_key(
+        &self,
+        key: &C::SecretKey,
+        header: &KeyManagementHeader,
+        encrypted_key: &[u8],
+        content_encryption: &str,
+        content_key_len: usize,
+    ) -> anyhow::Result<SecretBytes<'static>> {
+        let ephemeral_key = header
+            .ephemeral_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("`epk` header field is missing"))?;
+        let shared_secret = C::agreement(key, ephemeral_key)?;
+        let producer_info = header
+            .agreement_party_u_info
+            .as_deref()
+            .unwrap_or_default();
+        let recipient_info = header
+            .agreement_party_v_info
+            .as_deref()
+            .unwrap_or_default();
+
+        if let Some(key_wrap) = self.key_wrap {
+            let kek = concat_kdf(
+                &shared_secret,
+                &self.name(),
+                producer_info,
+                recipient_info,
+                key_wrap.key_len(),
+            );
+            let content_key = key_wrap.unwrap(&kek, encrypted_key)?;
+            anyhow::ensure!(
+                content_key.len() == content_key_len,
+                "invalid content encryption key length"
+            );
+            Ok(content_key)
+        } else {
+            anyhow::ensure!(
+                encrypted_key.is_empty(),
+                "encrypted key must be empty for `ECDH-ES` algorithm"
+            );
+            Ok(concat_kdf(
+                &shared_secret,
+                content_encryption,
+                producer_info,
+                recipient_info,
+                content_key_len,
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "p256")]
+mod p256_impl {
+    use p256::{
+        ecdh::{diffie_hellman, EphemeralSecret},
+        ecdsa::{SigningKey, VerifyingKey},
+        elliptic_curve::sec1::ToEncodedPoint,
+        PublicKey, SecretKey,
+    };
+    use rand_core::OsRng;
+
+    use super::EcdhCurve;
+    use crate::{
+        alg::SecretBytes,
+        alloc::Cow,
+        jwk::{JsonWebKey, JwkError},
+    };
+
+    /// P-256 elliptic curve (aka secp256r1) for [`EcdhEs`](super::EcdhEs) key agreement.
+    /// Uses [`PublicKey`] and [`SecretKey`] from the `p256` crate as keys.
+    #[derive(Debug, Clone, Copy, Default)]
+    #[cfg_attr(docsrs, doc(cfg(feature = "p256")))]
+    pub struct P256;
+
+    fn create_jwk(pk: &PublicKey, sk: Option<&SecretKey>) -> JsonWebKey<'static> {
+        let uncompressed = pk.to_encoded_point(false);
+        JsonWebKey::EllipticCurve {
+            curve: "P-256".into(),
+            x: Cow::Owned(uncompressed.x().expect("x coord").to_vec()),
+            y: Cow::Owned(uncompressed.y().expect("y coord").to_vec()),
+            secret: sk.map(|sk| SecretBytes::owned(sk.to_bytes().to_vec())),
+        }
+    }
+
+    impl From<&PublicKey> for JsonWebKey<'_> {
+        fn from(key: &PublicKey) -> Self {
+            create_jwk(key, None)
+        }
+    }
+
+    impl TryFrom<&JsonWebKey<'_>> for PublicKey {
+        type Error = JwkError;
+
+        fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+            VerifyingKey::try_from(jwk).map(Self::from)
+        }
+    }
+
+    impl From<&SecretKey> for JsonWebKey<'_> {
+        fn from(key: &SecretKey) -> Self {
+            create_jwk(&key.public_key(), Some(key))
+        }
+    }
+
+    impl TryFrom<&JsonWebKey<'_>> for SecretKey {
+        type Error = JwkError;
+
+        fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+            SigningKey::try_from(jwk).map(Self::from)
+        }
+    }
+
+    impl EcdhCurve for P256 {
+        type PublicKey = PublicKey;
+        type SecretKey = SecretKey;
+
+        fn ephemeral_agreement(
+            public_key: &PublicKey,
+        ) -> anyhow::Result<(JsonWebKey<'static>, SecretBytes<'static>)> {
+            let ephemeral_secret = EphemeralSecret::random(&mut OsRng);
+            let ephemeral_key = create_jwk(&ephemeral_secret.public_key(), None);
+            let shared_secret = ephemeral_secret.diffie_hellman(public_key);
+            let shared_secret = shared_secret.raw_secret_bytes().to_vec();
+            Ok((ephemeral_key, SecretBytes::owned(shared_secret)))
+        }
+
+        fn agreement(
+            secret_key: &SecretKey,
+            ephemeral_key: &JsonWebKey<'_>,
+        ) -> anyhow::Result<SecretBytes<'static>> {
+            let ephemeral_key =
+                PublicKey::try_from(ephemeral_key).map_err(|err| anyhow::anyhow!(err))?;
+            let shared_secret =
+                diffie_hellman(secret_key.to_nonzero_scalar(), ephemeral_key.as_affine());
+            let shared_secret = shared_secret.raw_secret_bytes().to_vec();
+            Ok(SecretBytes::owned(shared_secret))
+        }
+    }
+}
+
+#[cfg(feature = "p256")]
+pub use self::p256_impl::P256;
+
+#[cfg(feature = "x25519-dalek")]
+mod x25519_impl {
+    use rand_core::OsRng;
+    use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+    use super::EcdhCurve;
+    use crate::{
+        alg::SecretBytes,
+        alloc::Cow,
+        jwk::{JsonWebKey, JwkError, KeyType},
+    };
+
+    const KEY_LEN: usize = 32;
+
+    /// X25519 elliptic curve for [`EcdhEs`](super::EcdhEs) key agreement.
+    /// Uses [`PublicKey`] and [`StaticSecret`] from the `x25519-dalek` crate as keys.
+    #[derive(Debug, Clone, Copy, Default)]
+    #[cfg_attr(docsrs, doc(cfg(feature = "x25519-dalek")))]
+    pub struct X25519;
+
+    fn create_jwk(pk: &PublicKey, sk: Option<&StaticSecret>) -> JsonWebKey<'static> {
+        JsonWebKey::KeyPair {
+            curve: Cow::Borrowed("X25519"),
+            x: Cow::Owned(pk.as_bytes().to_vec()),
+            secret: sk.map(|sk| SecretBytes::owned(sk.as_bytes().to_vec())),
+        }
+    }
+
+    fn public_key_bytes(jwk: &JsonWebKey<'_>) -> Result<[u8; KEY_LEN], JwkError> {
+        let JsonWebKey::KeyPair { curve, x, .. } = jwk else {
+            return Err(JwkError::key_type(jwk, KeyType::KeyPair));
+        };
+        JsonWebKey::ensure_curve(curve, "X25519")?;
+        JsonWebKey::ensure_len("x", x, KEY_LEN)?;
+        let mut bytes = [0_u8; KEY_LEN];
+        bytes.copy_from_slice(x);
+        Ok(bytes)
+    }
+
+    impl From<&PublicKey> for JsonWebKey<'_> {
+        fn from(key: &PublicKey) -> Self {
+            create_jwk(key, None)
+        }
+    }
+
+    impl TryFrom<&JsonWebKey<'_>> for PublicKey {
+        type Error = JwkError;
+
+        fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+            public_key_bytes(jwk).map(Self::from)
+        }
+    }
+
+    impl From<&StaticSecret> for JsonWebKey<'_> {
+        fn from(key: &StaticSecret) -> Self {
+            create_jwk(&PublicKey::from(key), Some(key))
+        }
+    }
+
+    impl TryFrom<&JsonWebKey<'_>> for StaticSecret {
+        type Error = JwkError;
+
+        fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+            let public_key = public_key_bytes(jwk)?;
+            let JsonWebKey::KeyPair { secret, .. } = jwk else {
+                unreachable!("checked by `public_key_bytes()`");
+            };
+            let sk_bytes = secret.as_deref();
+            let sk_bytes = sk_bytes.ok_or_else(|| JwkError::NoField("d".into()))?;
+            JsonWebKey::ensure_len("d", sk_bytes, KEY_LEN)?;
+
+            let mut bytes = [0_u8; KEY_LEN];
+            bytes.copy_from_slice(sk_bytes);
+            let secret_key = Self::from(bytes);
+            if PublicKey::from(&secret_key).to_bytes() == public_key {
+                Ok(secret_key)
+            } else {
+                Err(JwkError::MismatchedKeys)
+            }
+        }
+    }
+
+    impl EcdhCurve for X25519 {
+        type PublicKey = PublicKey;
+        type SecretKey = StaticSecret;
+
+        fn ephemeral_agreement(
+            public_key: &PublicKey,
+        ) -> anyhow::Result<(JsonWebKey<'static>, SecretBytes<'static>)> {
+            let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+            let ephemeral_key = create_jwk(&PublicKey::from(&ephemeral_secret), None);
+            let shared_secret = ephemeral_secret.diffie_hellman(public_key);
+            anyhow::ensure!(shared_secret.was_contributory(), "public key has low order");
+            let shared_secret = shared_secret.as_bytes().to_vec();
+            Ok((ephemeral_key, SecretBytes::owned(shared_secret)))
+        }
+
+        fn agreement(
+            secret_key: &StaticSecret,
+            ephemeral_key: &JsonWebKey<'_>,
+        ) -> anyhow::Result<SecretBytes<'static>> {
+            let ephemeral_key =
+                PublicKey::try_from(ephemeral_key).map_err(|err| anyhow::anyhow!(err))?;
+            let shared_secret = secret_key.diffie_hellman(&ephemeral_key);
+            anyhow::ensure!(
+                shared_secret.was_contributory(),
+                "ephemeral public key has low order"
+            );
+            let shared_secret = shared_secret.as_bytes().to_vec();
+            Ok(SecretBytes::owned(shared_secret))
+        }
+    }
+}
+
+#[cfg(feature = "x25519-dalek")]
+pub use self::x25519_impl::X25519;
+
+#[cfg(all(test, feature = "p256"))]
+mod tests {
+    use base64ct::{Base64UrlUnpadded, Encoding};
+
+    use super::*;
+
+    #[test]
+    fn key_derivation_rfc7518_vector() {
+        // Test vector from RFC 7518, Appendix C.
+        let ephemeral_key = serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "gI0GAILBdu7T53akrFmMyGcsF3n5dO7MmwNBHKW5SV0",
+            "y": "SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFpps",
+        });
+        let recipient_key = serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ",
+            "y": "e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck",
+            "d": "VEmDZpDXXK8p8N0Cndsxs924q6nS1RXFASRl6BfUqdw",
+        });
+        let recipient_key: JsonWebKey<'_> = serde_json::from_value(recipient_key).unwrap();
+        let recipient_key = p256::SecretKey::try_from(&recipient_key).unwrap();
+
+        let header = serde_json::json!({ "epk": ephemeral_key, "apu": "QWxpY2U", "apv": "Qm9i" });
+        let header: KeyManagementHeader = serde_json::from_value(header).unwrap();
+        assert_eq!(
+            header.agreement_party_u_info.as_deref(),
+            Some(b"Alice".as_slice())
+        );
+
+        let content_key = EcdhEs::<P256>::direct()
+            .decrypt_key(&recipient_key, &header, &[], "A128GCM", 16)
+            .unwrap();
+        assert_eq!(
+            Base64UrlUnpadded::encode_string(&content_key),
+            "VqqN6vgjbSBcIijNcacQGg"
+        );
+
+        let header_json = serde_json::to_value(&header).unwrap();
+        assert_eq!(header_json["apv"], "Qm9i");
+        assert_eq!(header_json["epk"]["crv"], "P-256");
+    }
+}
@@ -0,0 +1,123 @@
+//! AES-GCM content encryption algorithms.
+
+use aes_gcm::{
+    aead::{
+        consts::{U12, U16},
+        AeadCore, AeadInPlace, KeyInit, KeySizeUser,
+    },
+    Aes128Gcm, Aes256Gcm, Nonce, Tag,
+};
+use rand_core::{OsRng, RngCore};
+
+use crate::{
+    alloc::{Cow, ToOwned, Vec},
+    jwe::{ContentEncryption, EncryptedContent},
+};
+
+/// Byte length of the initialization vector.
+const IV_LEN: usize = 12;
+/// Byte length of the authentication tag.
+const TAG_LEN: usize = 16;
+
+/// AES-GCM cipher with the standard initialization vector and tag lengths.
+trait GcmCipher: KeyInit + AeadInPlace + AeadCore<NonceSize = U12, TagSize = U16> {}
+
+impl<C> GcmCipher for C where C: KeyInit + AeadInPlace + AeadCore<NonceSize = U12, TagSize = U16> {}
+
+fn encrypt<C: GcmCipher>(
+    key: &[u8],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> anyhow::Result<EncryptedContent> {
+    let cipher = C::new_from_slice(key).map_err(|_| invalid_key_len::<C>(key))?;
+    let mut iv = [0_u8; IV_LEN];
+    OsRng.fill_bytes(&mut iv);
+    let mut ciphertext = plaintext.to_owned();
+    let tag = cipher
+        .encrypt_in_place_detached(&Nonce::from(iv), aad, &mut ciphertext)
+        .map_err(|_| anyhow::anyhow!("plaintext is too long"))?;
+    Ok(EncryptedContent {
+        iv: iv.to_vec(),
+        ciphertext,
+        tag: tag.to_vec(),
+    })
+}
+
+fn decrypt<C: GcmCipher>(
+    key: &[u8],
+    content: &EncryptedContent,
+    aad: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let cipher = C::new_from_slice(key).map_err(|_| invalid_key_len::<C>(key))?;
+    let iv: [u8; IV_LEN] = content
+        .iv
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("invalid initialization vector length"))?;
+    let tag: [u8; TAG_LEN] = content
+        .tag
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("invalid authentication tag length"))?;
+    let mut plaintext = content.ciphertext.clone();
+    cipher
+        .decrypt_in_place_detached(&Nonce::from(iv), aad, &mut plaintext, &Tag::from(tag))
+        .map_err(|_| anyhow::anyhow!("authentication tag mismatch"))?;
+    Ok(plaintext)
+}
+
+fn invalid_key_len<C: KeySizeUser>(key: &[u8]) -> anyhow::Error {
+    anyhow::anyhow!(
+        "invalid key length: expected {} bytes, got {} bytes",
+        C::key_size(),
+        key.len()
+    )
+}
+
+macro_rules! define_gcm_algorithm {
+    (
+        $(#[$($attr:meta)+])*
+        struct $name:ident<$cipher:ident>($alg_name:tt);
+    ) => {
+        $(#[$($attr)+])*
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $name;
+
+        impl ContentEncryption for $name {
+            fn name(&self) -> Cow<'static, str> {
+                Cow::Borrowed($alg_name)
+            }
+
+            fn key_len(&self) -> usize {
+                <$cipher as KeySizeUser>::key_size()
+            }
+
+            fn encrypt(
+                &self,
+                key: &[u8],
+                plaintext: &[u8],
+                aad: &[u8],
+            ) -> anyhow::Result<EncryptedContent> {
+                encrypt::<$cipher>(key, plaintext, aad)
+            }
+
+            fn decrypt(
+                &self,
+                key: &[u8],
+                content: &EncryptedContent,
+                aad: &[u8],
+            ) -> anyhow::Result<Vec<u8>> {
+                decrypt::<$cipher>(key, content, aad)
+            }
+        }
+    };
+}
+
+define_gcm_algorithm!(
+    /// `A128GCM` content encryption algorithm: AES-GCM with a 128-bit key.
+    struct A128Gcm<Aes128Gcm>("A128GCM");
+);
+define_gcm_algorithm!(
+    /// `A256GCM` content encryption algorithm: AES-GCM with a 256-bit key.
+    struct A256Gcm<Aes256Gcm>("A256GCM");
+);
@@ -0,0 +1,215 @@
+//! Symmetric keys and AES key wrap algorithms (`A128KW` and `A256KW`).
+
+use aes_kw::{KekAes128, KekAes256};
+use rand_core::{OsRng, RngCore};
+
+use core::fmt;
+
+use crate::{
+    alg::SecretBytes,
+    alloc::{Cow, ToOwned, Vec},
+    jwe::{ContentKey, KeyManagement, KeyManagementHeader},
+    jwk::{JsonWebKey, JwkError, KeyType},
+};
+
+/// Symmetric key used with the [`Direct`](crate::jwe::Direct) key management algorithm
+/// or AES key wrap algorithms ([`A128Kw`], [`A256Kw`]). Zeroed on drop.
+///
+/// The key can be converted from / to a [`JsonWebKey::Symmetric`].
+#[derive(Clone)]
+pub struct SymmetricKey(pub(super) SecretBytes<'static>);
+
+impl fmt::Debug for SymmetricKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_tuple("SymmetricKey").field(&"_").finish()
+    }
+}
+
+impl SymmetricKey {
+    /// Creates a key from the specified `bytes`.
+    pub fn new(bytes: impl AsRef<[u8]>) -> Self {
+        Self(SecretBytes::owned(bytes.as_ref().to_owned()))
+    }
+
+    pub(super) fn ensure_len(&self, expected_len: usize) -> anyhow::Result<()> {
+        let key_len = self.0.len();
+        if key_len == expected_len {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "invalid key length: expected {expected_len} bytes, got {key_len} bytes"
+            ))
+        }
+    }
+}
+
+impl AsRef<[u8]> for SymmetricKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a SymmetricKey> for JsonWebKey<'a> {
+    fn from(key: &'a SymmetricKey) -> JsonWebKey<'a> {
+        JsonWebKey::Symmetric {
+            secret: SecretBytes::borrowed(key.as_ref()),
+        }
+    }
+}
+
+impl TryFrom<&JsonWebKey<'_>> for SymmetricKey {
+    type Error = JwkError;
+
+    fn try_from(jwk: &JsonWebKey<'_>) -> Result<Self, Self::Error> {
+        match jwk {
+            JsonWebKey::Symmetric { secret } => Ok(Self::new(secret)),
+            _ => Err(JwkError::key_type(jwk, KeyType::Symmetric)),
+        }
+    }
+}
+
+/// Generates a random content encryption key with the specified byte length.
+pub(super) fn generate_content_key(len: usize) -> SecretBytes<'static> {
+    let mut key: Vec<u8> = core::iter::repeat(0).take(len).collect();
+    OsRng.fill_bytes(&mut key);
+    SecretBytes::owned(key)
+}
+
+/// Variants of the AES key wrap algorithm (RFC 3394).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum KeyWrap {
+    A128,
+    A256,
+}
+
+impl KeyWrap {
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Self::A128 => "A128KW",
+            Self::A256 => "A256KW",
+        }
+    }
+
+    pub(super) fn key_len(self) -> usize {
+        match self {
+            Self::A128 => 16,
+            Self::A256 => 32,
+        }
+    }
+
+    pub(super) fn wrap(self, kek: &[u8], content_key: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::A128 => Self::kek::<KekAes128>(kek)?.wrap_vec(content_key),
+            Self::A256 => Self::kek::<KekAes256>(kek)?.wrap_vec(content_key),
+        }
+        .map_err(|err| anyhow::anyhow!(err))
+    }
+
+    pub(super) fn unwrap(
+        self,
+        kek: &[u8],
+        encrypted_key: &[u8],
+    ) -> anyhow::Result<SecretBytes<'static>> {
+        match self {
+            Self::A128 => Self::kek::<KekAes128>(kek)?.unwrap_vec(encrypted_key),
+            Self::A256 => Self::kek::<KekAes256>(kek)?.unwrap_vec(encrypted_key),
+        }
+        .map(SecretBytes::owned)
+        .map_err(|err| anyhow::anyhow!(err))
+    }
+
+    fn kek<'a, K: TryFrom<&'a [u8]>>(kek: &'a [u8]) -> anyhow::Result<K> {
+        K::try_from(kek).map_err(|_| anyhow::anyhow!("invalid key encryption key length"))
+    }
+}
+
+macro_rules! define_key_wrap {
+    (
+        $(#[$($attr:meta)+])*
+        struct $name:ident($variant:ident);
+    ) => {
+        $(#[$($attr)+])*
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $name;
+
+        impl KeyManagement for $name {
+            type EncryptionKey = SymmetricKey;
+            type DecryptionKey = SymmetricKey;
+
+            fn name(&self) -> Cow<'static, str> {
+                Cow::Borrowed(KeyWrap::$variant.name())
+            }
+
+            fn encrypt_key(
+                &self,
+                key: &SymmetricKey,
+                _content_encryption: &str,
+                content_key_len: usize,
+            ) -> anyhow::Result<ContentKey> {
+                key.ensure_len(KeyWrap::$variant.key_len())?;
+                let content_key = generate_content_key(content_key_len);
+                let encrypted_key = KeyWrap::$variant.wrap(key.as_ref(), &content_key)?;
+                Ok(ContentKey {
+                    key: content_key,
+                    encrypted_key,
+                    header: KeyManagementHeader::default(),
+                })
+            }
+
+            fn decrypt_key(
+                &self,
+                key: &SymmetricKey,
+                _header: &KeyManagementHeader,
+                encrypted_key: &[u8],
+                _content_encryption: &str,
+                content_key_len: usize,
+            ) -> anyhow::Result<SecretBytes<'static>> {
+                key.ensure_len(KeyWrap::$variant.key_len())?;
+                let content_key = KeyWrap::$variant.unwrap(key.as_ref(), encrypted_key)?;
+                anyhow::ensure!(
+                    content_key.len() == content_key_len,
+                    "invalid content encryption key length"
+                );
+                Ok(content_key)
+            }
+        }
+    };
+}
+
+define_key_wrap!(
+    /// `A128KW` key management algorithm: the content encryption key is generated randomly
+    /// and wrapped with a 128-bit [`SymmetricKey`] using AES key wrap.
+    struct A128Kw(A128);
+);
+define_key_wrap!(
+    /// `A256KW` key management algorithm: the content encryption key is generated randomly
+    /// and wrapped with a 256-bit [`SymmetricKey`] using AES key wrap.
+    struct A256Kw(A256);
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc::ToString;
+
+    use const_decoder::Decoder::Hex;
+
+    #[test]
+    fn key_wrap_rfc3394_vector() {
+        // Test vector from RFC 3394, section 4.1.
+        const KEK: [u8; 16] = Hex.decode(b"000102030405060708090a0b0c0d0e0f");
+        const CONTENT_KEY: [u8; 16] = Hex.decode(b"00112233445566778899aabbccddeeff");
+        const WRAPPED: [u8; 24] = Hex.decode(b"1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5");
+
+        let wrapped = KeyWrap::A128.wrap(&KEK, &CONTENT_KEY).unwrap();
+        assert_eq!(wrapped, WRAPPED);
+        let unwrapped = KeyWrap::A128.unwrap(&KEK, &wrapped).unwrap();
+        assert_eq!(unwrapped.as_ref(), CONTENT_KEY);
+
+        let err = KeyWrap::A256.wrap(&KEK, &CONTENT_KEY).unwrap_err();
+        assert!(
+            err.to_string().contains("key encryption key length"),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! `RSA-OAEP-256` key management algorithm.
+
+use rand_core::OsRng;
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+use sha2::Sha256;
+
+use crate::{
+    alg::SecretBytes,
+    alloc::Cow,
+    jwe::{kw::generate_content_key, ContentKey, KeyManagement, KeyManagementHeader},
+};
+
+/// `RSA-OAEP-256` key management algorithm: the content encryption key is generated randomly
+/// and encrypted with the recipient's RSA public key using RSAES-OAEP with SHA-256.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "rsa")))]
+pub struct RsaOaep256;
+
+impl KeyManagement for RsaOaep256 {
+    type EncryptionKey = RsaPublicKey;
+    type DecryptionKey = RsaPrivateKey;
+
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed("RSA-OAEP-256")
+    }
+
+    fn encrypt_key(
+        &self,
+        key: &RsaPublicKey,
+        _content_encryption: &str,
+        content_key_len: usize,
+    ) -> anyhow::Result<ContentKey> {
+        let content_key = generate_content_key(content_key_len);
+        let encrypted_key = key
+            .encrypt(&mut OsRng, Oaep::new::<Sha256>(), &content_key)
+            .map_err(|err| anyhow::anyhow!(err))?;
+        Ok(ContentKey {
+            key: content_key,
+            encrypted_key,
+            header: KeyManagementHeader::default(),
+        })
+    }
+
+    fn decrypt_key(
+        &self,
+        key: &RsaPrivateKey,
+        _header: &KeyManagementHeader,
+        encrypted_key: &[u8],
+        _content_encryption: &str,
+        content_key_len: usize,
+    ) -> anyhow::Result<SecretBytes<'static>> {
+        let content_key = key.decrypt(Oaep::new::<Sha256>(), encrypted_key);
+        Ok(match content_key {
+            Ok(content_key) if content_key.len() == content_key_len => {
+                SecretBytes::owned(content_key)
+            }
+            // To mitigate timing attacks, a random key is substituted if the decryption fails,
+            // so that the failure is only detected during content decryption
+            // (see RFC 7516, section 11.5).
+            _ => generate_content_key(content_key_len),
+        })
+    }
+}
@@ -5,6 +5,13 @@
 //! [`JsonWebKey`]s can be (de)serialized using [`serde`] infrastructure, and can be used
 //! to compute key thumbprint as per [RFC 7638].
 //!
+//! Common key parameters, such as the key ID, are supported via [`ExtendedJsonWebKey`],
+//! and collections of keys published e.g. by identity providers via [`JsonWebKeySet`].
+//! `ExtendedJsonWebKey` checks that its parameters (such as the intended key use)
+//! are consistent with the requested operation when converting it to a signing or verifying key.
+//! [`KeyRotation`] manages a set of keys rotated over time, providing the signing key
+//! for new tokens, verifying keys for incoming tokens, and the public key set to publish.
+//!
 //! [`serde`]: https://crates.io/crates/serde
 //! [RFC 7638]: https://tools.ietf.org/html/rfc7638
 //!
@@ -43,6 +50,17 @@ use crate::{
     alloc::{Cow, String, ToString, Vec},
 };
 
+mod params;
+mod rotation;
+mod set;
+
+pub(crate) use self::params::certificate_chain;
+pub use self::{
+    params::{ExtendedJsonWebKey, KeyOperation, KeyUse},
+    rotation::{KeyRotation, RotatingKey},
+    set::{JsonWebKeySet, KeyQuery},
+};
+
 /// Type of a [`JsonWebKey`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
@@ -103,6 +121,8 @@ pub enum JwkError {
     },
     /// Signing and verifying keys do not match.
     MismatchedKeys,
+    /// Key operation is not permitted by the `key_ops` parameter of the key.
+    ForbiddenOperation(KeyOperation),
     /// Custom error specific to a crypto backend.
     Custom(anyhow::Error),
 }
@@ -140,6 +160,12 @@ impl fmt::Display for JwkError {
             Self::MismatchedKeys => {
                 formatter.write_str("private and public keys encoded in JWK do not match")
             }
+            Self::ForbiddenOperation(operation) => {
+                write!(
+                    formatter,
+                    "operation `{operation}` is not permitted by the `key_ops` JWK parameter"
+                )
+            }
             Self::Custom(err) => fmt::Display::fmt(err, formatter),
         }
     }
@@ -350,7 +376,7 @@ impl fmt::Display for JsonWebKey<'_> {
         // ^ unwrap() is safe: `JsonWebKey` serialization is always an object.
 
         let mut json_entries: Vec<_> = json_value.iter().collect();
-        json_entries.sort_unstable_by(|(x, _), (y, _)| x.cmp(y));
+        json_entries.sort_unstable_by_key(|(name, _)| *name);
 
         formatter.write_str("{")?;
         let field_count = json_entries.len();
@@ -419,13 +445,27 @@ pub struct RsaPrimeFactor<'a> {
 #[cfg(any(
     feature = "es256k",
     feature = "k256",
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
     feature = "exonum-crypto",
     feature = "ed25519-dalek",
-    feature = "ed25519-compact"
+    feature = "ed25519-compact",
+    all(feature = "jwe", feature = "x25519-dalek")
 ))]
 mod helpers {
     use super::{JsonWebKey, JwkError};
-    use crate::{alg::SigningKey, alloc::ToOwned, Algorithm};
+    use crate::alloc::ToOwned;
+    #[cfg(any(
+        feature = "es256k",
+        feature = "k256",
+        feature = "p256",
+        feature = "p384",
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact"
+    ))]
+    use crate::{alg::SigningKey, Algorithm};
 
     impl JsonWebKey<'_> {
         pub(crate) fn ensure_curve(curve: &str, expected: &str) -> Result<(), JwkError> {
@@ -458,6 +498,16 @@ mod helpers {
 
         /// Ensures that the provided signing key matches the verifying key restored from the same JWK.
         /// This is useful when implementing [`TryFrom`] conversion from `JsonWebKey` for private keys.
+        // Not used by P-521 keys since their verifying keys do not implement `PartialEq`.
+        #[cfg(any(
+            feature = "es256k",
+            feature = "k256",
+            feature = "p256",
+            feature = "p384",
+            feature = "exonum-crypto",
+            feature = "ed25519-dalek",
+            feature = "ed25519-compact"
+        ))]
         pub(crate) fn ensure_key_match<Alg, K>(&self, signing_key: K) -> Result<K, JwkError>
         where
             Alg: Algorithm<SigningKey = K>,
@@ -474,7 +524,7 @@ mod helpers {
     }
 }
 
-mod base64url {
+pub(crate) mod base64url {
     use base64ct::{Base64UrlUnpadded, Encoding};
     use serde::{
         de::{Error as DeError, Unexpected, Visitor},
@@ -525,7 +575,7 @@ mod base64url {
 
         struct BytesVisitor;
 
-        impl<'de> Visitor<'de> for BytesVisitor {
+        impl Visitor<'_> for BytesVisitor {
             type Value = Vec<u8>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -649,18 +699,18 @@ mod tests {
     }
 
     #[test]
-    #[cfg(feature = "serde_cbor")]
+    #[cfg(feature = "cbor")]
     fn jwk_with_cbor() {
         let key = JsonWebKey::KeyPair {
             curve: Cow::Borrowed("Ed25519"),
             x: Cow::Borrowed(b"public"),
             secret: Some(SecretBytes::borrowed(b"private")),
         };
-        let bytes = serde_cbor::to_vec(&key).unwrap();
+        let bytes = crate::cbor::to_vec(&key).unwrap();
         assert!(bytes.windows(6).any(|window| window == b"public"));
         assert!(bytes.windows(7).any(|window| window == b"private"));
 
-        let restored: JsonWebKey<'_> = serde_cbor::from_slice(&bytes).unwrap();
+        let restored: JsonWebKey<'_> = crate::cbor::from_slice(&bytes).unwrap();
         assert_eq!(restored, key);
     }
 }
@@ -0,0 +1,676 @@
+//! Common JWK parameters not related to the key material.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::digest::{Digest, Output};
+
+use core::fmt;
+
+use super::{JsonWebKey, JwkError};
+use crate::{
+    alloc::{Cow, String, ToOwned, Vec},
+    Algorithm, Empty, Thumbprint,
+};
+
+/// Intended use of a public key, as specified by the [`use`] JWK parameter.
+///
+/// [`use`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.2
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KeyUse {
+    /// Key is used for signatures (`sig`).
+    Signature,
+    /// Key is used for encryption (`enc`).
+    Encryption,
+    /// Other key use not defined by RFC 7517.
+    Other(String),
+}
+
+impl KeyUse {
+    /// Returns the string presentation of this key use, as used in JWKs.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Signature => "sig",
+            Self::Encryption => "enc",
+            Self::Other(value) => value,
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "sig" => Self::Signature,
+            "enc" => Self::Encryption,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for KeyUse {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for KeyUse {
+    fn from(value: &str) -> Self {
+        Self::parse(value).unwrap_or_else(|| Self::Other(value.to_owned()))
+    }
+}
+
+impl Serialize for KeyUse {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyUse {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(Self::parse(&value).unwrap_or(Self::Other(value)))
+    }
+}
+
+/// Operation for which a key is intended to be used, as specified by the [`key_ops`]
+/// JWK parameter.
+///
+/// [`key_ops`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.3
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KeyOperation {
+    /// Computing digital signatures or MACs (`sign`).
+    Sign,
+    /// Verifying digital signatures or MACs (`verify`).
+    Verify,
+    /// Encrypting content (`encrypt`).
+    Encrypt,
+    /// Decrypting content and validating decryption (`decrypt`).
+    Decrypt,
+    /// Encrypting a key (`wrapKey`).
+    WrapKey,
+    /// Decrypting a key and validating decryption (`unwrapKey`).
+    UnwrapKey,
+    /// Deriving a key (`deriveKey`).
+    DeriveKey,
+    /// Deriving bits not to be used as a key (`deriveBits`).
+    DeriveBits,
+    /// Other operation not defined by RFC 7517.
+    Other(String),
+}
+
+impl KeyOperation {
+    /// Returns the string presentation of this operation, as used in JWKs.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Sign => "sign",
+            Self::Verify => "verify",
+            Self::Encrypt => "encrypt",
+            Self::Decrypt => "decrypt",
+            Self::WrapKey => "wrapKey",
+            Self::UnwrapKey => "unwrapKey",
+            Self::DeriveKey => "deriveKey",
+            Self::DeriveBits => "deriveBits",
+            Self::Other(value) => value,
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "sign" => Self::Sign,
+            "verify" => Self::Verify,
+            "encrypt" => Self::Encrypt,
+            "decrypt" => Self::Decrypt,
+            "wrapKey" => Self::WrapKey,
+            "unwrapKey" => Self::UnwrapKey,
+            "deriveKey" => Self::DeriveKey,
+            "deriveBits" => Self::DeriveBits,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for KeyOperation {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for KeyOperation {
+    fn from(value: &str) -> Self {
+        Self::parse(value).unwrap_or_else(|| Self::Other(value.to_owned()))
+    }
+}
+
+impl Serialize for KeyOperation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyOperation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(Self::parse(&value).unwrap_or(Self::Other(value)))
+    }
+}
+
+/// [`JsonWebKey`] together with common parameters defined in [Section 4 of RFC 7517][params],
+/// such as the key ID (`kid`).
+///
+/// The key material is flattened into the same JSON object as the parameters, so that
+/// this type (de)serializes from / to a JWK as it is usually encountered in the wild.
+/// Parameters not covered by the fields of this type can be captured
+/// by the `other_fields` field.
+///
+/// Key parameters do not influence the [key thumbprint](Self::thumbprint()).
+/// [`Self::signing_key()`] and [`Self::verifying_key()`] check that the `alg`, `use`
+/// and `key_ops` parameters (if present) are consistent with the requested key usage.
+///
+/// # Examples
+///
+/// ```
+/// # use jwt_compact::{alg::Hs256, jwk::{ExtendedJsonWebKey, KeyUse}};
+/// # fn main() -> anyhow::Result<()> {
+/// let json = r#"{
+///     "kty": "oct",
+///     "kid": "my-key",
+///     "use": "sig",
+///     "alg": "HS256",
+///     "k": "t-bdv41MJXExXnpquHBuDn7n1YGyX7gLQchVHAoNu50"
+/// }"#;
+/// let jwk: ExtendedJsonWebKey<'_> = serde_json::from_str(json)?;
+/// assert_eq!(jwk.key_id.as_deref(), Some("my-key"));
+/// assert_eq!(jwk.key_use, Some(KeyUse::Signature));
+/// assert_eq!(jwk.algorithm.as_deref(), Some("HS256"));
+///
+/// // Key parameters are checked when converting the key.
+/// let key = jwk.verifying_key(&Hs256)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [params]: https://www.rfc-editor.org/rfc/rfc7517#section-4
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ExtendedJsonWebKey<'a, T = Empty> {
+    /// Key material.
+    #[serde(flatten)]
+    pub base: JsonWebKey<'a>,
+
+    /// Key identifier. This field is renamed to [`kid`] for serialization.
+    ///
+    /// [`kid`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.5
+    #[serde(rename = "kid", default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+
+    /// Intended use of the key. This field is renamed to [`use`] for serialization.
+    ///
+    /// [`use`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.2
+    #[serde(rename = "use", default, skip_serializing_if = "Option::is_none")]
+    pub key_use: Option<KeyUse>,
+
+    /// Algorithm intended to be used with the key. This field is renamed to [`alg`]
+    /// for serialization.
+    ///
+    /// [`alg`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.4
+    #[serde(rename = "alg", default, skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<String>,
+
+    /// Operations for which the key is intended to be used. This field is renamed
+    /// to [`key_ops`] for serialization.
+    ///
+    /// [`key_ops`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.3
+    #[serde(rename = "key_ops", default, skip_serializing_if = "Option::is_none")]
+    pub key_operations: Option<Vec<KeyOperation>>,
+
+    /// URL of the X.509 certificate (chain) for the key. This field is renamed to [`x5u`]
+    /// for serialization.
+    ///
+    /// [`x5u`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.6
+    #[serde(rename = "x5u", default, skip_serializing_if = "Option::is_none")]
+    pub certificate_url: Option<String>,
+
+    /// X.509 certificate chain for the key, with each certificate in the DER format.
+    /// The certificate containing the key must be the first one. This field is renamed
+    /// to [`x5c`] for serialization; certificates are serialized using the standard
+    /// (not URL-safe) base64 encoding with padding, as per the spec.
+    ///
+    /// [`x5c`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.7
+    #[serde(
+        rename = "x5c",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "certificate_chain"
+    )]
+    pub certificate_chain: Vec<Cow<'a, [u8]>>,
+
+    /// SHA-1 thumbprint of the X.509 certificate for the key. This field is renamed
+    /// to [`x5t`] for serialization.
+    ///
+    /// [`x5t`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.8
+    #[serde(rename = "x5t", default, skip_serializing_if = "Option::is_none")]
+    pub certificate_sha1_thumbprint: Option<Thumbprint<20>>,
+
+    /// SHA-256 thumbprint of the X.509 certificate for the key. This field is renamed
+    /// to [`x5t#S256`] for serialization.
+    ///
+    /// [`x5t#S256`]: https://www.rfc-editor.org/rfc/rfc7517#section-4.9
+    #[serde(rename = "x5t#S256", default, skip_serializing_if = "Option::is_none")]
+    pub certificate_thumbprint: Option<Thumbprint<32>>,
+
+    /// Other fields encoded in the key.
+    ///
+    /// For (de)serialization to work properly, the fields type must [`Serialize`]
+    /// to a JSON object.
+    #[serde(flatten)]
+    pub other_fields: T,
+}
+
+impl<'a> ExtendedJsonWebKey<'a> {
+    /// Creates a key with no parameters set.
+    pub const fn new(base: JsonWebKey<'a>) -> Self {
+        Self::with_fields(base, Empty {})
+    }
+}
+
+impl<'a> From<JsonWebKey<'a>> for ExtendedJsonWebKey<'a> {
+    fn from(base: JsonWebKey<'a>) -> Self {
+        Self::new(base)
+    }
+}
+
+impl<'a, T> ExtendedJsonWebKey<'a, T> {
+    /// Creates a key with the specified custom fields.
+    pub const fn with_fields(base: JsonWebKey<'a>, fields: T) -> Self {
+        Self {
+            base,
+            key_id: None,
+            key_use: None,
+            algorithm: None,
+            key_operations: None,
+            certificate_url: None,
+            certificate_chain: Vec::new(),
+            certificate_sha1_thumbprint: None,
+            certificate_thumbprint: None,
+            other_fields: fields,
+        }
+    }
+
+    /// Sets the `key_id` field for this key.
+    #[must_use]
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Sets the `key_use` field for this key.
+    #[must_use]
+    pub fn with_key_use(mut self, key_use: KeyUse) -> Self {
+        self.key_use = Some(key_use);
+        self
+    }
+
+    /// Sets the `algorithm` field for this key.
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: impl Into<String>) -> Self {
+        self.algorithm = Some(algorithm.into());
+        self
+    }
+
+    /// Sets the `key_operations` field for this key.
+    #[must_use]
+    pub fn with_key_operations(
+        mut self,
+        operations: impl IntoIterator<Item = KeyOperation>,
+    ) -> Self {
+        self.key_operations = Some(operations.into_iter().collect());
+        self
+    }
+
+    /// Sets the `certificate_url` field for this key.
+    #[must_use]
+    pub fn with_certificate_url(mut self, certificate_url: impl Into<String>) -> Self {
+        self.certificate_url = Some(certificate_url.into());
+        self
+    }
+
+    /// Sets the `certificate_chain` field for this key.
+    #[must_use]
+    pub fn with_certificate_chain(
+        mut self,
+        certificates: impl IntoIterator<Item = Cow<'a, [u8]>>,
+    ) -> Self {
+        self.certificate_chain = certificates.into_iter().collect();
+        self
+    }
+
+    /// Sets the `certificate_sha1_thumbprint` field for this key.
+    #[must_use]
+    pub fn with_certificate_sha1_thumbprint(
+        mut self,
+        certificate_thumbprint: impl Into<Thumbprint<20>>,
+    ) -> Self {
+        self.certificate_sha1_thumbprint = Some(certificate_thumbprint.into());
+        self
+    }
+
+    /// Sets the `certificate_thumbprint` field for this key.
+    #[must_use]
+    pub fn with_certificate_thumbprint(
+        mut self,
+        certificate_thumbprint: impl Into<Thumbprint<32>>,
+    ) -> Self {
+        self.certificate_thumbprint = Some(certificate_thumbprint.into());
+        self
+    }
+
+    /// Computes a thumbprint of the key material. Key parameters do not influence
+    /// the thumbprint, as required by [RFC 7638].
+    ///
+    /// [RFC 7638]: https://tools.ietf.org/html/rfc7638
+    pub fn thumbprint<D: Digest>(&self) -> Output<D> {
+        self.base.thumbprint::<D>()
+    }
+
+    /// Checks that the key parameters allow using this key with the specified `algorithm`
+    /// for the specified `operation`.
+    ///
+    /// - If the `algorithm` field is set, it must match the algorithm name.
+    /// - If the `key_use` field is set, it must be [`KeyUse::Signature`].
+    /// - If the `key_operations` field is set, it must contain `operation`.
+    fn check_params<A: Algorithm + ?Sized>(
+        &self,
+        algorithm: &A,
+        operation: &KeyOperation,
+    ) -> Result<(), JwkError> {
+        if let Some(key_algorithm) = &self.algorithm {
+            let expected = algorithm.name();
+            if *key_algorithm != expected {
+                return Err(JwkError::UnexpectedValue {
+                    field: "alg".to_owned(),
+                    expected: expected.into_owned(),
+                    actual: key_algorithm.clone(),
+                });
+            }
+        }
+        if let Some(key_use) = &self.key_use {
+            if *key_use != KeyUse::Signature {
+                return Err(JwkError::UnexpectedValue {
+                    field: "use".to_owned(),
+                    expected: KeyUse::Signature.as_str().to_owned(),
+                    actual: key_use.as_str().to_owned(),
+                });
+            }
+        }
+        if let Some(operations) = &self.key_operations {
+            if !operations.contains(operation) {
+                return Err(JwkError::ForbiddenOperation(operation.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts this key to a signing key for the specified `algorithm`, checking that
+    /// the key parameters are consistent with such use.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `alg`, `use` or `key_ops` parameters are inconsistent with
+    /// signing using `algorithm`, or if the conversion of the key material fails.
+    pub fn signing_key<A>(&self, algorithm: &A) -> Result<A::SigningKey, JwkError>
+    where
+        A: Algorithm + ?Sized,
+        A::SigningKey: for<'jwk> TryFrom<&'jwk JsonWebKey<'jwk>, Error = JwkError>,
+    {
+        self.check_params(algorithm, &KeyOperation::Sign)?;
+        A::SigningKey::try_from(&self.base)
+    }
+
+    /// Converts this key to a verifying key for the specified `algorithm`, checking that
+    /// the key parameters are consistent with such use.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `alg`, `use` or `key_ops` parameters are inconsistent with
+    /// signature verification using `algorithm`, or if the conversion of the key material fails.
+    pub fn verifying_key<A>(&self, algorithm: &A) -> Result<A::VerifyingKey, JwkError>
+    where
+        A: Algorithm + ?Sized,
+        A::VerifyingKey: for<'jwk> TryFrom<&'jwk JsonWebKey<'jwk>, Error = JwkError>,
+    {
+        self.check_params(algorithm, &KeyOperation::Verify)?;
+        A::VerifyingKey::try_from(&self.base)
+    }
+}
+
+/// (De)serialization of X.509 certificate chains (`x5c`), shared with [`Header`](crate::Header).
+pub(crate) mod certificate_chain {
+    use base64ct::{Base64, Encoding};
+    use serde::{
+        de::{Error as DeError, SeqAccess, Unexpected, Visitor},
+        ser::SerializeSeq,
+        Deserializer, Serializer,
+    };
+
+    use core::{fmt, marker::PhantomData};
+
+    use crate::alloc::{Cow, Vec};
+
+    pub fn serialize<S, T>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        let human_readable = serializer.is_human_readable();
+        let mut seq = serializer.serialize_seq(Some(value.len()))?;
+        for certificate in value {
+            if human_readable {
+                seq.serialize_element(&Base64::encode_string(certificate.as_ref()))?;
+            } else {
+                seq.serialize_element(&serde_bytes_compat::Bytes(certificate.as_ref()))?;
+            }
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<u8>>,
+    {
+        struct ChainVisitor<T> {
+            human_readable: bool,
+            _item: PhantomData<T>,
+        }
+
+        impl<'de, T: From<Vec<u8>>> Visitor<'de> for ChainVisitor<T> {
+            type Value = Vec<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("sequence of base64-encoded certificates")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut certificates = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                if self.human_readable {
+                    while let Some(encoded) = seq.next_element::<Cow<'de, str>>()? {
+                        let certificate = Base64::decode_vec(&encoded).map_err(|_| {
+                            A::Error::invalid_value(Unexpected::Str(&encoded), &"base64 data")
+                        })?;
+                        certificates.push(certificate.into());
+                    }
+                } else {
+                    while let Some(bytes) = seq.next_element::<serde_bytes_compat::ByteBuf>()? {
+                        certificates.push(bytes.0.into());
+                    }
+                }
+                Ok(certificates)
+            }
+        }
+
+        let human_readable = deserializer.is_human_readable();
+        deserializer.deserialize_seq(ChainVisitor {
+            human_readable,
+            _item: PhantomData,
+        })
+    }
+
+    /// Minimal replacement for the `serde_bytes` crate.
+    mod serde_bytes_compat {
+        use serde::{
+            de::{Error as DeError, Visitor},
+            Deserialize, Deserializer, Serialize, Serializer,
+        };
+
+        use core::fmt;
+
+        use crate::alloc::Vec;
+
+        pub struct Bytes<'a>(pub &'a [u8]);
+
+        impl Serialize for Bytes<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        pub struct ByteBuf(pub Vec<u8>);
+
+        impl<'de> Deserialize<'de> for ByteBuf {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct BytesVisitor;
+
+                impl Visitor<'_> for BytesVisitor {
+                    type Value = Vec<u8>;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        formatter.write_str("byte buffer")
+                    }
+
+                    fn visit_bytes<E: DeError>(self, value: &[u8]) -> Result<Self::Value, E> {
+                        Ok(value.to_vec())
+                    }
+
+                    fn visit_byte_buf<E: DeError>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+                        Ok(value)
+                    }
+                }
+
+                deserializer.deserialize_bytes(BytesVisitor).map(Self)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key, Hs384},
+        alloc::ToString,
+    };
+
+    use assert_matches::assert_matches;
+    use sha2::Sha256;
+
+    fn create_jwk() -> ExtendedJsonWebKey<'static> {
+        let base = JsonWebKey::Symmetric {
+            secret: crate::alg::SecretBytes::borrowed(b"super_secret_key_donut_steel"),
+        };
+        ExtendedJsonWebKey::new(base)
+    }
+
+    #[test]
+    fn serializing_key_params() {
+        let jwk = create_jwk()
+            .with_key_id("key")
+            .with_key_use(KeyUse::Signature)
+            .with_algorithm("HS256")
+            .with_key_operations([KeyOperation::Sign, KeyOperation::from("custom")])
+            .with_certificate_chain([Cow::Borrowed(&b"\xff\xfe"[..])])
+            .with_certificate_thumbprint([1; 32]);
+
+        let json = serde_json::to_value(&jwk).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "kty": "oct",
+                "k": "c3VwZXJfc2VjcmV0X2tleV9kb251dF9zdGVlbA",
+                "kid": "key",
+                "use": "sig",
+                "alg": "HS256",
+                "key_ops": ["sign", "custom"],
+                "x5c": ["//4="],
+                "x5t#S256": "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE",
+            })
+        );
+        let restored: ExtendedJsonWebKey<'_> = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, jwk);
+        assert_matches!(
+            restored.key_operations.as_deref(),
+            Some([KeyOperation::Sign, KeyOperation::Other(op)]) if op == "custom"
+        );
+
+        #[cfg(feature = "cbor")]
+        {
+            let bytes = crate::cbor::to_vec(&jwk).unwrap();
+            assert!(bytes.windows(2).any(|window| window == b"\xff\xfe"));
+            let restored: ExtendedJsonWebKey<'_> = crate::cbor::from_slice(&bytes).unwrap();
+            assert_eq!(restored, jwk);
+        }
+    }
+
+    #[test]
+    fn invalid_certificate_chain() {
+        let json = serde_json::json!({ "kty": "oct", "k": "dGVzdA", "x5c": ["??"] });
+        let err = serde_json::from_value::<ExtendedJsonWebKey<'_>>(json).unwrap_err();
+        assert!(err.to_string().contains("base64 data"), "{err}");
+    }
+
+    #[test]
+    fn params_do_not_influence_thumbprint() {
+        let jwk = create_jwk();
+        let extended_jwk = jwk
+            .clone()
+            .with_key_id("key")
+            .with_key_operations([KeyOperation::Sign, KeyOperation::Verify]);
+        assert_eq!(
+            extended_jwk.thumbprint::<Sha256>(),
+            jwk.base.thumbprint::<Sha256>()
+        );
+    }
+
+    #[test]
+    fn checking_params_on_conversion() {
+        let jwk = create_jwk();
+        jwk.signing_key(&Hs256).unwrap();
+        jwk.verifying_key(&Hs256).unwrap();
+
+        let jwk = jwk.with_algorithm("HS256");
+        let key: Hs256Key = jwk.signing_key(&Hs256).unwrap();
+        assert_eq!(JsonWebKey::from(&key), jwk.base);
+        let err = jwk.verifying_key(&Hs384).map(drop).unwrap_err();
+        assert_matches!(
+            err,
+            JwkError::UnexpectedValue { field, expected, actual }
+                if field == "alg" && expected == "HS384" && actual == "HS256"
+        );
+
+        let jwk = jwk.with_key_use(KeyUse::Encryption);
+        let err = jwk.signing_key(&Hs256).map(drop).unwrap_err();
+        assert_matches!(
+            err,
+            JwkError::UnexpectedValue { field, expected, actual }
+                if field == "use" && expected == "sig" && actual == "enc"
+        );
+
+        let jwk = jwk
+            .with_key_use(KeyUse::Signature)
+            .with_key_operations([KeyOperation::Verify]);
+        jwk.verifying_key(&Hs256).unwrap();
+        let err = jwk.signing_key(&Hs256).map(drop).unwrap_err();
+        assert_matches!(err, JwkError::ForbiddenOperation(KeyOperation::Sign));
+        assert_eq!(
+            err.to_string(),
+            "operation `sign` is not permitted by the `key_ops` JWK parameter"
+        );
+    }
+}
@@ -0,0 +1,557 @@
+//! Rotation-aware key storage.
+
+use chrono::{DateTime, Duration, Utc};
+
+use core::fmt;
+
+use super::{ExtendedJsonWebKey, JsonWebKey, JsonWebKeySet, JwkError, KeyType, KeyUse};
+use crate::{
+    alg::{KeyedAlg, KeyedSigningKey, SigningKey},
+    alloc::{String, ToOwned, Vec},
+    Algorithm, Header, TimeOptions,
+};
+
+/// Key managed by a [`KeyRotation`], together with its lifecycle timestamps.
+///
+/// A key is used to sign new tokens starting from its activation timestamp until it is retired.
+/// A key is retired either explicitly (see [`Self::with_retirement()`]), or implicitly,
+/// once a newer key in the same [`KeyRotation`] becomes active.
+#[derive(Debug, Clone)]
+pub struct RotatingKey<A: Algorithm> {
+    signing_key: KeyedSigningKey<A::SigningKey>,
+    verifying_key: A::VerifyingKey,
+    activated_at: DateTime<Utc>,
+    retired_at: Option<DateTime<Utc>>,
+}
+
+impl<A: Algorithm> RotatingKey<A> {
+    /// Creates a key with the specified ID that becomes active at `activated_at`.
+    pub fn new(
+        key_id: impl Into<String>,
+        signing_key: A::SigningKey,
+        activated_at: DateTime<Utc>,
+    ) -> Self
+    where
+        A::SigningKey: SigningKey<A>,
+    {
+        Self {
+            verifying_key: signing_key.to_verifying_key(),
+            signing_key: KeyedSigningKey::new(signing_key, key_id),
+            activated_at,
+            retired_at: None,
+        }
+    }
+
+    /// Creates a key from a private JWK. The key ID is taken from the `kid` parameter
+    /// of the JWK, which is thus required.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JWK has no key ID, if its parameters are inconsistent with
+    /// signing using `algorithm`, or if the conversion of the key material fails.
+    pub fn from_jwk<T>(
+        algorithm: &A,
+        jwk: &ExtendedJsonWebKey<'_, T>,
+        activated_at: DateTime<Utc>,
+    ) -> Result<Self, JwkError>
+    where
+        A::SigningKey: SigningKey<A> + for<'jwk> TryFrom<&'jwk JsonWebKey<'jwk>, Error = JwkError>,
+    {
+        let key_id = jwk
+            .key_id
+            .as_deref()
+            .ok_or_else(|| JwkError::NoField("kid".to_owned()))?;
+        let signing_key = jwk.signing_key(algorithm)?;
+        Ok(Self::new(key_id, signing_key, activated_at))
+    }
+
+    /// Sets the timestamp after which the key is no longer used to sign new tokens.
+    #[must_use]
+    pub fn with_retirement(mut self, retired_at: DateTime<Utc>) -> Self {
+        self.retired_at = Some(retired_at);
+        self
+    }
+
+    /// Returns the key ID.
+    pub fn key_id(&self) -> &str {
+        self.signing_key.key_id()
+    }
+
+    /// Returns the signing key paired with the key ID.
+    pub fn signing_key(&self) -> &KeyedSigningKey<A::SigningKey> {
+        &self.signing_key
+    }
+
+    /// Returns the verifying key.
+    pub fn verifying_key(&self) -> &A::VerifyingKey {
+        &self.verifying_key
+    }
+
+    /// Returns the timestamp since which the key is used to sign new tokens.
+    pub fn activated_at(&self) -> DateTime<Utc> {
+        self.activated_at
+    }
+
+    /// Returns the explicitly set retirement timestamp, if any.
+    pub fn retired_at(&self) -> Option<DateTime<Utc>> {
+        self.retired_at
+    }
+
+    fn is_active_at(&self, timestamp: DateTime<Utc>) -> bool {
+        self.activated_at <= timestamp && self.retired_at.map_or(true, |end| timestamp < end)
+    }
+}
+
+/// Set of keys for a single algorithm rotated over time.
+///
+/// The set provides the signing key for new tokens and the verifying keys for incoming tokens,
+/// depending on the current timestamp obtained from [`TimeOptions`]:
+///
+/// - New tokens are signed with the most recently activated key that is not retired.
+///   Since the key is provided as a [`KeyedSigningKey`], the key ID is automatically
+///   recorded in the `kid` field of the token header if the token is created
+///   using [`Self::signing_algorithm()`].
+/// - Tokens are verified with the key identified by the `kid` header field. A key is accepted
+///   since its activation and for the verification period after its retirement,
+///   so that tokens signed with a retired key can be verified until they expire.
+///   Hence, the verification period should be not less than the maximum token lifetime.
+///   Time leeway from [`TimeOptions`] is applied to both ends of the interval.
+///
+/// Keys that are not yet active are still published in the
+/// [public key set](Self::public_key_set()), so that verifiers can obtain them in advance.
+///
+/// # Examples
+///
+/// ```
+/// # use chrono::{Duration, TimeZone, Utc};
+/// # use jwt_compact::{prelude::*, alg::{Hs256, Hs256Key}, jwk::{KeyRotation, RotatingKey}, Empty};
+/// # fn main() -> anyhow::Result<()> {
+/// let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+/// let rotation = KeyRotation::new(Hs256, Duration::days(1))
+///     .with_key(RotatingKey::new("key-1", Hs256Key::new(b"first_secret"), start))
+///     .with_key(RotatingKey::new(
+///         "key-2",
+///         Hs256Key::new(b"second_secret"),
+///         start + Duration::weeks(1),
+///     ));
+///
+/// let now = start + Duration::days(3);
+/// let time_options = TimeOptions::new(Duration::minutes(1), move || now);
+/// let signing_key = rotation.signing_key(&time_options).expect("no active key");
+/// assert_eq!(signing_key.key_id(), "key-1");
+/// let token = rotation
+///     .signing_algorithm()
+///     .token(&Header::empty(), &Claims::empty(), signing_key)?;
+///
+/// // Verify the token after the rotation.
+/// let now = start + Duration::weeks(1) + Duration::hours(1);
+/// let time_options = TimeOptions::new(Duration::minutes(1), move || now);
+/// assert_eq!(rotation.signing_key(&time_options).unwrap().key_id(), "key-2");
+/// let token = UntrustedToken::new(&token)?;
+/// let verifying_key = rotation
+///     .verifying_key(token.header(), &time_options)
+///     .expect("no matching key");
+/// rotation.algorithm().validator::<Empty>(verifying_key).validate(&token)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct KeyRotation<A: Algorithm> {
+    algorithm: KeyedAlg<A>,
+    verification_period: Duration,
+    keys: Vec<RotatingKey<A>>,
+}
+
+impl<A> fmt::Debug for KeyRotation<A>
+where
+    A: Algorithm + fmt::Debug,
+    RotatingKey<A>: fmt::Debug,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("KeyRotation")
+            .field("algorithm", &self.algorithm.0)
+            .field("verification_period", &self.verification_period)
+            .field("keys", &self.keys)
+            .finish()
+    }
+}
+
+impl<A> Clone for KeyRotation<A>
+where
+    A: Algorithm + Clone,
+    RotatingKey<A>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            algorithm: self.algorithm.clone(),
+            verification_period: self.verification_period,
+            keys: self.keys.clone(),
+        }
+    }
+}
+
+impl<A: Algorithm> KeyRotation<A> {
+    /// Creates an empty set of keys for the specified `algorithm`. Keys are accepted
+    /// for verification during `verification_period` after their retirement.
+    pub fn new(algorithm: A, verification_period: Duration) -> Self {
+        Self {
+            algorithm: KeyedAlg(algorithm),
+            verification_period,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Returns the algorithm used by keys in this set.
+    pub fn algorithm(&self) -> &A {
+        &self.algorithm.0
+    }
+
+    /// Returns the algorithm wrapper that should be used to sign new tokens with the key
+    /// returned by [`Self::signing_key()`].
+    pub fn signing_algorithm(&self) -> &KeyedAlg<A> {
+        &self.algorithm
+    }
+
+    /// Returns all keys in this set, including expired ones.
+    pub fn keys(&self) -> &[RotatingKey<A>] {
+        &self.keys
+    }
+
+    /// Adds a key to this set.
+    pub fn push(&mut self, key: RotatingKey<A>) {
+        self.keys.push(key);
+    }
+
+    /// Adds a key to this set.
+    #[must_use]
+    pub fn with_key(mut self, key: RotatingKey<A>) -> Self {
+        self.push(key);
+        self
+    }
+
+    /// Returns the key to sign new tokens with, or `None` if there are no active keys.
+    pub fn signing_key<F>(
+        &self,
+        time_options: &TimeOptions<F>,
+    ) -> Option<&KeyedSigningKey<A::SigningKey>>
+    where
+        F: Fn() -> DateTime<Utc>,
+    {
+        let now = (time_options.clock_fn)();
+        self.keys
+            .iter()
+            .filter(|key| key.is_active_at(now))
+            .max_by_key(|key| key.activated_at)
+            .map(RotatingKey::signing_key)
+    }
+
+    /// Returns the timestamp after which `key` is no longer used for signing, taking into
+    /// account both explicit retirement and activation of newer keys.
+    fn signing_end(&self, key: &RotatingKey<A>) -> Option<DateTime<Utc>> {
+        let superseded_at = self
+            .keys
+            .iter()
+            .map(|other| other.activated_at)
+            .filter(|&activated_at| activated_at > key.activated_at)
+            .min();
+        match (key.retired_at, superseded_at) {
+            (Some(retired_at), Some(superseded_at)) => Some(retired_at.min(superseded_at)),
+            (retired_at, superseded_at) => retired_at.or(superseded_at),
+        }
+    }
+
+    fn accepts_at(&self, key: &RotatingKey<A>, timestamp: DateTime<Utc>, leeway: Duration) -> bool {
+        // On underflow, the lower bound is effectively unbounded; on overflow,
+        // the upper bound is. Hence, both cases are treated as accepting the `timestamp`.
+        let is_activated = key
+            .activated_at
+            .checked_sub_signed(leeway)
+            .map_or(true, |activated_at| timestamp >= activated_at);
+        if !is_activated {
+            return false;
+        }
+        self.signing_end(key).map_or(true, |end| {
+            self.verification_period
+                .checked_add(&leeway)
+                .and_then(|period| end.checked_add_signed(period))
+                .map_or(true, |verified_until| timestamp < verified_until)
+        })
+    }
+
+    /// Iterates over keys that can be used to verify tokens at the current timestamp.
+    pub fn verifying_keys<'s, F>(
+        &'s self,
+        time_options: &TimeOptions<F>,
+    ) -> impl Iterator<Item = &'s RotatingKey<A>> + 's
+    where
+        F: Fn() -> DateTime<Utc>,
+    {
+        let now = (time_options.clock_fn)();
+        let leeway = time_options.leeway;
+        self.keys
+            .iter()
+            .filter(move |key| self.accepts_at(key, now, leeway))
+    }
+
+    /// Returns the verifying key for a token with the specified `header`. The key
+    /// is looked up by the `kid` header field; if the field is absent, or there is no
+    /// key with this ID that can be used at the current timestamp, returns `None`.
+    pub fn verifying_key<H, F>(
+        &self,
+        header: &Header<H>,
+        time_options: &TimeOptions<F>,
+    ) -> Option<&A::VerifyingKey>
+    where
+        F: Fn() -> DateTime<Utc>,
+    {
+        let key_id = header.key_id.as_deref()?;
+        self.verifying_keys(time_options)
+            .find(|key| key.key_id() == key_id)
+            .map(RotatingKey::verifying_key)
+    }
+
+    /// Exports public keys as a JWK set. The set contains keys that can be used for verification
+    /// at the current timestamp, and keys that are not yet active. Each key has the `kid`,
+    /// `alg` and `use` parameters set.
+    ///
+    /// Symmetric keys (e.g., ones for `HS*` algorithms) are never included into the set
+    /// since they are secret.
+    pub fn public_key_set<F>(&self, time_options: &TimeOptions<F>) -> JsonWebKeySet<'_>
+    where
+        F: Fn() -> DateTime<Utc>,
+        for<'a> &'a A::VerifyingKey: Into<JsonWebKey<'a>>,
+    {
+        let now = (time_options.clock_fn)();
+        let leeway = time_options.leeway;
+        self.keys
+            .iter()
+            .filter(|key| key.activated_at > now || self.accepts_at(key, now, leeway))
+            .filter_map(|key| {
+                let jwk: JsonWebKey<'_> = key.verifying_key().into();
+                if jwk.key_type() == KeyType::Symmetric {
+                    return None;
+                }
+                let jwk = ExtendedJsonWebKey::new(jwk)
+                    .with_key_id(key.key_id())
+                    .with_algorithm(self.algorithm.name())
+                    .with_key_use(KeyUse::Signature);
+                Some(jwk)
+            })
+            .collect()
+    }
+
+    /// Removes keys that can no longer be used for verification at the current timestamp.
+    /// Keys that are not yet active are retained.
+    pub fn remove_expired<F>(&mut self, time_options: &TimeOptions<F>)
+    where
+        F: Fn() -> DateTime<Utc>,
+    {
+        let now = (time_options.clock_fn)();
+        let leeway = time_options.leeway;
+        let retained: Vec<bool> = self
+            .keys
+            .iter()
+            .map(|key| key.activated_at > now || self.accepts_at(key, now, leeway))
+            .collect();
+        let mut retained = retained.into_iter();
+        self.keys.retain(|_| retained.next().unwrap_or(true));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key},
+        AlgorithmExt, Claims, Empty, UntrustedToken,
+    };
+
+    use chrono::TimeZone;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn time_options_at(timestamp: DateTime<Utc>) -> TimeOptions<impl Fn() -> DateTime<Utc>> {
+        TimeOptions::new(Duration::minutes(1), move || timestamp)
+    }
+
+    fn create_rotation() -> KeyRotation<Hs256> {
+        let start = start();
+        KeyRotation::new(Hs256, Duration::days(1))
+            .with_key(RotatingKey::new("1", Hs256Key::new(b"first"), start))
+            .with_key(RotatingKey::new(
+                "2",
+                Hs256Key::new(b"second"),
+                start + Duration::weeks(1),
+            ))
+            .with_key(RotatingKey::new(
+                "3",
+                Hs256Key::new(b"third"),
+                start + Duration::weeks(2),
+            ))
+    }
+
+    fn verifying_key_ids<'a, F: Fn() -> DateTime<Utc>>(
+        rotation: &'a KeyRotation<Hs256>,
+        time_options: &TimeOptions<F>,
+    ) -> Vec<&'a str> {
+        rotation
+            .verifying_keys(time_options)
+            .map(RotatingKey::key_id)
+            .collect()
+    }
+
+    #[test]
+    fn signing_keys_are_rotated() {
+        let rotation = create_rotation();
+        let time_options = time_options_at(start() - Duration::hours(1));
+        assert!(rotation.signing_key(&time_options).is_none());
+        assert!(verifying_key_ids(&rotation, &time_options).is_empty());
+
+        let time_options = time_options_at(start() + Duration::days(1));
+        assert_eq!(rotation.signing_key(&time_options).unwrap().key_id(), "1");
+        assert_eq!(verifying_key_ids(&rotation, &time_options), ["1"]);
+
+        let time_options = time_options_at(start() + Duration::days(7) + Duration::hours(1));
+        assert_eq!(rotation.signing_key(&time_options).unwrap().key_id(), "2");
+        assert_eq!(verifying_key_ids(&rotation, &time_options), ["1", "2"]);
+
+        let time_options = time_options_at(start() + Duration::days(9));
+        assert_eq!(rotation.signing_key(&time_options).unwrap().key_id(), "2");
+        assert_eq!(verifying_key_ids(&rotation, &time_options), ["2"]);
+
+        let time_options = time_options_at(start() + Duration::weeks(3));
+        assert_eq!(rotation.signing_key(&time_options).unwrap().key_id(), "3");
+        assert_eq!(verifying_key_ids(&rotation, &time_options), ["3"]);
+    }
+
+    #[test]
+    fn retired_key() {
+        let retired_at = start() + Duration::days(3);
+        let rotation = KeyRotation::new(Hs256, Duration::days(1)).with_key(
+            RotatingKey::new("1", Hs256Key::new(b"first"), start()).with_retirement(retired_at),
+        );
+
+        let time_options = time_options_at(retired_at - Duration::hours(1));
+        assert!(rotation.signing_key(&time_options).is_some());
+        let time_options = time_options_at(retired_at + Duration::hours(1));
+        assert!(rotation.signing_key(&time_options).is_none());
+        assert_eq!(verifying_key_ids(&rotation, &time_options), ["1"]);
+        let time_options = time_options_at(retired_at + Duration::days(2));
+        assert!(verifying_key_ids(&rotation, &time_options).is_empty());
+    }
+
+    #[test]
+    fn tokens_are_verified_after_rotation() {
+        let rotation = create_rotation();
+        let time_options = time_options_at(start() + Duration::days(6));
+        let signing_key = rotation.signing_key(&time_options).unwrap();
+        let token = rotation
+            .signing_algorithm()
+            .token(&Header::empty(), &Claims::empty(), signing_key)
+            .unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+        assert_eq!(token.header().key_id.as_deref(), Some("1"));
+
+        let time_options = time_options_at(start() + Duration::days(7) + Duration::hours(12));
+        let verifying_key = rotation
+            .verifying_key(token.header(), &time_options)
+            .unwrap();
+        rotation
+            .algorithm()
+            .validator::<Empty>(verifying_key)
+            .validate(&token)
+            .unwrap();
+
+        let time_options = time_options_at(start() + Duration::days(9));
+        assert!(rotation
+            .verifying_key(token.header(), &time_options)
+            .is_none());
+        assert!(rotation
+            .verifying_key(&Header::empty(), &time_options)
+            .is_none());
+    }
+
+    #[test]
+    fn extreme_timestamps() {
+        let rotation = KeyRotation::new(Hs256, Duration::days(1)).with_key(
+            RotatingKey::new("1", Hs256Key::new(b"first"), DateTime::<Utc>::MIN_UTC)
+                .with_retirement(DateTime::<Utc>::MAX_UTC),
+        );
+        let time_options = time_options_at(start());
+        assert_eq!(verifying_key_ids(&rotation, &time_options), ["1"]);
+        let time_options = time_options_at(DateTime::<Utc>::MAX_UTC);
+        assert_eq!(verifying_key_ids(&rotation, &time_options), ["1"]);
+        // Symmetric keys are never exported, but checking them must not panic either.
+        assert!(rotation.public_key_set(&time_options).keys().is_empty());
+
+        let mut rotation = KeyRotation::new(Hs256, Duration::weeks(1_000_000_000))
+            .with_key(RotatingKey::new("1", Hs256Key::new(b"first"), start()))
+            .with_key(RotatingKey::new(
+                "2",
+                Hs256Key::new(b"second"),
+                start() + Duration::weeks(1),
+            ));
+        let time_options = time_options_at(start() + Duration::weeks(100));
+        assert_eq!(verifying_key_ids(&rotation, &time_options), ["1", "2"]);
+        rotation.remove_expired(&time_options);
+        assert_eq!(rotation.keys().len(), 2);
+    }
+
+    #[test]
+    fn removing_expired_keys() {
+        let mut rotation = create_rotation();
+        rotation.remove_expired(&time_options_at(start() + Duration::days(9)));
+        let key_ids: Vec<_> = rotation.keys().iter().map(RotatingKey::key_id).collect();
+        assert_eq!(key_ids, ["2", "3"]);
+    }
+
+    #[test]
+    fn key_from_jwk() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let jwk = ExtendedJsonWebKey::new(JsonWebKey::from(&key));
+        let err = RotatingKey::from_jwk(&Hs256, &jwk, start()).unwrap_err();
+        assert!(matches!(err, JwkError::NoField(field) if field == "kid"));
+
+        let jwk = jwk.with_key_id("my-key");
+        let key = RotatingKey::from_jwk(&Hs256, &jwk, start()).unwrap();
+        assert_eq!(key.key_id(), "my-key");
+        let rotation = KeyRotation::new(Hs256, Duration::days(1)).with_key(key);
+        // Symmetric keys are not exported.
+        let key_set = rotation.public_key_set(&time_options_at(start()));
+        assert!(key_set.keys().is_empty());
+    }
+
+    #[cfg(feature = "p256")]
+    #[test]
+    fn exporting_public_keys() {
+        use crate::alg::Es256;
+
+        let signing_key = <Es256 as Algorithm>::SigningKey::from_slice(&[1; 32]).unwrap();
+        let next_signing_key = <Es256 as Algorithm>::SigningKey::from_slice(&[2; 32]).unwrap();
+        let rotation = KeyRotation::new(Es256, Duration::days(1))
+            .with_key(RotatingKey::new("1", signing_key, start()))
+            .with_key(RotatingKey::new(
+                "2",
+                next_signing_key,
+                start() + Duration::weeks(1),
+            ));
+
+        let key_set = rotation.public_key_set(&time_options_at(start()));
+        let key_ids: Vec<_> = key_set
+            .keys()
+            .iter()
+            .map(|key| key.key_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(key_ids, ["1", "2"]);
+        let key = &key_set.keys()[0];
+        assert_eq!(key.algorithm.as_deref(), Some("ES256"));
+        assert_eq!(key.key_use, Some(KeyUse::Signature));
+        assert!(!key.base.is_signing_key());
+
+        let key_set = rotation.public_key_set(&time_options_at(start() + Duration::days(10)));
+        assert_eq!(key_set.keys().len(), 1);
+        assert_eq!(key_set.keys()[0].key_id.as_deref(), Some("2"));
+    }
+}
@@ -0,0 +1,303 @@
+//! JWK sets.
+
+use serde::{de::IgnoredAny, Deserialize, Deserializer, Serialize};
+
+use super::{ExtendedJsonWebKey, KeyUse};
+use crate::{alloc::Vec, Empty, Header};
+
+/// [JWK set], i.e. a collection of [`ExtendedJsonWebKey`]s, as usually published
+/// by identity providers.
+///
+/// # Serialization
+///
+/// A key set is (de)serialized as an object with the `keys` field containing the array of keys.
+/// As recommended by the spec, keys that cannot be parsed (e.g., ones with an unknown
+/// key type `kty`, or missing required fields) are skipped on deserialization instead of
+/// failing the entire set. Other fields of the set object are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use jwt_compact::{alg::Hs256Key, jwk::{JsonWebKeySet, KeyQuery}};
+/// # use jwt_compact::Header;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let json = r#"{
+///     "keys": [
+///         { "kty": "oct", "kid": "1", "k": "c2VjcmV0X2tleQ" },
+///         { "kty": "unknown", "kid": "2" }
+///     ]
+/// }"#;
+/// let key_set: JsonWebKeySet<'_> = serde_json::from_str(json)?;
+/// assert_eq!(key_set.keys().len(), 1);
+///
+/// let header = Header::empty().with_key_id("1");
+/// let jwk = key_set
+///     .find(&KeyQuery::for_header(&header).with_algorithm("HS256"))
+///     .expect("key not found");
+/// let key = Hs256Key::try_from(&jwk.base)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [JWK set]: https://www.rfc-editor.org/rfc/rfc7517#section-5
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonWebKeySet<'a, T = Empty> {
+    keys: Vec<ExtendedJsonWebKey<'a, T>>,
+}
+
+impl<T> Default for JsonWebKeySet<'_, T> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<'a, T> From<Vec<ExtendedJsonWebKey<'a, T>>> for JsonWebKeySet<'a, T> {
+    fn from(keys: Vec<ExtendedJsonWebKey<'a, T>>) -> Self {
+        Self { keys }
+    }
+}
+
+impl<'a, T> FromIterator<ExtendedJsonWebKey<'a, T>> for JsonWebKeySet<'a, T> {
+    fn from_iter<I: IntoIterator<Item = ExtendedJsonWebKey<'a, T>>>(iter: I) -> Self {
+        Self {
+            keys: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a, T> JsonWebKeySet<'a, T> {
+    /// Creates an empty key set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns keys in this set.
+    pub fn keys(&self) -> &[ExtendedJsonWebKey<'a, T>] {
+        &self.keys
+    }
+
+    /// Adds a key to this set.
+    pub fn push(&mut self, key: ExtendedJsonWebKey<'a, T>) {
+        self.keys.push(key);
+    }
+
+    /// Adds a key to this set.
+    #[must_use]
+    pub fn with_key(mut self, key: ExtendedJsonWebKey<'a, T>) -> Self {
+        self.push(key);
+        self
+    }
+
+    /// Iterates over keys in this set matching the specified `query`.
+    pub fn filter<'s>(
+        &'s self,
+        query: &'s KeyQuery<'_>,
+    ) -> impl Iterator<Item = &'s ExtendedJsonWebKey<'a, T>> + 's {
+        self.keys.iter().filter(move |key| query.matches(key))
+    }
+
+    /// Finds the first key in this set matching the specified `query`.
+    pub fn find(&self, query: &KeyQuery<'_>) -> Option<&ExtendedJsonWebKey<'a, T>> {
+        self.keys.iter().find(|key| query.matches(key))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for JsonWebKeySet<'_, T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        #[allow(clippy::large_enum_variant)] // values are short-lived
+        enum MaybeKey<'a, T> {
+            Key(ExtendedJsonWebKey<'a, T>),
+            Unsupported(IgnoredAny),
+        }
+
+        #[derive(Deserialize)]
+        struct RawKeySet<'a, T> {
+            keys: Vec<MaybeKey<'a, T>>,
+        }
+
+        let raw = RawKeySet::<T>::deserialize(deserializer)?;
+        let keys = raw.keys.into_iter().filter_map(|key| match key {
+            MaybeKey::Key(key) => Some(key),
+            MaybeKey::Unsupported(_) => None,
+        });
+        Ok(keys.collect())
+    }
+}
+
+/// Query to find keys in a [`JsonWebKeySet`].
+///
+/// A key matches the query if all conditions specified in the query hold:
+///
+/// - If the query specifies a key ID, the key must have the same ID.
+/// - If the query specifies an algorithm or key use, the key must either have the same value
+///   of the corresponding parameter, or not specify the parameter at all.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyQuery<'q> {
+    key_id: Option<&'q str>,
+    algorithm: Option<&'q str>,
+    key_use: Option<&'q KeyUse>,
+}
+
+impl<'q> KeyQuery<'q> {
+    /// Creates a query matching all keys.
+    pub const fn new() -> Self {
+        Self {
+            key_id: None,
+            algorithm: None,
+            key_use: None,
+        }
+    }
+
+    /// Creates a query for the key ID specified in the token `header`. If the header
+    /// does not specify the key ID, the query will match keys regardless of their ID.
+    pub fn for_header<H>(header: &'q Header<H>) -> Self {
+        Self {
+            key_id: header.key_id.as_deref(),
+            ..Self::new()
+        }
+    }
+
+    /// Requires that matching keys have the specified ID.
+    #[must_use]
+    pub fn with_key_id(mut self, key_id: &'q str) -> Self {
+        self.key_id = Some(key_id);
+        self
+    }
+
+    /// Requires that matching keys are intended for use with the specified `algorithm`.
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: &'q str) -> Self {
+        self.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Requires that matching keys have the specified intended use.
+    #[must_use]
+    pub fn with_key_use(mut self, key_use: &'q KeyUse) -> Self {
+        self.key_use = Some(key_use);
+        self
+    }
+
+    /// Checks whether the provided `key` matches this query.
+    pub fn matches<T>(&self, key: &ExtendedJsonWebKey<'_, T>) -> bool {
+        if let Some(key_id) = self.key_id {
+            if key.key_id.as_deref() != Some(key_id) {
+                return false;
+            }
+        }
+        if let (Some(expected), Some(actual)) = (self.algorithm, &key.algorithm) {
+            if expected != actual {
+                return false;
+            }
+        }
+        if let (Some(expected), Some(actual)) = (self.key_use, &key.key_use) {
+            if expected != actual {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alloc::ToString,
+        jwk::{JsonWebKey, KeyType},
+    };
+
+    use assert_matches::assert_matches;
+
+    /// Public keys from [RFC 7517](https://www.rfc-editor.org/rfc/rfc7517#appendix-A.1)
+    /// with an added key of an unknown type.
+    const KEY_SET: &str = r#"{
+        "keys": [
+            {
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+                "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM",
+                "use": "enc",
+                "kid": "1"
+            },
+            {
+                "kty": "RSA",
+                "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+                "e": "AQAB",
+                "alg": "RS256",
+                "kid": "2011-04-29"
+            },
+            {
+                "kty": "unknown",
+                "kid": "2011-04-29"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn deserializing_key_set() {
+        let key_set: JsonWebKeySet<'_> = serde_json::from_str(KEY_SET).unwrap();
+        assert_eq!(key_set.keys().len(), 2);
+
+        let ec_key = &key_set.keys()[0];
+        assert_eq!(ec_key.base.key_type(), KeyType::EllipticCurve);
+        assert_eq!(ec_key.key_id.as_deref(), Some("1"));
+        assert_eq!(ec_key.key_use, Some(KeyUse::Encryption));
+        assert_eq!(ec_key.algorithm, None);
+
+        let rsa_key = &key_set.keys()[1];
+        assert_eq!(rsa_key.base.key_type(), KeyType::Rsa);
+        assert_eq!(rsa_key.key_id.as_deref(), Some("2011-04-29"));
+        assert_eq!(rsa_key.key_use, None);
+        assert_eq!(rsa_key.algorithm.as_deref(), Some("RS256"));
+
+        let json = serde_json::to_value(&key_set).unwrap();
+        assert_eq!(json["keys"].as_array().unwrap().len(), 2);
+        assert_eq!(json["keys"][1]["alg"], "RS256");
+        let restored: JsonWebKeySet<'_> = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, key_set);
+    }
+
+    #[test]
+    fn key_set_with_invalid_structure() {
+        let err = serde_json::from_str::<JsonWebKeySet<'_>>(r#"{"keys": {}}"#).unwrap_err();
+        assert!(err.to_string().contains("expected a sequence"), "{err}");
+        let err = serde_json::from_str::<JsonWebKeySet<'_>>("{}").unwrap_err();
+        assert!(err.to_string().contains("missing field `keys`"), "{err}");
+    }
+
+    #[test]
+    fn finding_keys() {
+        let key_set: JsonWebKeySet<'_> = serde_json::from_str(KEY_SET).unwrap();
+
+        let key = key_set.find(&KeyQuery::new().with_key_id("1")).unwrap();
+        assert_matches!(&key.base, JsonWebKey::EllipticCurve { curve, .. } if curve == "P-256");
+        let query = KeyQuery::new()
+            .with_key_id("1")
+            .with_key_use(&KeyUse::Signature);
+        assert!(key_set.find(&query).is_none());
+        let query = KeyQuery::new().with_key_id("1").with_algorithm("ES256");
+        assert!(key_set.find(&query).is_some());
+
+        let header = Header::empty().with_key_id("2011-04-29");
+        let query = KeyQuery::for_header(&header);
+        let key = key_set.find(&query).unwrap();
+        assert_eq!(key.base.key_type(), KeyType::Rsa);
+        let query = query
+            .with_algorithm("RS256")
+            .with_key_use(&KeyUse::Signature);
+        assert!(key_set.find(&query).is_some());
+        let query = query.with_algorithm("PS256");
+        assert!(key_set.find(&query).is_none());
+
+        assert!(key_set.find(&KeyQuery::new().with_key_id("2")).is_none());
+        assert_eq!(key_set.filter(&KeyQuery::new()).count(), 2);
+        let header = Header::empty();
+        let query = KeyQuery::for_header(&header).with_key_use(&KeyUse::Encryption);
+        assert_eq!(key_set.filter(&query).count(), 2);
+    }
+}
@@ -0,0 +1,531 @@
+//! Fetching and caching of [JWK sets](JsonWebKeySet) published by identity providers.
+//!
+//! The key set is loaded from the endpoint by a [`JwksTransport`] supplied by the application
+//! (e.g., a wrapper around an HTTP client), so this module does not depend on a particular
+//! HTTP client or async runtime. [`JwksCache`] caches the loaded key set as follows:
+//!
+//! - The key set is cached for the duration specified by the `max-age` directive
+//!   of the `Cache-Control` response header, or for the [default duration] if the header
+//!   is absent. `no-cache` and `no-store` directives disable caching.
+//! - If a token refers to a key ID not present in the cached set, the set is refetched
+//!   (e.g., because the identity provider has rotated its keys). Such refetches are rate-limited
+//!   by the [refetch interval].
+//! - If the key set cannot be fetched, the previously loaded (stale) key set is used.
+//!
+//! [default duration]: JwksCache::with_default_max_age()
+//! [refetch interval]: JwksCache::with_refetch_interval()
+//!
+//! # Examples
+//!
+//! ```
+//! # use chrono::Duration;
+//! use jwt_compact::{
+//!     alg::Hs256,
+//!     jwks::{FetchFuture, JwksCache, JwksResponse, JwksTransport},
+//!     prelude::*, Empty,
+//! };
+//!
+//! /// Transport returning a static key set. In real applications, the transport
+//! /// would use an HTTP client.
+//! struct StaticTransport;
+//!
+//! impl JwksTransport for StaticTransport {
+//!     fn fetch<'a>(&'a self, _url: &'a str) -> FetchFuture<'a> {
+//!         let body = br#"{ "keys": [{ "kty": "oct", "kid": "1", "k": "c2VjcmV0X2tleQ" }] }"#;
+//!         let response = JwksResponse::new(body.to_vec())
+//!             .with_cache_control("public, max-age=3600");
+//!         Box::pin(async move { Ok(response) })
+//!     }
+//! }
+//!
+//! async fn validate_token(
+//!     cache: &JwksCache<StaticTransport>,
+//!     token: &str,
+//! ) -> anyhow::Result<Token<Empty>> {
+//!     let token = UntrustedToken::new(token)?;
+//!     let time_options = TimeOptions::default();
+//!     let verifying_key = cache.resolve_key(&Hs256, &token, &time_options).await?;
+//!     Ok(Hs256.validator(&verifying_key).validate(&token)?)
+//! }
+//!
+//! let cache = JwksCache::new(StaticTransport, "https://example.com/.well-known/jwks.json")
+//!     .with_refetch_interval(Duration::minutes(5));
+//! // Use `cache` in an async context...
+//! ```
+
+use chrono::{DateTime, Duration, Utc};
+
+use core::{fmt, future::Future, pin::Pin};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::{
+    alloc::{Box, String, Vec},
+    jwk::{JsonWebKey, JsonWebKeySet, JwkError},
+    Algorithm, KeyRequest, KeyResolver, TimeOptions, UntrustedToken, ValidationError,
+};
+
+/// Future returned by [`JwksTransport::fetch()`].
+pub type FetchFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<JwksResponse, anyhow::Error>> + Send + 'a>>;
+
+/// Transport used by [`JwksCache`] to load key sets, e.g., a wrapper around an HTTP client.
+pub trait JwksTransport {
+    /// Fetches the key set from the specified `url`.
+    fn fetch<'a>(&'a self, url: &'a str) -> FetchFuture<'a>;
+}
+
+/// Response returned by a [`JwksTransport`].
+#[derive(Debug, Clone)]
+pub struct JwksResponse {
+    body: Vec<u8>,
+    cache_control: Option<String>,
+}
+
+impl JwksResponse {
+    /// Creates a response with the specified body, which should contain the JSON-encoded
+    /// key set.
+    pub fn new(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            body: body.into(),
+            cache_control: None,
+        }
+    }
+
+    /// Sets the value of the `Cache-Control` header of the response.
+    #[must_use]
+    pub fn with_cache_control(mut self, value: impl Into<String>) -> Self {
+        self.cache_control = Some(value.into());
+        self
+    }
+
+    /// Parses the `Cache-Control` header value. Returns `None` if the header is absent
+    /// or does not specify caching duration.
+    fn max_age(&self) -> Option<Duration> {
+        let cache_control = self.cache_control.as_deref()?;
+        let mut max_age = None;
+        for directive in cache_control.split(',') {
+            let directive = directive.trim();
+            let (name, value) = directive
+                .split_once('=')
+                .map_or((directive, None), |(name, value)| (name, Some(value)));
+            if name.eq_ignore_ascii_case("no-cache") || name.eq_ignore_ascii_case("no-store") {
+                return Some(Duration::zero());
+            } else if name.eq_ignore_ascii_case("max-age") {
+                let seconds = value.and_then(|value| value.trim_matches('"').parse::<u32>().ok());
+                max_age = seconds.map(|seconds| Duration::seconds(seconds.into()));
+            }
+        }
+        max_age
+    }
+}
+
+/// Errors that can occur when fetching a key set.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JwksError {
+    /// Error returned by the [`JwksTransport`].
+    Transport(anyhow::Error),
+    /// The response cannot be parsed as a key set.
+    Malformed(serde_json::Error),
+}
+
+impl fmt::Display for JwksError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(err) => write!(formatter, "cannot fetch key set: {err}"),
+            Self::Malformed(err) => write!(formatter, "malformed key set: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for JwksError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err.as_ref()),
+            Self::Malformed(err) => Some(err),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    key_set: Option<Arc<JsonWebKeySet<'static>>>,
+    expires_at: Option<DateTime<Utc>>,
+    last_fetched_at: Option<DateTime<Utc>>,
+}
+
+impl CacheState {
+    fn can_refetch(&self, now: DateTime<Utc>, refetch_interval: Duration) -> bool {
+        self.last_fetched_at.map_or(true, |fetched_at| {
+            // On overflow, there is no representable time to wait for, so we allow refetching.
+            fetched_at
+                .checked_add_signed(refetch_interval)
+                .map_or(true, |refetch_at| now >= refetch_at)
+        })
+    }
+}
+
+/// Cache of a [`JsonWebKeySet`] loaded from a single endpoint.
+///
+/// See the [module docs](self) for details on caching logic.
+#[derive(Debug)]
+pub struct JwksCache<T> {
+    transport: T,
+    url: String,
+    default_max_age: Duration,
+    max_age_limit: Duration,
+    refetch_interval: Duration,
+    state: Mutex<CacheState>,
+}
+
+impl<T: JwksTransport> JwksCache<T> {
+    /// Creates a cache for the key set at the specified `url` loaded using the `transport`.
+    pub fn new(transport: T, url: impl Into<String>) -> Self {
+        Self {
+            transport,
+            url: url.into(),
+            default_max_age: Duration::hours(1),
+            max_age_limit: Duration::days(1),
+            refetch_interval: Duration::minutes(1),
+            state: Mutex::default(),
+        }
+    }
+
+    /// Sets the caching duration used if the response does not contain the `Cache-Control`
+    /// header. The default value is 1 hour.
+    #[must_use]
+    pub fn with_default_max_age(mut self, max_age: Duration) -> Self {
+        self.default_max_age = max_age;
+        self
+    }
+
+    /// Sets the upper bound for the caching duration specified in the `Cache-Control`
+    /// response header. The default value is 1 day.
+    #[must_use]
+    pub fn with_max_age_limit(mut self, max_age: Duration) -> Self {
+        self.max_age_limit = max_age;
+        self
+    }
+
+    /// Sets the minimum interval between fetches caused by unknown key IDs or fetch failures.
+    /// The default value is 1 minute.
+    #[must_use]
+    pub fn with_refetch_interval(mut self, interval: Duration) -> Self {
+        self.refetch_interval = interval;
+        self
+    }
+
+    /// Returns the URL of the key set.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        // The state is always consistent, so it's safe to recover from poisoning.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the currently cached key set without fetching it. The returned set may be stale.
+    pub fn cached_key_set(&self) -> Option<Arc<JsonWebKeySet<'static>>> {
+        self.lock_state().key_set.clone()
+    }
+
+    async fn fetch(&self, now: DateTime<Utc>) -> Result<Arc<JsonWebKeySet<'static>>, JwksError> {
+        self.lock_state().last_fetched_at = Some(now);
+        let response = self
+            .transport
+            .fetch(&self.url)
+            .await
+            .map_err(JwksError::Transport)?;
+        let key_set: JsonWebKeySet<'static> =
+            serde_json::from_slice(&response.body).map_err(JwksError::Malformed)?;
+        let key_set = Arc::new(key_set);
+
+        let max_age = response
+            .max_age()
+            .unwrap_or(self.default_max_age)
+            .min(self.max_age_limit);
+        let mut state = self.lock_state();
+        state.key_set = Some(Arc::clone(&key_set));
+        let expires_at = now
+            .checked_add_signed(max_age)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        state.expires_at = Some(expires_at);
+        Ok(key_set)
+    }
+
+    /// Returns the key set, fetching it if the cached set is absent or expired.
+    /// If fetching fails, but there is a stale cached set, it is returned instead
+    /// of an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key set is not cached, and cannot be fetched.
+    pub async fn key_set<F>(
+        &self,
+        time_options: &TimeOptions<F>,
+    ) -> Result<Arc<JsonWebKeySet<'static>>, JwksError>
+    where
+        F: Fn() -> DateTime<Utc>,
+    {
+        let now = (time_options.clock_fn)();
+        let stale_key_set = {
+            let state = self.lock_state();
+            let is_fresh = state
+                .expires_at
+                .map_or(false, |expires_at| now < expires_at);
+            match &state.key_set {
+                Some(key_set) if is_fresh => return Ok(Arc::clone(key_set)),
+                Some(key_set) if !state.can_refetch(now, self.refetch_interval) => {
+                    // Do not retry failed fetches too often.
+                    return Ok(Arc::clone(key_set));
+                }
+                stale_key_set => stale_key_set.clone(),
+            }
+        };
+
+        self.fetch(now)
+            .await
+            .or_else(|err| stale_key_set.ok_or(err))
+    }
+
+    /// Resolves the verifying key for the `token` using the key set. The key is selected
+    /// as described in the [`KeyResolver`] implementation for [`JsonWebKeySet`]
+    /// (i.e., by the key ID, algorithm and the certificate thumbprint). If the key
+    /// is not found, the key set is refetched (subject to rate limiting), and the lookup
+    /// is repeated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError::KeyNotFound`] if the key is not found,
+    /// and [`ValidationError::KeyResolution`] if the key set cannot be fetched.
+    pub async fn resolve_key<A, H, F>(
+        &self,
+        algorithm: &A,
+        token: &UntrustedToken<'_, H>,
+        time_options: &TimeOptions<F>,
+    ) -> Result<A::VerifyingKey, ValidationError>
+    where
+        A: Algorithm + ?Sized,
+        A::VerifyingKey: for<'jwk> TryFrom<&'jwk JsonWebKey<'jwk>, Error = JwkError>,
+        F: Fn() -> DateTime<Utc>,
+    {
+        let key_set = self
+            .key_set(time_options)
+            .await
+            .map_err(|err| ValidationError::KeyResolution(err.into()))?;
+        let request = KeyRequest::new(token);
+        match key_set.resolve_key(algorithm, &request) {
+            Err(ValidationError::KeyNotFound) => { /* continue */ }
+            result => return result,
+        }
+
+        let now = (time_options.clock_fn)();
+        if !self.lock_state().can_refetch(now, self.refetch_interval) {
+            return Err(ValidationError::KeyNotFound);
+        }
+        // Do not return fetch errors since they will be reported on `key_set()` calls.
+        let key_set = self
+            .fetch(now)
+            .await
+            .map_err(|_| ValidationError::KeyNotFound)?;
+        key_set.resolve_key(algorithm, &request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key},
+        alloc::{ToOwned, ToString},
+        AlgorithmExt, Claims, Empty, Header,
+    };
+
+    use assert_matches::assert_matches;
+    use chrono::TimeZone;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const KEY_SET: &str = r#"{ "keys": [{ "kty": "oct", "kid": "1", "k": "c2VjcmV0X2tleQ" }] }"#;
+    const ROTATED_KEY_SET: &str = r#"{
+        "keys": [
+            { "kty": "oct", "kid": "1", "k": "c2VjcmV0X2tleQ" },
+            { "kty": "oct", "kid": "2", "k": "b3RoZXJfa2V5" }
+        ]
+    }"#;
+
+    #[derive(Debug, Default)]
+    struct MockTransport {
+        responses: Mutex<Vec<Result<JwksResponse, String>>>,
+        fetch_count: AtomicUsize,
+    }
+
+    impl MockTransport {
+        fn push_response(&self, response: Result<JwksResponse, &str>) {
+            let response = response.map_err(ToOwned::to_owned);
+            self.responses.lock().unwrap().push(response);
+        }
+    }
+
+    impl JwksTransport for MockTransport {
+        fn fetch<'a>(&'a self, url: &'a str) -> FetchFuture<'a> {
+            assert_eq!(url, "https://example.com/jwks");
+            self.fetch_count.fetch_add(1, Ordering::SeqCst);
+            let response = self.responses.lock().unwrap().remove(0);
+            Box::pin(async move { response.map_err(anyhow::Error::msg) })
+        }
+    }
+
+    fn create_cache() -> JwksCache<MockTransport> {
+        JwksCache::new(MockTransport::default(), "https://example.com/jwks")
+    }
+
+    fn time_options_at(timestamp: DateTime<Utc>) -> TimeOptions<impl Fn() -> DateTime<Utc> + Copy> {
+        TimeOptions::new(Duration::zero(), move || timestamp)
+    }
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn fetch_count(cache: &JwksCache<MockTransport>) -> usize {
+        cache.transport.fetch_count.load(Ordering::SeqCst)
+    }
+
+    fn assert_send<T: Send>(_value: &T) {}
+
+    #[test]
+    fn futures_are_send() {
+        let cache = create_cache();
+        let time_options = time_options_at(start());
+        assert_send(&cache.key_set(&time_options));
+        let token = UntrustedToken::new("eyJhbGciOiJIUzI1NiJ9.e30.c2ln").unwrap();
+        assert_send(&cache.resolve_key(&Hs256, &token, &time_options));
+    }
+
+    #[test]
+    fn parsing_cache_control() {
+        let response = JwksResponse::new(vec![]);
+        assert_eq!(response.max_age(), None);
+        let response = response.with_cache_control("public, max-age=600, must-revalidate");
+        assert_eq!(response.max_age(), Some(Duration::minutes(10)));
+        let response = response.with_cache_control("Max-Age=\"60\"");
+        assert_eq!(response.max_age(), Some(Duration::minutes(1)));
+        let response = response.with_cache_control("max-age=600, no-cache");
+        assert_eq!(response.max_age(), Some(Duration::zero()));
+        let response = response.with_cache_control("max-age=invalid");
+        assert_eq!(response.max_age(), None);
+    }
+
+    #[test]
+    fn caching_key_set() {
+        let cache = create_cache();
+        let response = JwksResponse::new(KEY_SET).with_cache_control("max-age=600");
+        cache.transport.push_response(Ok(response));
+
+        let time_options = time_options_at(start());
+        let key_set = pollster::block_on(cache.key_set(&time_options)).unwrap();
+        assert_eq!(key_set.keys().len(), 1);
+        let time_options = time_options_at(start() + Duration::minutes(5));
+        pollster::block_on(cache.key_set(&time_options)).unwrap();
+        assert_eq!(fetch_count(&cache), 1);
+
+        // Stale key set is returned on fetch failure.
+        cache.transport.push_response(Err("connection refused"));
+        let time_options = time_options_at(start() + Duration::minutes(15));
+        let key_set = pollster::block_on(cache.key_set(&time_options)).unwrap();
+        assert_eq!(key_set.keys().len(), 1);
+        assert_eq!(fetch_count(&cache), 2);
+        // ...and fetches are not retried until the refetch interval passes.
+        pollster::block_on(cache.key_set(&time_options)).unwrap();
+        assert_eq!(fetch_count(&cache), 2);
+
+        let response = JwksResponse::new(ROTATED_KEY_SET);
+        cache.transport.push_response(Ok(response));
+        let time_options = time_options_at(start() + Duration::minutes(16));
+        let key_set = pollster::block_on(cache.key_set(&time_options)).unwrap();
+        assert_eq!(key_set.keys().len(), 2);
+        assert_eq!(fetch_count(&cache), 3);
+        assert_eq!(cache.cached_key_set().unwrap().keys().len(), 2);
+    }
+
+    #[test]
+    fn extreme_durations() {
+        let cache = create_cache()
+            .with_default_max_age(Duration::weeks(1_000_000_000))
+            .with_max_age_limit(Duration::weeks(1_000_000_000))
+            .with_refetch_interval(Duration::weeks(1_000_000_000));
+        cache
+            .transport
+            .push_response(Ok(JwksResponse::new(KEY_SET)));
+        let time_options = time_options_at(start());
+        pollster::block_on(cache.key_set(&time_options)).unwrap();
+        // The key set never expires.
+        let time_options = time_options_at(start() + Duration::weeks(1_000_000));
+        pollster::block_on(cache.key_set(&time_options)).unwrap();
+        assert_eq!(fetch_count(&cache), 1);
+
+        let cache = create_cache()
+            .with_default_max_age(Duration::zero())
+            .with_refetch_interval(Duration::weeks(1_000_000_000));
+        cache
+            .transport
+            .push_response(Ok(JwksResponse::new(KEY_SET)));
+        let time_options = time_options_at(DateTime::<Utc>::MAX_UTC);
+        pollster::block_on(cache.key_set(&time_options)).unwrap();
+        cache
+            .transport
+            .push_response(Ok(JwksResponse::new(ROTATED_KEY_SET)));
+        let key_set = pollster::block_on(cache.key_set(&time_options)).unwrap();
+        assert_eq!(key_set.keys().len(), 2);
+        assert_eq!(fetch_count(&cache), 2);
+    }
+
+    #[test]
+    fn fetch_errors() {
+        let cache = create_cache();
+        cache.transport.push_response(Err("connection refused"));
+        let time_options = time_options_at(start());
+        let err = pollster::block_on(cache.key_set(&time_options)).unwrap_err();
+        assert_matches!(err, JwksError::Transport(_));
+        assert_eq!(err.to_string(), "cannot fetch key set: connection refused");
+
+        cache
+            .transport
+            .push_response(Ok(JwksResponse::new(&b"{}"[..])));
+        let err = pollster::block_on(cache.key_set(&time_options)).unwrap_err();
+        assert_matches!(err, JwksError::Malformed(_));
+    }
+
+    #[test]
+    fn resolving_keys() {
+        let cache = create_cache();
+        cache
+            .transport
+            .push_response(Ok(JwksResponse::new(KEY_SET)));
+        let claims = Claims::empty();
+        let header = Header::empty().with_key_id("2");
+        let key = Hs256Key::new(b"other_key");
+        let token = Hs256.token(&header, &claims, &key).unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+
+        let time_options = time_options_at(start());
+        let resolved = pollster::block_on(cache.resolve_key(&Hs256, &token, &time_options));
+        assert_matches!(resolved.unwrap_err(), ValidationError::KeyNotFound);
+        // The key set was not refetched due to rate limiting.
+        assert_eq!(fetch_count(&cache), 1);
+
+        cache
+            .transport
+            .push_response(Ok(JwksResponse::new(ROTATED_KEY_SET)));
+        let time_options = time_options_at(start() + Duration::minutes(2));
+        let resolved = pollster::block_on(cache.resolve_key(&Hs256, &token, &time_options));
+        let verifying_key = resolved.unwrap();
+        assert_eq!(fetch_count(&cache), 2);
+        Hs256
+            .validator::<Empty>(&verifying_key)
+            .validate(&token)
+            .unwrap();
+    }
+}
@@ -15,12 +15,41 @@
 //! # Additional features
 //!
 //! - The crate supports more compact [CBOR] encoding of the claims. This feature is enabled
-//!   via the [`serde_cbor` feature](#cbor-support).
+//!   via the [`cbor` feature](#cbor-support).
 //! - The crate supports `EdDSA` algorithm with the Ed25519 elliptic curve, and `ES256K` algorithm
 //!   with the secp256k1 elliptic curve.
 //! - Supports basic [JSON Web Key](https://tools.ietf.org/html/rfc7517.html) functionality,
 //!   e.g., for converting keys to / from JSON or computing
 //!   [a key thumbprint](https://tools.ietf.org/html/rfc7638).
+//! - [`ValidationPolicy`] bundles requirements for tokens (e.g., required claims, expected
+//!   issuers and audiences) and checks them together with the token integrity in a single call.
+//! - Supports [JSON Web Encryption](jwe) with `dir`, AES key wrap, `RSA-OAEP-256` and `ECDH-ES`
+//!   key management and AES-GCM / AES-CBC-HMAC-SHA2 content encryption. This functionality is gated behind
+//!   the `jwe` crate feature.
+//! - Supports [JWS JSON serialization](https://www.rfc-editor.org/rfc/rfc7515#section-7.2)
+//!   (both general and flattened) with multiple signatures over the same claims; see
+//!   [`JsonTokenBuilder`] and [`UntrustedJsonToken`].
+//! - Supports detached and [unencoded](https://www.rfc-editor.org/rfc/rfc7797) payloads; see
+//!   [`AlgorithmExt::detached_token()`] and [`UntrustedToken::with_detached_payload()`].
+//! - Supports [nested tokens](NestedToken) (e.g., a signed token wrapped into another signed
+//!   or encrypted token).
+//! - Supports [CBOR Web Tokens](cwt) (CWTs) secured with `COSE_Sign1` / `COSE_Mac0`
+//!   structures, and converting JWKs to / from the `COSE_Key` form. This functionality is gated
+//!   behind the `cwt` crate feature.
+//! - Supports importing and exporting keys in the DER and PEM formats (SPKI, PKCS#8,
+//!   SEC1 and PKCS#1) via [`PublicKeyEncoding`](alg::PublicKeyEncoding) and
+//!   [`PrivateKeyEncoding`](alg::PrivateKeyEncoding) traits. This functionality is gated
+//!   behind the `pem` crate feature.
+//! - Supports [validating X.509 certificate chains](x509) from the `x5c` header field
+//!   against a set of trust anchors, and extracting the signing key from the leaf certificate.
+//!   This functionality is gated behind the `x509` crate feature.
+//! - Supports [fetching and caching JWK sets](jwks) published by identity providers
+//!   via a transport supplied by the application. This functionality is gated behind
+//!   the `jwks` crate feature.
+//! - Supports validating [OAuth 2.0 access tokens](oauth) as per RFC 9068
+//!   and [OIDC ID tokens](oidc).
+//! - Supports creating and validating [`DPoP` proofs](dpop) for sender-constrained
+//!   access tokens as per RFC 9449.
 //!
 //! ## Supported algorithms
 //!
@@ -33,6 +62,8 @@
 //! | `ES256K` | `es256k` | [Rust binding][`secp256k1`] for [`libsecp256k1`] |
 //! | `ES256K` | [`k256`] | Pure Rust implementation |
 //! | `ES256`  | [`p256`] | Pure Rust implementation |
+//! | `ES384`  | [`p384`] | Pure Rust implementation |
+//! | `ES512`  | [`p521`] | Pure Rust implementation |
 //! | `RS*`, `PS*` (RSA) | `rsa` | Uses pure Rust [`rsa`] crate with blinding |
 //!
 //! `EdDSA` and `ES256K` algorithms are somewhat less frequently supported by JWT implementations
@@ -42,22 +73,29 @@
 //! securely generated). These algs have 128-bit security, making them an alternative
 //! to `ES256`.
 //!
-//! RSA support requires a system-wide RNG retrieved via the [`getrandom`] crate.
+//! RSA and `ES512` support requires a system-wide RNG retrieved via the [`getrandom`] crate.
 //! In case of a compilation failure in the `getrandom` crate, you may want
 //! to include it as a direct dependency and specify one of its features
 //! to assist `getrandom` with choosing an appropriate RNG implementation; consult `getrandom` docs
 //! for more details. See also WASM and bare-metal E2E tests included
 //! in the [source code repository] of this crate.
 //!
+//! If the algorithm is not known in advance (e.g., if it is determined by a [JWK](jwk)
+//! from a key set), [`AnyAlgorithm`](alg::AnyAlgorithm) can be used. It dispatches to one
+//! of the algorithms enabled via crate features, and selects the algorithm from an explicit
+//! allow-list ensuring that it is compatible with the verifying key.
+//!
 //! ## CBOR support
 //!
-//! If the `serde_cbor` crate feature is enabled (and it is enabled by default), token claims can
+//! If the `cbor` crate feature is enabled (and it is enabled by default), token claims can
 //! be encoded using [CBOR] with the [`AlgorithmExt::compact_token()`] method.
 //! The compactly encoded JWTs have the [`cty` field] (content type) in their header
 //! set to `"CBOR"`. Tokens with such encoding can be verified in the same way as ordinary tokens;
-//! see [examples below](#examples).
+//! see [examples below](#examples). Claims are encoded using the [deterministic encoding]
+//! from RFC 8949, so that signatures over CBOR claims are reproducible.
+//! (The `serde_cbor` feature is a deprecated alias for `cbor`; the CBOR backend is [`ciborium`].)
 //!
-//! If the `serde_cbor` feature is disabled, `AlgorithmExt::compact_token()` is not available.
+//! If the `cbor` feature is disabled, `AlgorithmExt::compact_token()` is not available.
 //! Verifying CBOR-encoded tokens in this case is not supported either;
 //! a [`ParseError::UnsupportedContentType`] will be returned when creating an [`UntrustedToken`]
 //! from the token string.
@@ -83,7 +121,9 @@
 //! [JWT header]: https://tools.ietf.org/html/rfc7519#section-5
 //! [`alg` field]: https://tools.ietf.org/html/rfc7515#section-4.1.1
 //! [`cty` field]: https://tools.ietf.org/html/rfc7515#section-4.1.10
-//! [CBOR]: https://tools.ietf.org/html/rfc7049
+//! [CBOR]: https://www.rfc-editor.org/rfc/rfc8949
+//! [deterministic encoding]: https://www.rfc-editor.org/rfc/rfc8949#section-4.2.1
+//! [`ciborium`]: https://crates.io/crates/ciborium
 //! [`sha2`]: https://docs.rs/sha2/
 //! [`libsodium`]: https://download.libsodium.org/doc/
 //! [`exonum-crypto`]: https://docs.rs/exonum-crypto/
@@ -93,6 +133,8 @@
 //! [`libsecp256k1`]: https://github.com/bitcoin-core/secp256k1
 //! [`k256`]: https://docs.rs/k256/
 //! [`p256`]: https://docs.rs/p256/
+//! [`p384`]: https://docs.rs/p384/
+//! [`p521`]: https://docs.rs/p521/
 //! [`rsa`]: https://docs.rs/rsa/
 //! [`chrono`]: https://docs.rs/chrono/
 //! [`getrandom`]: https://docs.rs/getrandom/
@@ -107,13 +149,13 @@
 //! use jwt_compact::{prelude::*, alg::{Hs256, Hs256Key}};
 //! use serde::{Serialize, Deserialize};
 //!
-//! /// Custom claims encoded in the token.
+//! /// Custom claims encoded in the token. Registered claims (such as `sub`)
+//! /// are a part of `Claims` and do not need to be specified here.
 //! #[derive(Debug, PartialEq, Serialize, Deserialize)]
 //! struct CustomClaims {
-//!     /// `sub` is a standard claim which denotes claim subject:
-//!     /// https://tools.ietf.org/html/rfc7519#section-4.1.2
-//!     #[serde(rename = "sub")]
-//!     subject: String,
+//!     /// Whether the token subject is an administrator.
+//!     #[serde(default)]
+//!     admin: bool,
 //! }
 //!
 //! # fn main() -> anyhow::Result<()> {
@@ -123,7 +165,9 @@
 //! let key = Hs256Key::new(b"super_secret_key_donut_steel");
 //! // Create a token.
 //! let header = Header::empty().with_key_id("my-key");
-//! let claims = Claims::new(CustomClaims { subject: "alice".to_owned() })
+//! let claims = Claims::new(CustomClaims { admin: false })
+//!     .set_subject("alice")
+//!     .set_audience("my-service")
 //!     .set_duration_and_issuance(&time_options, Duration::days(7))
 //!     .set_not_before(Utc::now() - Duration::hours(1));
 //! let token_string = Hs256.token(&header, &claims, &key)?;
@@ -139,10 +183,11 @@
 //! // Validate additional conditions.
 //! token.claims()
 //!     .validate_expiration(&time_options)?
-//!     .validate_maturity(&time_options)?;
+//!     .validate_maturity(&time_options)?
+//!     .validate_audience("my-service")?;
 //! // Now, we can extract information from the token (e.g., its subject).
-//! let subject = &token.claims().custom.subject;
-//! assert_eq!(subject, "alice");
+//! let subject = token.claims().subject.as_deref();
+//! assert_eq!(subject, Some("alice"));
 //! # Ok(())
 //! # } // end main()
 //! ```
@@ -157,18 +202,16 @@
 //! /// Custom claims encoded in the token.
 //! #[derive(Debug, PartialEq, Serialize, Deserialize)]
 //! struct CustomClaims {
-//!     /// `sub` is a standard claim which denotes claim subject:
-//!     ///     https://tools.ietf.org/html/rfc7519#section-4.1.2
-//!     /// The custom serializer we use allows to efficiently
-//!     /// encode the subject in CBOR.
-//!     #[serde(rename = "sub", with = "HexForm")]
-//!     subject: [u8; 32],
+//!     /// Private claim with the user ID. The custom serializer we use
+//!     /// allows to efficiently encode the ID in CBOR.
+//!     #[serde(rename = "uid", with = "HexForm")]
+//!     user_id: [u8; 32],
 //! }
 //!
 //! # fn main() -> anyhow::Result<()> {
 //! let time_options = TimeOptions::default();
 //! let key = Hs256Key::new(b"super_secret_key_donut_steel");
-//! let claims = Claims::new(CustomClaims { subject: [111; 32] })
+//! let claims = Claims::new(CustomClaims { user_id: [111; 32] })
 //!     .set_duration_and_issuance(&time_options, Duration::days(7));
 //! let token = Hs256.token(&Header::empty(), &claims, &key)?;
 //! println!("token: {token}");
@@ -180,8 +223,8 @@
 //! let token = UntrustedToken::new(&compact_token)?;
 //! let token: Token<CustomClaims> = Hs256.validator(&key).validate(&token)?;
 //! token.claims().validate_expiration(&time_options)?;
-//! // Now, we can extract information from the token (e.g., its subject).
-//! assert_eq!(token.claims().custom.subject, [111; 32]);
+//! // Now, we can extract information from the token (e.g., the user ID).
+//! assert_eq!(token.claims().custom.user_id, [111; 32]);
 //! # Ok(())
 //! # } // end main()
 //! ```
@@ -236,11 +279,31 @@
 )]
 
 pub mod alg;
+#[cfg(feature = "cbor")]
+mod cbor;
 mod claims;
+#[cfg(feature = "cwt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cwt")))]
+pub mod cwt;
+pub mod dpop;
 mod error;
+mod json;
+#[cfg(feature = "jwe")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jwe")))]
+pub mod jwe;
 pub mod jwk;
+#[cfg(feature = "jwks")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jwks")))]
+pub mod jwks;
+pub mod oauth;
+pub mod oidc;
+mod policy;
+mod resolver;
 mod token;
 mod traits;
+#[cfg(feature = "x509")]
+#[cfg_attr(docsrs, doc(cfg(feature = "x509")))]
+pub mod x509;
 
 // Polyfill for `alloc` types.
 mod alloc {
@@ -263,9 +326,12 @@ pub mod prelude {
 }
 
 pub use crate::{
-    claims::{Claims, Empty, TimeOptions},
-    error::{Claim, CreationError, ParseError, ValidationError},
-    token::{Header, SignedToken, Thumbprint, Token, UntrustedToken},
+    claims::{Audience, Claims, Empty, TimeOptions},
+    error::{Claim, CreationError, ParseError, PolicyError, ValidationError},
+    json::{JsonTokenBuilder, UntrustedJsonToken},
+    policy::ValidationPolicy,
+    resolver::{KeyRequest, KeyResolver},
+    token::{Header, NestedToken, SignedToken, Thumbprint, Token, UntrustedToken},
     traits::{Algorithm, AlgorithmExt, AlgorithmSignature, Renamed, Validator},
 };
 
@@ -0,0 +1,508 @@
+//! [JWT profile] for OAuth 2.0 access tokens.
+//!
+//! [`AccessTokenClaims`] contains claims specific to access tokens (such as `client_id`
+//! and `scope`), which are used together with the registered claims from [`Claims`](crate::Claims).
+//! [`AccessTokenValidator`] performs the access token validation checks prescribed by
+//! [RFC 9068][validation] in addition to checking the token integrity.
+//!
+//! [JWT profile]: https://www.rfc-editor.org/rfc/rfc9068.html
+//! [validation]: https://www.rfc-editor.org/rfc/rfc9068.html#section-4
+//!
+//! # Examples
+//!
+//! ```
+//! # use chrono::Duration;
+//! use jwt_compact::{
+//!     alg::{Hs256, Hs256Key},
+//!     oauth::{AccessTokenClaims, AccessTokenValidator, ACCESS_TOKEN_TYPE},
+//!     prelude::*,
+//! };
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let key = Hs256Key::new(b"super_secret_key_donut_steel");
+//! let time_options = TimeOptions::default();
+//!
+//! // Token creation is usually performed by the authorization server.
+//! let at_claims = AccessTokenClaims::empty()
+//!     .set_client_id("s6BhdRkqt3")
+//!     .set_scopes(["openid", "profile", "reademail"]);
+//! let claims = Claims::new(at_claims)
+//!     .set_issuer("https://authorization-server.example.com/")
+//!     .set_subject("5ba552d67")
+//!     .set_audience("https://rs.example.com/")
+//!     .set_jwt_id("dbe39bf3a3ba4238a513f51d6e1691c4")
+//!     .set_duration_and_issuance(&time_options, Duration::minutes(10));
+//! let header = Header::empty().with_token_type(ACCESS_TOKEN_TYPE);
+//! let token = Hs256.token(&header, &claims, &key)?;
+//!
+//! // Validation is performed by the resource server.
+//! let validator = AccessTokenValidator::new(
+//!     "https://authorization-server.example.com/",
+//!     "https://rs.example.com/",
+//!     time_options,
+//! );
+//! let validator = validator.with_required_scope("reademail");
+//! let token = UntrustedToken::new(&token)?;
+//! let token = validator.validate(Hs256.validator::<AccessTokenClaims>(&key), &token)?;
+//! assert!(token.claims().custom.has_scope("profile"));
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use core::fmt;
+
+use crate::{
+    alloc::{String, ToOwned, Vec},
+    dpop::Confirmation,
+    Algorithm, Claim, Empty, Header, TimeOptions, Token, UntrustedToken, ValidationError,
+    Validator,
+};
+
+/// Value of the `typ` header field for access tokens.
+pub const ACCESS_TOKEN_TYPE: &str = "at+jwt";
+
+/// Checks whether the provided `typ` header field value denotes an access token. As per
+/// [RFC 9068], the comparison is case-insensitive, and the `application/` prefix is allowed.
+///
+/// [RFC 9068]: https://www.rfc-editor.org/rfc/rfc9068.html#section-4
+pub fn is_access_token_type(token_type: &str) -> bool {
+    const MEDIA_TYPE_PREFIX: &str = "application/";
+
+    let token_type = match token_type.get(..MEDIA_TYPE_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(MEDIA_TYPE_PREFIX) => {
+            &token_type[MEDIA_TYPE_PREFIX.len()..]
+        }
+        _ => token_type,
+    };
+    token_type.eq_ignore_ascii_case(ACCESS_TOKEN_TYPE)
+}
+
+/// Claims specific to [access tokens], used as custom claims in [`Claims`](crate::Claims).
+///
+/// [access tokens]: https://www.rfc-editor.org/rfc/rfc9068.html#section-2.2
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct AccessTokenClaims<T = Empty> {
+    /// Client ID of the OAuth client that requested the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    /// Space-delimited list of scopes associated with the token. Use [`Self::scopes()`]
+    /// to iterate over individual scopes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+
+    /// Time when the end-user authentication occurred.
+    #[serde(
+        rename = "auth_time",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::claims::serde_timestamp"
+    )]
+    pub auth_time: Option<DateTime<Utc>>,
+
+    /// Authentication context class reference.
+    #[serde(rename = "acr", default, skip_serializing_if = "Option::is_none")]
+    pub auth_context_class: Option<String>,
+
+    /// Authentication method references.
+    #[serde(rename = "amr", default, skip_serializing_if = "Option::is_none")]
+    pub auth_methods: Option<Vec<String>>,
+
+    /// Confirmation of the key the token is bound to, e.g. a [`DPoP`](crate::dpop) key.
+    #[serde(rename = "cnf", default, skip_serializing_if = "Option::is_none")]
+    pub confirmation: Option<Confirmation>,
+
+    /// Other custom claims.
+    #[serde(flatten)]
+    pub custom: T,
+}
+
+impl AccessTokenClaims<Empty> {
+    /// Creates an empty claims instance.
+    pub fn empty() -> Self {
+        Self::new(Empty {})
+    }
+}
+
+impl<T> AccessTokenClaims<T> {
+    /// Creates a new instance with the provided custom claims.
+    pub fn new(custom_claims: T) -> Self {
+        Self {
+            client_id: None,
+            scope: None,
+            auth_time: None,
+            auth_context_class: None,
+            auth_methods: None,
+            confirmation: None,
+            custom: custom_claims,
+        }
+    }
+
+    /// Sets the `client_id` claim.
+    #[must_use]
+    pub fn set_client_id(self, client_id: impl Into<String>) -> Self {
+        Self {
+            client_id: Some(client_id.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `scope` claim by joining the provided `scopes` with spaces.
+    #[must_use]
+    pub fn set_scopes<S: AsRef<str>>(self, scopes: impl IntoIterator<Item = S>) -> Self {
+        let scopes: Vec<_> = scopes.into_iter().collect();
+        let scopes: Vec<&str> = scopes.iter().map(AsRef::as_ref).collect();
+        Self {
+            scope: Some(scopes.join(" ")),
+            ..self
+        }
+    }
+
+    /// Sets the `auth_time` claim.
+    #[must_use]
+    pub fn set_auth_time(self, auth_time: DateTime<Utc>) -> Self {
+        Self {
+            auth_time: Some(auth_time),
+            ..self
+        }
+    }
+
+    /// Iterates over scopes in the `scope` claim.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> + '_ {
+        self.scope
+            .as_deref()
+            .unwrap_or_default()
+            .split_ascii_whitespace()
+    }
+
+    /// Checks whether the `scope` claim contains the specified scope.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().any(|token_scope| token_scope == scope)
+    }
+}
+
+/// Identifier of an access token claim in [`AccessTokenClaims`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AccessTokenClaim {
+    /// `client_id` claim.
+    ClientId,
+}
+
+impl fmt::Display for AccessTokenClaim {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::ClientId => "client_id",
+        })
+    }
+}
+
+/// Errors that can occur during [access token validation](AccessTokenValidator).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AccessTokenError {
+    /// Token integrity, the token type or one of the registered claims (e.g., `iss`, `aud`
+    /// or `exp`) has failed validation.
+    Validation(ValidationError),
+    /// Claim required for validation is not present in the token.
+    NoClaim(AccessTokenClaim),
+    /// Token does not contain a required scope in the `scope` claim.
+    MissingScope(String),
+}
+
+impl From<ValidationError> for AccessTokenError {
+    fn from(err: ValidationError) -> Self {
+        Self::Validation(err)
+    }
+}
+
+impl fmt::Display for AccessTokenError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Validation(err) => fmt::Display::fmt(err, formatter),
+            Self::NoClaim(claim) => write!(
+                formatter,
+                "claim `{claim}` requested during validation is not present in the token"
+            ),
+            Self::MissingScope(scope) => {
+                write!(formatter, "token does not have required scope ({scope})")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AccessTokenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            // `Display` delegates to the error, so we need to do the same with `source()`.
+            Self::Validation(err) => err.source(),
+            _ => None,
+        }
+    }
+}
+
+/// Validator of access tokens performing checks from [RFC 9068][validation].
+///
+/// The following checks are performed in addition to checking the token integrity:
+///
+/// - The token type (the `typ` header field) is `at+jwt` (see [`is_access_token_type()`]).
+/// - The token issuer (`iss` claim) is equal to the expected issuer.
+/// - The token audience (`aud` claim) contains the resource server identifier.
+/// - The token is not expired (subject to the leeway and the clock from [`TimeOptions`]).
+/// - The token contains the `sub`, `client_id`, `iat` and `jti` claims.
+/// - The `scope` claim contains all [required scopes](Self::with_required_scope()).
+///
+/// Checks are performed in the specified order; the first failed check is reported
+/// as an [`AccessTokenError`].
+///
+/// See the [module-level docs](self) for an example of usage.
+///
+/// [validation]: https://www.rfc-editor.org/rfc/rfc9068.html#section-4
+#[derive(Debug, Clone)]
+pub struct AccessTokenValidator<F = fn() -> DateTime<Utc>> {
+    time_options: TimeOptions<F>,
+    issuer: String,
+    audience: String,
+    required_scopes: Vec<String>,
+}
+
+impl<F: Fn() -> DateTime<Utc>> AccessTokenValidator<F> {
+    /// Creates a validator for access tokens issued by the specified `issuer`
+    /// to the resource server with the specified `audience` identifier.
+    pub fn new(
+        issuer: impl Into<String>,
+        audience: impl Into<String>,
+        time_options: TimeOptions<F>,
+    ) -> Self {
+        Self {
+            time_options,
+            issuer: issuer.into(),
+            audience: audience.into(),
+            required_scopes: Vec::new(),
+        }
+    }
+
+    /// Adds a scope that must be present in the `scope` claim.
+    #[must_use]
+    pub fn with_required_scope(mut self, scope: impl Into<String>) -> Self {
+        self.required_scopes.push(scope.into());
+        self
+    }
+
+    /// Validates the token integrity using the provided `validator`, and then checks
+    /// the token header and claims as described in the [type-level docs](Self).
+    pub fn validate<A, T, H>(
+        &self,
+        validator: Validator<'_, A, AccessTokenClaims<T>>,
+        token: &UntrustedToken<'_, H>,
+    ) -> Result<Token<AccessTokenClaims<T>, H>, AccessTokenError>
+    where
+        A: Algorithm + ?Sized,
+        T: DeserializeOwned,
+        H: Clone,
+    {
+        let validated_token = validator.validate(token)?;
+        self.check(&validated_token)?;
+        Ok(validated_token)
+    }
+
+    /// Checks the header and claims of an already validated `token`.
+    pub fn check<T, H>(
+        &self,
+        token: &Token<AccessTokenClaims<T>, H>,
+    ) -> Result<(), AccessTokenError> {
+        check_token_type(token.header())?;
+
+        let claims = token.claims();
+        claims.validate_issuer(&self.issuer)?;
+        claims.validate_audience(&self.audience)?;
+        claims.validate_expiration(&self.time_options)?;
+
+        if claims.subject.is_none() {
+            return Err(ValidationError::NoClaim(Claim::Subject).into());
+        }
+        if claims.custom.client_id.is_none() {
+            return Err(AccessTokenError::NoClaim(AccessTokenClaim::ClientId));
+        }
+        if claims.issued_at.is_none() {
+            return Err(ValidationError::NoClaim(Claim::IssuedAt).into());
+        }
+        if claims.jwt_id.is_none() {
+            return Err(ValidationError::NoClaim(Claim::JwtId).into());
+        }
+
+        let missing_scope = self
+            .required_scopes
+            .iter()
+            .find(|&scope| !claims.custom.has_scope(scope));
+        if let Some(scope) = missing_scope {
+            return Err(AccessTokenError::MissingScope(scope.clone()));
+        }
+        Ok(())
+    }
+}
+
+fn check_token_type<H>(header: &Header<H>) -> Result<(), ValidationError> {
+    let token_type = header.token_type.as_deref();
+    if token_type.map_or(false, is_access_token_type) {
+        Ok(())
+    } else {
+        Err(ValidationError::UnexpectedTokenType {
+            expected: ACCESS_TOKEN_TYPE.to_owned(),
+            actual: token_type.map(ToOwned::to_owned),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "clock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key},
+        alloc::ToString,
+        AlgorithmExt, Claims,
+    };
+
+    use assert_matches::assert_matches;
+    use chrono::Duration;
+
+    const ISSUER: &str = "https://authorization-server.example.com/";
+    const AUDIENCE: &str = "https://rs.example.com/";
+
+    fn create_claims() -> Claims<AccessTokenClaims> {
+        let at_claims = AccessTokenClaims::empty()
+            .set_client_id("s6BhdRkqt3")
+            .set_scopes(["openid", "profile"]);
+        Claims::new(at_claims)
+            .set_issuer(ISSUER)
+            .set_subject("5ba552d67")
+            .set_audience(AUDIENCE)
+            .set_jwt_id("dbe39bf3a3ba4238a513f51d6e1691c4")
+            .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(10))
+    }
+
+    fn validate(
+        validator: &AccessTokenValidator,
+        token_type: Option<&str>,
+        claims: &Claims<AccessTokenClaims>,
+    ) -> Result<Token<AccessTokenClaims>, AccessTokenError> {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let mut header = Header::empty();
+        header.token_type = token_type.map(ToOwned::to_owned);
+        let token = Hs256.token(&header, claims, &key).unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+        validator.validate(Hs256.validator(&key), &token)
+    }
+
+    #[test]
+    fn checking_token_type() {
+        for token_type in [
+            "at+jwt",
+            "AT+JWT",
+            "application/at+jwt",
+            "Application/At+JWT",
+        ] {
+            assert!(is_access_token_type(token_type), "{token_type}");
+        }
+        for token_type in [
+            "JWT",
+            "at+jwt2",
+            "application/",
+            "application/jwt",
+            "app/at+jwt",
+        ] {
+            assert!(!is_access_token_type(token_type), "{token_type}");
+        }
+    }
+
+    #[test]
+    fn scope_helpers() {
+        let claims = AccessTokenClaims::empty().set_scopes(["openid", "profile"]);
+        assert_eq!(claims.scope.as_deref(), Some("openid profile"));
+        assert_eq!(claims.scopes().collect::<Vec<_>>(), ["openid", "profile"]);
+        assert!(claims.has_scope("profile"));
+        assert!(!claims.has_scope("email"));
+        assert!(!claims.has_scope("open"));
+
+        let claims = AccessTokenClaims::empty();
+        assert_eq!(claims.scopes().count(), 0);
+        let json = serde_json::to_value(&claims).unwrap();
+        assert_eq!(json, serde_json::json!({}));
+    }
+
+    #[test]
+    fn successful_validation() {
+        let validator = AccessTokenValidator::new(ISSUER, AUDIENCE, TimeOptions::default())
+            .with_required_scope("openid");
+        let claims = create_claims();
+        let token = validate(&validator, Some("application/at+JWT"), &claims).unwrap();
+        let at_claims = &token.claims().custom;
+        assert_eq!(at_claims.client_id.as_deref(), Some("s6BhdRkqt3"));
+        assert!(at_claims.has_scope("profile"));
+    }
+
+    #[test]
+    fn token_type_errors() {
+        let validator = AccessTokenValidator::new(ISSUER, AUDIENCE, TimeOptions::default());
+        let claims = create_claims();
+        let err = validate(&validator, Some("JWT"), &claims).unwrap_err();
+        assert_matches!(
+            err,
+            AccessTokenError::Validation(ValidationError::UnexpectedTokenType {
+                actual: Some(actual),
+                ..
+            }) if actual == "JWT"
+        );
+        let err = validate(&validator, None, &claims).unwrap_err();
+        assert_matches!(
+            err,
+            AccessTokenError::Validation(ValidationError::UnexpectedTokenType { actual: None, .. })
+        );
+    }
+
+    #[test]
+    fn claims_errors() {
+        let validator = AccessTokenValidator::new(ISSUER, AUDIENCE, TimeOptions::default());
+        let claims = create_claims().set_audience("https://other.example.com/");
+        let err = validate(&validator, Some(ACCESS_TOKEN_TYPE), &claims).unwrap_err();
+        assert_matches!(
+            err,
+            AccessTokenError::Validation(ValidationError::AudienceMismatch { .. })
+        );
+
+        let mut claims = create_claims();
+        claims.custom.client_id = None;
+        let err = validate(&validator, Some(ACCESS_TOKEN_TYPE), &claims).unwrap_err();
+        assert_matches!(err, AccessTokenError::NoClaim(AccessTokenClaim::ClientId));
+        assert_eq!(
+            err.to_string(),
+            "claim `client_id` requested during validation is not present in the token"
+        );
+
+        let mut claims = create_claims();
+        claims.jwt_id = None;
+        let err = validate(&validator, Some(ACCESS_TOKEN_TYPE), &claims).unwrap_err();
+        assert_matches!(
+            err,
+            AccessTokenError::Validation(ValidationError::NoClaim(Claim::JwtId))
+        );
+    }
+
+    #[test]
+    fn scope_errors() {
+        let validator = AccessTokenValidator::new(ISSUER, AUDIENCE, TimeOptions::default())
+            .with_required_scope("openid")
+            .with_required_scope("email");
+        let err = validate(&validator, Some(ACCESS_TOKEN_TYPE), &create_claims()).unwrap_err();
+        assert_matches!(&err, AccessTokenError::MissingScope(scope) if scope == "email");
+        assert_eq!(
+            err.to_string(),
+            "token does not have required scope (email)"
+        );
+    }
+}
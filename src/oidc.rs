@@ -0,0 +1,766 @@
+//! [OpenID Connect] ID tokens.
+//!
+//! [`IdTokenClaims`] contains claims specific to ID tokens (such as `nonce`, `auth_time`
+//! and `azp`), which are used together with the registered claims from [`Claims`].
+//! [`IdTokenValidator`] performs the ID token validation checks prescribed by
+//! [OpenID Connect Core §3.1.3.7][validation] in addition to checking the token integrity.
+//!
+//! [OpenID Connect]: https://openid.net/specs/openid-connect-core-1_0.html
+//! [validation]: https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
+//!
+//! # Examples
+//!
+//! ```
+//! # use chrono::Duration;
+//! use jwt_compact::{
+//!     alg::{Hs256, Hs256Key},
+//!     oidc::{self, IdTokenClaims, IdTokenValidator},
+//!     prelude::*,
+//! };
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let key = Hs256Key::new(b"client_secret_donut_steel");
+//! let time_options = TimeOptions::default();
+//! let access_token = "SlAV32hkKG";
+//!
+//! // Token creation is usually performed by the OpenID provider.
+//! let mut id_claims = IdTokenClaims::empty()
+//!     .set_nonce("n-0S6_WzA2Mj")
+//!     .set_auth_time((time_options.clock_fn)());
+//! id_claims.access_token_hash = oidc::token_hash("HS256", access_token);
+//! let claims = Claims::new(id_claims)
+//!     .set_issuer("https://server.example.com")
+//!     .set_subject("24400320")
+//!     .set_audience("s6BhdRkqt3")
+//!     .set_duration_and_issuance(&time_options, Duration::minutes(10));
+//! let token = Hs256.token(&Header::empty(), &claims, &key)?;
+//!
+//! // Validation is performed by the client (relying party).
+//! let validator =
+//!     IdTokenValidator::new("https://server.example.com", "s6BhdRkqt3", time_options)
+//!     .with_nonce("n-0S6_WzA2Mj")
+//!     .with_max_age(Duration::hours(1))
+//!     .with_access_token(access_token);
+//! let token = UntrustedToken::new(&token)?;
+//! let token = validator.validate(Hs256.validator::<IdTokenClaims>(&key), &token)?;
+//! assert_eq!(token.claims().subject.as_deref(), Some("24400320"));
+//! # Ok(())
+//! # }
+//! ```
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use core::fmt;
+
+use crate::{
+    alloc::{String, ToOwned, Vec},
+    Algorithm, Claim, Claims, Empty, TimeOptions, Token, UntrustedToken, ValidationError,
+    Validator,
+};
+
+/// Claims specific to [ID tokens], used as custom claims in [`Claims`].
+///
+/// [ID tokens]: https://openid.net/specs/openid-connect-core-1_0.html#IDToken
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct IdTokenClaims<T = Empty> {
+    /// Time when the end-user authentication occurred.
+    #[serde(
+        rename = "auth_time",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::claims::serde_timestamp"
+    )]
+    pub auth_time: Option<DateTime<Utc>>,
+
+    /// Value associating the client session with the ID token, used to mitigate replay attacks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+
+    /// Authentication context class reference.
+    #[serde(rename = "acr", default, skip_serializing_if = "Option::is_none")]
+    pub auth_context_class: Option<String>,
+
+    /// Authentication method references.
+    #[serde(rename = "amr", default, skip_serializing_if = "Option::is_none")]
+    pub auth_methods: Option<Vec<String>>,
+
+    /// Authorized party, i.e., the client ID of the party to which the ID token was issued.
+    #[serde(rename = "azp", default, skip_serializing_if = "Option::is_none")]
+    pub authorized_party: Option<String>,
+
+    /// Hash of the access token issued together with the ID token; see [`token_hash()`].
+    #[serde(rename = "at_hash", default, skip_serializing_if = "Option::is_none")]
+    pub access_token_hash: Option<String>,
+
+    /// Hash of the authorization code issued together with the ID token;
+    /// see [`token_hash()`].
+    #[serde(rename = "c_hash", default, skip_serializing_if = "Option::is_none")]
+    pub code_hash: Option<String>,
+
+    /// Other custom claims.
+    #[serde(flatten)]
+    pub custom: T,
+}
+
+impl IdTokenClaims<Empty> {
+    /// Creates an empty claims instance.
+    pub fn empty() -> Self {
+        Self::new(Empty {})
+    }
+}
+
+impl<T> IdTokenClaims<T> {
+    /// Creates a new instance with the provided custom claims.
+    pub fn new(custom_claims: T) -> Self {
+        Self {
+            auth_time: None,
+            nonce: None,
+            auth_context_class: None,
+            auth_methods: None,
+            authorized_party: None,
+            access_token_hash: None,
+            code_hash: None,
+            custom: custom_claims,
+        }
+    }
+
+    /// Sets the `auth_time` claim.
+    #[must_use]
+    pub fn set_auth_time(self, auth_time: DateTime<Utc>) -> Self {
+        Self {
+            auth_time: Some(auth_time),
+            ..self
+        }
+    }
+
+    /// Sets the `nonce` claim.
+    #[must_use]
+    pub fn set_nonce(self, nonce: impl Into<String>) -> Self {
+        Self {
+            nonce: Some(nonce.into()),
+            ..self
+        }
+    }
+
+    /// Sets the `azp` claim.
+    #[must_use]
+    pub fn set_authorized_party(self, authorized_party: impl Into<String>) -> Self {
+        Self {
+            authorized_party: Some(authorized_party.into()),
+            ..self
+        }
+    }
+}
+
+/// Computes the hash of an access token or an authorization code as used in the `at_hash`
+/// and `c_hash` claims: the left half of the digest of the `value` is base64url-encoded.
+/// The hash function is determined by the token `algorithm`. Returns `None` if the hash
+/// function for the algorithm is not known.
+///
+/// # Examples
+///
+/// ```
+/// # use jwt_compact::oidc::token_hash;
+/// let hash = token_hash("RS256", "jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y");
+/// assert_eq!(hash.as_deref(), Some("77QmUPtjPfzWtF2AnpK9RQ"));
+/// assert_eq!(token_hash("none", "jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y"), None);
+/// ```
+pub fn token_hash(algorithm: &str, value: &str) -> Option<String> {
+    match algorithm {
+        "HS256" | "RS256" | "PS256" | "ES256" | "ES256K" => Some(left_half_hash::<Sha256>(value)),
+        "HS384" | "RS384" | "PS384" | "ES384" => Some(left_half_hash::<Sha384>(value)),
+        // Ed25519 uses SHA-512 internally, which is also used for hashing.
+        "HS512" | "RS512" | "PS512" | "ES512" | "EdDSA" => Some(left_half_hash::<Sha512>(value)),
+        _ => None,
+    }
+}
+
+fn left_half_hash<D: Digest>(value: &str) -> String {
+    let digest = D::digest(value.as_bytes());
+    Base64UrlUnpadded::encode_string(&digest[..digest.len() / 2])
+}
+
+/// Identifier of an ID token claim in [`IdTokenClaims`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IdTokenClaim {
+    /// `auth_time` claim (authentication time).
+    AuthTime,
+    /// `nonce` claim.
+    Nonce,
+    /// `azp` claim (authorized party).
+    AuthorizedParty,
+    /// `at_hash` claim (access token hash).
+    AccessTokenHash,
+    /// `c_hash` claim (authorization code hash).
+    CodeHash,
+}
+
+impl fmt::Display for IdTokenClaim {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::AuthTime => "auth_time",
+            Self::Nonce => "nonce",
+            Self::AuthorizedParty => "azp",
+            Self::AccessTokenHash => "at_hash",
+            Self::CodeHash => "c_hash",
+        })
+    }
+}
+
+/// Errors that can occur during [ID token validation](IdTokenValidator).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IdTokenError {
+    /// Token integrity or one of the registered claims (e.g., `iss`, `aud` or `exp`)
+    /// has failed validation.
+    Validation(ValidationError),
+    /// Token audience (`aud` claim) contains a value that is neither the client ID
+    /// nor a trusted audience.
+    UntrustedAudience(String),
+    /// Claim required for validation is not present in the token.
+    NoClaim(IdTokenClaim),
+    /// Authorized party (`azp` claim) differs from the client ID.
+    AuthorizedPartyMismatch {
+        /// Expected authorized party (i.e., the client ID).
+        expected: String,
+        /// Actual authorized party in the token.
+        actual: String,
+    },
+    /// Token nonce (`nonce` claim) differs from the expected one.
+    NonceMismatch,
+    /// End-user authentication (`auth_time` claim) is older than the maximum authentication age.
+    AuthenticationTooOld,
+    /// Hash claim (`at_hash` or `c_hash`) does not match the hashed value.
+    HashMismatch(IdTokenClaim),
+    /// Hash claims cannot be checked since the hash function for the token algorithm
+    /// is not known.
+    UnsupportedAlgorithm(String),
+}
+
+impl From<ValidationError> for IdTokenError {
+    fn from(err: ValidationError) -> Self {
+        Self::Validation(err)
+    }
+}
+
+impl fmt::Display for IdTokenError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Validation(err) => fmt::Display::fmt(err, formatter),
+            Self::UntrustedAudience(audience) => write!(
+                formatter,
+                "token audience contains untrusted value ({audience})"
+            ),
+            Self::NoClaim(claim) => write!(
+                formatter,
+                "claim `{claim}` requested during validation is not present in the token"
+            ),
+            Self::AuthorizedPartyMismatch { expected, actual } => write!(
+                formatter,
+                "token authorized party ({actual}) differs from the client ID ({expected})"
+            ),
+            Self::NonceMismatch => formatter.write_str("token nonce differs from expected"),
+            Self::AuthenticationTooOld => formatter.write_str("end-user authentication is too old"),
+            Self::HashMismatch(claim) => {
+                write!(formatter, "claim `{claim}` does not match the hashed value")
+            }
+            Self::UnsupportedAlgorithm(alg) => write!(
+                formatter,
+                "cannot compute hash claims for token algorithm ({alg})"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IdTokenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            // `Display` delegates to the error, so we need to do the same with `source()`.
+            Self::Validation(err) => err.source(),
+            _ => None,
+        }
+    }
+}
+
+/// Validator of ID tokens performing checks from [OpenID Connect Core §3.1.3.7][validation].
+///
+/// The following checks are performed in addition to checking the token integrity:
+///
+/// - The token issuer (`iss` claim) is equal to the expected issuer.
+/// - The token audience (`aud` claim) contains the client ID, and all other audiences
+///   are [trusted](Self::with_trusted_audience()).
+/// - If the token has multiple audiences, the authorized party (`azp` claim) is present.
+///   If the authorized party is present, it is equal to the client ID.
+/// - The token contains the `sub`, `exp` and `iat` claims, and is not expired
+///   (subject to the leeway and the clock from [`TimeOptions`]).
+/// - If the [nonce](Self::with_nonce()) was sent in the authentication request,
+///   the `nonce` claim is present and is equal to it.
+/// - If the [maximum authentication age](Self::with_max_age()) was requested,
+///   the `auth_time` claim is present and the authentication is not older than the max age.
+/// - If the [access token](Self::with_access_token()) or the
+///   [authorization code](Self::with_authorization_code()) was issued together with
+///   the ID token, the `at_hash` / `c_hash` claims, if present, match them. The claims
+///   can be [required](Self::require_access_token_hash()) to be present (e.g., `c_hash`
+///   is required for ID tokens issued from the authorization endpoint in the hybrid flow).
+///
+/// Checks are performed in the specified order; the first failed check is reported
+/// as an [`IdTokenError`].
+///
+/// See the [module-level docs](self) for an example of usage.
+///
+/// [validation]: https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
+#[derive(Debug, Clone)]
+pub struct IdTokenValidator<F = fn() -> DateTime<Utc>> {
+    time_options: TimeOptions<F>,
+    issuer: String,
+    client_id: String,
+    trusted_audiences: Vec<String>,
+    nonce: Option<String>,
+    max_age: Option<Duration>,
+    access_token: Option<String>,
+    authorization_code: Option<String>,
+    requires_access_token_hash: bool,
+    requires_code_hash: bool,
+}
+
+impl<F: Fn() -> DateTime<Utc>> IdTokenValidator<F> {
+    /// Creates a validator for ID tokens issued by the specified `issuer` to the client
+    /// with the specified ID.
+    pub fn new(
+        issuer: impl Into<String>,
+        client_id: impl Into<String>,
+        time_options: TimeOptions<F>,
+    ) -> Self {
+        Self {
+            time_options,
+            issuer: issuer.into(),
+            client_id: client_id.into(),
+            trusted_audiences: Vec::new(),
+            nonce: None,
+            max_age: None,
+            access_token: None,
+            authorization_code: None,
+            requires_access_token_hash: false,
+            requires_code_hash: false,
+        }
+    }
+
+    /// Adds a trusted audience, which may be present in the `aud` claim
+    /// in addition to the client ID.
+    #[must_use]
+    pub fn with_trusted_audience(mut self, audience: impl Into<String>) -> Self {
+        self.trusted_audiences.push(audience.into());
+        self
+    }
+
+    /// Sets the nonce sent in the authentication request. If set, the token must contain
+    /// the `nonce` claim equal to it.
+    #[must_use]
+    pub fn with_nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Sets the maximum authentication age sent in the authentication request (the `max_age`
+    /// parameter). If set, the token must contain the `auth_time` claim, and the authentication
+    /// must not be older than `max_age` (subject to the leeway).
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the access token issued together with the ID token. If the token contains
+    /// the `at_hash` claim, it must match the access token.
+    #[must_use]
+    pub fn with_access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+
+    /// Sets the authorization code issued together with the ID token. If the token contains
+    /// the `c_hash` claim, it must match the code.
+    #[must_use]
+    pub fn with_authorization_code(mut self, code: impl Into<String>) -> Self {
+        self.authorization_code = Some(code.into());
+        self
+    }
+
+    /// Requires the token to contain the `at_hash` claim. If the access token
+    /// is [set](Self::with_access_token()), the claim must also match it.
+    #[must_use]
+    pub fn require_access_token_hash(mut self) -> Self {
+        self.requires_access_token_hash = true;
+        self
+    }
+
+    /// Requires the token to contain the `c_hash` claim. If the authorization code
+    /// is [set](Self::with_authorization_code()), the claim must also match it.
+    #[must_use]
+    pub fn require_code_hash(mut self) -> Self {
+        self.requires_code_hash = true;
+        self
+    }
+
+    /// Validates the token integrity using the provided `validator`, and then checks
+    /// the token claims as described in the [type-level docs](Self).
+    pub fn validate<A, T, H>(
+        &self,
+        validator: Validator<'_, A, IdTokenClaims<T>>,
+        token: &UntrustedToken<'_, H>,
+    ) -> Result<Token<IdTokenClaims<T>, H>, IdTokenError>
+    where
+        A: Algorithm + ?Sized,
+        T: DeserializeOwned,
+        H: Clone,
+    {
+        let validated_token = validator.validate(token)?;
+        self.check(token.algorithm(), validated_token.claims())?;
+        Ok(validated_token)
+    }
+
+    fn check<T>(
+        &self,
+        algorithm: &str,
+        claims: &Claims<IdTokenClaims<T>>,
+    ) -> Result<(), IdTokenError> {
+        claims.validate_issuer(&self.issuer)?;
+        claims.validate_audience(&self.client_id)?;
+        self.check_audience_and_party(claims)?;
+
+        if claims.subject.is_none() {
+            return Err(ValidationError::NoClaim(Claim::Subject).into());
+        }
+        claims.validate_expiration(&self.time_options)?;
+        if claims.issued_at.is_none() {
+            return Err(ValidationError::NoClaim(Claim::IssuedAt).into());
+        }
+
+        let id_claims = &claims.custom;
+        if let Some(expected) = &self.nonce {
+            let nonce = id_claims
+                .nonce
+                .as_ref()
+                .ok_or(IdTokenError::NoClaim(IdTokenClaim::Nonce))?;
+            if nonce != expected {
+                return Err(IdTokenError::NonceMismatch);
+            }
+        }
+        if let Some(max_age) = self.max_age {
+            self.check_auth_time(id_claims, max_age)?;
+        }
+
+        check_hash(
+            algorithm,
+            IdTokenClaim::AccessTokenHash,
+            id_claims.access_token_hash.as_deref(),
+            self.access_token.as_deref(),
+            self.requires_access_token_hash,
+        )?;
+        check_hash(
+            algorithm,
+            IdTokenClaim::CodeHash,
+            id_claims.code_hash.as_deref(),
+            self.authorization_code.as_deref(),
+            self.requires_code_hash,
+        )
+    }
+
+    fn check_audience_and_party<T>(
+        &self,
+        claims: &Claims<IdTokenClaims<T>>,
+    ) -> Result<(), IdTokenError> {
+        let Some(audience) = &claims.audience else {
+            return Err(ValidationError::NoClaim(Claim::Audience).into());
+        };
+        let untrusted_audience = audience.iter().find(|&aud| {
+            aud != self.client_id && !self.trusted_audiences.iter().any(|trusted| trusted == aud)
+        });
+        if let Some(untrusted_audience) = untrusted_audience {
+            return Err(IdTokenError::UntrustedAudience(
+                untrusted_audience.to_owned(),
+            ));
+        }
+
+        match &claims.custom.authorized_party {
+            Some(party) if *party != self.client_id => Err(IdTokenError::AuthorizedPartyMismatch {
+                expected: self.client_id.clone(),
+                actual: party.clone(),
+            }),
+            None if audience.iter().count() > 1 => {
+                Err(IdTokenError::NoClaim(IdTokenClaim::AuthorizedParty))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_auth_time<T>(
+        &self,
+        claims: &IdTokenClaims<T>,
+        max_age: Duration,
+    ) -> Result<(), IdTokenError> {
+        let auth_time = claims
+            .auth_time
+            .ok_or(IdTokenError::NoClaim(IdTokenClaim::AuthTime))?;
+        let age = (self.time_options.clock_fn)().signed_duration_since(auth_time);
+        let is_too_old = max_age
+            .checked_add(&self.time_options.leeway)
+            .map_or(false, |max_age_with_leeway| age > max_age_with_leeway);
+        if is_too_old {
+            Err(IdTokenError::AuthenticationTooOld)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn check_hash(
+    algorithm: &str,
+    claim: IdTokenClaim,
+    actual: Option<&str>,
+    hashed_value: Option<&str>,
+    is_required: bool,
+) -> Result<(), IdTokenError> {
+    let Some(actual) = actual else {
+        return if is_required {
+            Err(IdTokenError::NoClaim(claim))
+        } else {
+            Ok(())
+        };
+    };
+    let Some(hashed_value) = hashed_value else {
+        return Ok(());
+    };
+    let expected = token_hash(algorithm, hashed_value)
+        .ok_or_else(|| IdTokenError::UnsupportedAlgorithm(algorithm.to_owned()))?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(IdTokenError::HashMismatch(claim))
+    }
+}
+
+#[cfg(all(test, feature = "clock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key},
+        alloc::ToString,
+        AlgorithmExt, Header,
+    };
+
+    use assert_matches::assert_matches;
+
+    const ISSUER: &str = "https://server.example.com";
+    const CLIENT_ID: &str = "s6BhdRkqt3";
+
+    fn create_claims() -> Claims<IdTokenClaims> {
+        let id_claims = IdTokenClaims::empty()
+            .set_nonce("n-0S6_WzA2Mj")
+            .set_auth_time(Utc::now() - Duration::minutes(5));
+        Claims::new(id_claims)
+            .set_issuer(ISSUER)
+            .set_subject("24400320")
+            .set_audience(CLIENT_ID)
+            .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(10))
+    }
+
+    fn validate(
+        validator: &IdTokenValidator,
+        claims: &Claims<IdTokenClaims>,
+    ) -> Result<Token<IdTokenClaims>, IdTokenError> {
+        let key = Hs256Key::new(b"client_secret_donut_steel");
+        let token = Hs256.token(&Header::empty(), claims, &key).unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+        validator.validate(Hs256.validator(&key), &token)
+    }
+
+    #[test]
+    fn computing_token_hashes() {
+        // Examples from OpenID Connect Core, Appendix A.
+        let hash = token_hash("RS256", "jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y").unwrap();
+        assert_eq!(hash, "77QmUPtjPfzWtF2AnpK9RQ");
+        let hash = token_hash(
+            "RS256",
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk",
+        )
+        .unwrap();
+        assert_eq!(hash, "LDktKdoQak3Pk0cnXxCltA");
+
+        assert_eq!(token_hash("HS384", "code").unwrap().len(), 32);
+        assert_eq!(token_hash("EdDSA", "code").unwrap().len(), 43);
+    }
+
+    #[test]
+    fn claims_serialization() {
+        let claims = create_claims();
+        let json = serde_json::to_value(&claims).unwrap();
+        assert_eq!(json["nonce"], "n-0S6_WzA2Mj");
+        assert!(json["auth_time"].is_i64());
+        assert!(json.get("azp").is_none());
+
+        let restored: Claims<IdTokenClaims> = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.custom.nonce.as_deref(), Some("n-0S6_WzA2Mj"));
+        assert_eq!(
+            restored.custom.auth_time.map(|time| time.timestamp()),
+            claims.custom.auth_time.map(|time| time.timestamp())
+        );
+    }
+
+    #[test]
+    fn successful_validation() {
+        let mut claims = create_claims().set_audience(vec![
+            CLIENT_ID.to_owned(),
+            "https://api.example.com".to_owned(),
+        ]);
+        claims.custom.authorized_party = Some(CLIENT_ID.to_owned());
+        claims.custom.access_token_hash = token_hash("HS256", "access_token");
+        claims.custom.code_hash = token_hash("HS256", "code");
+
+        let validator = IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default())
+            .with_trusted_audience("https://api.example.com")
+            .with_nonce("n-0S6_WzA2Mj")
+            .with_max_age(Duration::minutes(10))
+            .with_access_token("access_token")
+            .with_authorization_code("code");
+        let token = validate(&validator, &claims).unwrap();
+        assert_eq!(token.claims().subject.as_deref(), Some("24400320"));
+    }
+
+    #[test]
+    fn registered_claims_errors() {
+        let validator = IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default());
+        let claims = create_claims().set_issuer("https://evil.example.com");
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(
+            err,
+            IdTokenError::Validation(ValidationError::IssuerMismatch { .. })
+        );
+
+        let claims = create_claims().set_audience("other");
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(
+            err,
+            IdTokenError::Validation(ValidationError::AudienceMismatch { .. })
+        );
+
+        let mut claims = create_claims();
+        claims.issued_at = None;
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(
+            err,
+            IdTokenError::Validation(ValidationError::NoClaim(Claim::IssuedAt))
+        );
+    }
+
+    #[test]
+    fn audience_and_authorized_party_errors() {
+        let validator = IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default());
+        let claims = create_claims().set_audience(vec![CLIENT_ID.to_owned(), "other".to_owned()]);
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(&err, IdTokenError::UntrustedAudience(aud) if aud == "other");
+        assert_eq!(
+            err.to_string(),
+            "token audience contains untrusted value (other)"
+        );
+
+        let validator = validator.with_trusted_audience("other");
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(err, IdTokenError::NoClaim(IdTokenClaim::AuthorizedParty));
+
+        let mut claims = create_claims();
+        claims.custom.authorized_party = Some("other".to_owned());
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(
+            err,
+            IdTokenError::AuthorizedPartyMismatch { actual, .. } if actual == "other"
+        );
+    }
+
+    #[test]
+    fn nonce_and_auth_time_errors() {
+        let validator = IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default())
+            .with_nonce("other-nonce");
+        let err = validate(&validator, &create_claims()).unwrap_err();
+        assert_matches!(err, IdTokenError::NonceMismatch);
+
+        let mut claims = create_claims();
+        claims.custom.nonce = None;
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(err, IdTokenError::NoClaim(IdTokenClaim::Nonce));
+        assert_eq!(
+            err.to_string(),
+            "claim `nonce` requested during validation is not present in the token"
+        );
+
+        let validator = IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default())
+            .with_max_age(Duration::minutes(2));
+        let err = validate(&validator, &create_claims()).unwrap_err();
+        assert_matches!(err, IdTokenError::AuthenticationTooOld);
+
+        let mut claims = create_claims();
+        claims.custom.auth_time = None;
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(err, IdTokenError::NoClaim(IdTokenClaim::AuthTime));
+    }
+
+    #[test]
+    fn hash_errors() {
+        let mut claims = create_claims();
+        claims.custom.access_token_hash = token_hash("HS256", "access_token");
+        claims.custom.code_hash = token_hash("HS256", "code");
+
+        let validator = IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default())
+            .with_access_token("other_token");
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(
+            err,
+            IdTokenError::HashMismatch(IdTokenClaim::AccessTokenHash)
+        );
+
+        let validator = IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default())
+            .with_authorization_code("other_code");
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(err, IdTokenError::HashMismatch(IdTokenClaim::CodeHash));
+
+        // Absent hash claims are not checked.
+        claims.custom.code_hash = None;
+        validate(&validator, &claims).unwrap();
+    }
+
+    #[test]
+    fn required_hash_claims() {
+        let mut claims = create_claims();
+        claims.custom.access_token_hash = token_hash("HS256", "access_token");
+
+        let validator = IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default())
+            .with_access_token("access_token")
+            .require_access_token_hash();
+        validate(&validator, &claims).unwrap();
+        let validator = validator
+            .with_authorization_code("code")
+            .require_code_hash();
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(err, IdTokenError::NoClaim(IdTokenClaim::CodeHash));
+        assert_eq!(
+            err.to_string(),
+            "claim `c_hash` requested during validation is not present in the token"
+        );
+
+        claims.custom.access_token_hash = None;
+        claims.custom.code_hash = token_hash("HS256", "code");
+        let err = validate(&validator, &claims).unwrap_err();
+        assert_matches!(err, IdTokenError::NoClaim(IdTokenClaim::AccessTokenHash));
+
+        // If the hashed value is not set, only the claim presence is checked.
+        let validator =
+            IdTokenValidator::new(ISSUER, CLIENT_ID, TimeOptions::default()).require_code_hash();
+        validate(&validator, &claims).unwrap();
+    }
+}
@@ -0,0 +1,486 @@
+//! Declarative validation policies.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    alloc::{format, String, ToOwned, Vec},
+    Algorithm, Claim, Claims, PolicyError, TimeOptions, Token, UntrustedToken, ValidationError,
+    Validator,
+};
+
+/// Reusable set of requirements for tokens, checked in a single call together with
+/// the token integrity.
+///
+/// A policy bundles the following checks:
+///
+/// - The token algorithm is one of the [accepted algorithms](Self::with_algorithm())
+///   (by default, any algorithm supported by the validator is accepted).
+/// - The token header has the [expected type](Self::with_token_type()) (the `typ` field).
+/// - The token contains all [required claims](Self::with_required_claim()). By default,
+///   the expiration claim (`exp`) is required.
+/// - The token is not expired, and is mature (the `exp` and `nbf` claims are checked
+///   if present, using the leeway and the clock from [`TimeOptions`]).
+/// - The token issuer (`iss` claim) is one of the [expected issuers](Self::with_issuer()),
+///   and the token audience (`aud` claim) contains one of the
+///   [expected audiences](Self::with_audience()).
+/// - The token is not [older](Self::with_max_age()) than the specified duration,
+///   and is not issued in the future (checked via the `iat` claim).
+///
+/// If the token integrity cannot be verified, only this error is reported. Otherwise,
+/// all failed checks are reported in the returned [`PolicyError`].
+///
+/// # Examples
+///
+/// ```
+/// # use chrono::Duration;
+/// use jwt_compact::{alg::{Hs256, Hs256Key}, prelude::*, Empty, ValidationPolicy};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let policy = ValidationPolicy::default()
+///     .with_issuer("https://auth.example.com/")
+///     .with_audience("my-service")
+///     .with_token_type("JWT")
+///     .with_max_age(Duration::hours(1));
+///
+/// let key = Hs256Key::new(b"super_secret_key_donut_steel");
+/// let claims = Claims::empty()
+///     .set_issuer("https://auth.example.com/")
+///     .set_audience("my-service")
+///     .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(10));
+/// let header = Header::empty().with_token_type("JWT");
+/// let token = Hs256.token(&header, &claims, &key)?;
+///
+/// let token = UntrustedToken::new(&token)?;
+/// let token = policy.validate(Hs256.validator::<Empty>(&key), &token)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ValidationPolicy<F = fn() -> DateTime<Utc>> {
+    time_options: TimeOptions<F>,
+    algorithms: Vec<String>,
+    token_type: Option<String>,
+    required_claims: Vec<Claim>,
+    issuers: Vec<String>,
+    audiences: Vec<String>,
+    max_age: Option<Duration>,
+}
+
+/// Creates a policy with the [default `TimeOptions`](TimeOptions#impl-Default-for-TimeOptions).
+///
+/// This impl is supported on **crate feature `clock`** only.
+#[cfg(feature = "clock")]
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        Self::new(TimeOptions::default())
+    }
+}
+
+impl<F: Fn() -> DateTime<Utc>> ValidationPolicy<F> {
+    /// Creates a policy with the specified time options. The policy only requires
+    /// the expiration claim (`exp`) to be present.
+    pub fn new(time_options: TimeOptions<F>) -> Self {
+        Self {
+            time_options,
+            algorithms: Vec::new(),
+            token_type: None,
+            required_claims: Vec::from([Claim::Expiration]),
+            issuers: Vec::new(),
+            audiences: Vec::new(),
+            max_age: None,
+        }
+    }
+
+    /// Sets the leeway used for time-related checks.
+    #[must_use]
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.time_options.leeway = leeway;
+        self
+    }
+
+    /// Adds an accepted algorithm. If no algorithms are added, the algorithm is checked
+    /// by the validator only.
+    #[must_use]
+    pub fn with_algorithm(mut self, algorithm: impl Into<String>) -> Self {
+        self.algorithms.push(algorithm.into());
+        self
+    }
+
+    /// Requires the `typ` field in the token header to be equal to the specified value.
+    /// As per [RFC 7515], values are compared case-insensitively.
+    ///
+    /// [RFC 7515]: https://www.rfc-editor.org/rfc/rfc7515#section-4.1.9
+    #[must_use]
+    pub fn with_token_type(mut self, token_type: impl Into<String>) -> Self {
+        self.token_type = Some(token_type.into());
+        self
+    }
+
+    /// Requires the specified claim to be present in the token.
+    #[must_use]
+    pub fn with_required_claim(mut self, claim: Claim) -> Self {
+        if !self.required_claims.contains(&claim) {
+            self.required_claims.push(claim);
+        }
+        self
+    }
+
+    /// Removes all required claims, including the default requirement for the `exp` claim.
+    #[must_use]
+    pub fn without_required_claims(mut self) -> Self {
+        self.required_claims.clear();
+        self
+    }
+
+    /// Adds an expected issuer. If at least one issuer is added, the token must contain
+    /// the `iss` claim equal to one of the added issuers.
+    #[must_use]
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuers.push(issuer.into());
+        self
+    }
+
+    /// Adds an expected audience. If at least one audience is added, the token must contain
+    /// the `aud` claim including one of the added audiences.
+    #[must_use]
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audiences.push(audience.into());
+        self
+    }
+
+    /// Sets the maximum age of the token. If set, the token must contain the `iat` claim,
+    /// the token must not be older than `max_age`, and must not be issued in the future
+    /// (both subject to the leeway).
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Validates the token integrity using the provided `validator`, and then checks
+    /// the token header and claims against this policy.
+    pub fn validate<A, T, H>(
+        &self,
+        validator: Validator<'_, A, T>,
+        token: &UntrustedToken<'_, H>,
+    ) -> Result<Token<T, H>, PolicyError>
+    where
+        A: Algorithm + ?Sized,
+        T: DeserializeOwned,
+        H: Clone,
+    {
+        let token_algorithm = token.algorithm();
+        if !self.algorithms.is_empty() && !self.algorithms.iter().any(|alg| alg == token_algorithm)
+        {
+            let err = ValidationError::AlgorithmNotAllowed(token_algorithm.to_owned());
+            return Err(err.into());
+        }
+
+        let token = validator.validate(token)?;
+        self.check(&token)?;
+        Ok(token)
+    }
+
+    /// Checks the header and claims of an already validated `token` against this policy.
+    /// Unlike [`Self::validate()`], the accepted algorithms are not checked.
+    pub fn check<T, H>(&self, token: &Token<T, H>) -> Result<(), PolicyError> {
+        let mut errors = Vec::new();
+
+        if let Some(expected) = &self.token_type {
+            let actual = token.header().token_type.as_deref();
+            if !actual.map_or(false, |actual| actual.eq_ignore_ascii_case(expected)) {
+                errors.push(ValidationError::UnexpectedTokenType {
+                    expected: expected.clone(),
+                    actual: actual.map(ToOwned::to_owned),
+                });
+            }
+        }
+
+        let claims = token.claims();
+        for claim in &self.required_claims {
+            if !has_claim(claims, claim) {
+                errors.push(ValidationError::NoClaim(claim.clone()));
+            }
+        }
+        // Checks below report missing claims themselves; skip them if the missing claim
+        // is already reported as required, so that it is not reported twice.
+        let is_reported_missing =
+            |claim: &Claim| self.required_claims.contains(claim) && !has_claim(claims, claim);
+
+        let options = &self.time_options;
+        if claims.expiration.is_some() {
+            if let Err(err) = claims.validate_expiration(options) {
+                errors.push(err);
+            }
+        }
+        if claims.not_before.is_some() {
+            if let Err(err) = claims.validate_maturity(options) {
+                errors.push(err);
+            }
+        }
+        if let Some(max_age) = self.max_age {
+            if !is_reported_missing(&Claim::IssuedAt) {
+                if let Err(err) = self.check_age(claims, max_age) {
+                    errors.push(err);
+                }
+            }
+        }
+
+        if !is_reported_missing(&Claim::Issuer) {
+            if let Err(err) = self.check_issuer(claims) {
+                errors.push(err);
+            }
+        }
+        if !is_reported_missing(&Claim::Audience) {
+            if let Err(err) = self.check_audience(claims) {
+                errors.push(err);
+            }
+        }
+
+        PolicyError::from_errors(errors)
+    }
+
+    fn check_age<T>(&self, claims: &Claims<T>, max_age: Duration) -> Result<(), ValidationError> {
+        let issued_at = claims
+            .issued_at
+            .ok_or(ValidationError::NoClaim(Claim::IssuedAt))?;
+        let age = (self.time_options.clock_fn)().signed_duration_since(issued_at);
+        if -age > self.time_options.leeway {
+            return Err(ValidationError::IssuedInFuture);
+        }
+        let is_too_old = max_age
+            .checked_add(&self.time_options.leeway)
+            .map_or(false, |max_age_with_leeway| age > max_age_with_leeway);
+        if is_too_old {
+            Err(ValidationError::TooOld)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_issuer<T>(&self, claims: &Claims<T>) -> Result<(), ValidationError> {
+        if let [issuer] = self.issuers.as_slice() {
+            return claims.validate_issuer(issuer).map(drop);
+        } else if self.issuers.is_empty() {
+            return Ok(());
+        }
+
+        let issuer = claims
+            .issuer
+            .as_deref()
+            .ok_or(ValidationError::NoClaim(Claim::Issuer))?;
+        if self.issuers.iter().any(|expected| expected == issuer) {
+            Ok(())
+        } else {
+            Err(ValidationError::IssuerMismatch {
+                expected: format!("one of: {}", self.issuers.join(", ")),
+                actual: issuer.to_owned(),
+            })
+        }
+    }
+
+    fn check_audience<T>(&self, claims: &Claims<T>) -> Result<(), ValidationError> {
+        if let [audience] = self.audiences.as_slice() {
+            return claims.validate_audience(audience).map(drop);
+        } else if self.audiences.is_empty() {
+            return Ok(());
+        }
+
+        let audience = claims
+            .audience
+            .as_ref()
+            .ok_or(ValidationError::NoClaim(Claim::Audience))?;
+        if self
+            .audiences
+            .iter()
+            .any(|expected| audience.contains(expected))
+        {
+            Ok(())
+        } else {
+            Err(ValidationError::AudienceMismatch {
+                expected: format!("one of: {}", self.audiences.join(", ")),
+            })
+        }
+    }
+}
+
+fn has_claim<T>(claims: &Claims<T>, claim: &Claim) -> bool {
+    match claim {
+        Claim::Expiration => claims.expiration.is_some(),
+        Claim::NotBefore => claims.not_before.is_some(),
+        Claim::IssuedAt => claims.issued_at.is_some(),
+        Claim::Issuer => claims.issuer.is_some(),
+        Claim::Subject => claims.subject.is_some(),
+        Claim::Audience => claims.audience.is_some(),
+        Claim::JwtId => claims.jwt_id.is_some(),
+    }
+}
+
+#[cfg(all(test, feature = "clock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key},
+        AlgorithmExt, Empty, Header,
+    };
+
+    use assert_matches::assert_matches;
+
+    fn create_token(header: &Header, claims: &Claims<Empty>) -> (String, Hs256Key) {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let token = Hs256.token(header, claims, &key).unwrap();
+        (token, key)
+    }
+
+    #[test]
+    fn successful_validation() {
+        let policy = ValidationPolicy::default()
+            .with_algorithm("HS256")
+            .with_token_type("jwt")
+            .with_issuer("issuer")
+            .with_audience("first")
+            .with_audience("second")
+            .with_required_claim(Claim::Subject)
+            .with_max_age(Duration::minutes(5));
+
+        let claims = Claims::empty()
+            .set_issuer("issuer")
+            .set_subject("alice")
+            .set_audience("second")
+            .set_duration_and_issuance(&TimeOptions::default(), Duration::minutes(10));
+        let header = Header::empty().with_token_type("JWT");
+        let (token, key) = create_token(&header, &claims);
+        let token = UntrustedToken::new(&token).unwrap();
+        let token = policy
+            .validate(Hs256.validator::<Empty>(&key), &token)
+            .unwrap();
+        assert_eq!(token.claims().subject.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn all_failures_are_reported() {
+        let now = Utc::now();
+        let policy = ValidationPolicy::default()
+            .with_token_type("JWT")
+            .with_issuer("issuer")
+            .with_audience("audience")
+            .with_required_claim(Claim::JwtId)
+            .with_max_age(Duration::minutes(5));
+
+        let mut claims = Claims::empty().set_issuer("other").set_audience("other");
+        claims.issued_at = Some(now - Duration::hours(1));
+        claims.not_before = Some(now + Duration::hours(1));
+        let (token, key) = create_token(&Header::empty(), &claims);
+        let token = UntrustedToken::new(&token).unwrap();
+        let err = policy
+            .validate(Hs256.validator::<Empty>(&key), &token)
+            .unwrap_err();
+
+        let errors = err.errors();
+        assert_eq!(errors.len(), 7, "{errors:?}");
+        assert_matches!(
+            &errors[0],
+            ValidationError::UnexpectedTokenType { actual: None, .. }
+        );
+        assert_matches!(&errors[1], ValidationError::NoClaim(Claim::Expiration));
+        assert_matches!(&errors[2], ValidationError::NoClaim(Claim::JwtId));
+        assert_matches!(&errors[3], ValidationError::NotMature);
+        assert_matches!(&errors[4], ValidationError::TooOld);
+        assert_matches!(
+            &errors[5],
+            ValidationError::IssuerMismatch { actual, .. } if actual == "other"
+        );
+        assert_matches!(&errors[6], ValidationError::AudienceMismatch { .. });
+    }
+
+    #[test]
+    fn integrity_errors_are_reported_alone() {
+        let policy = ValidationPolicy::default().with_issuer("issuer");
+        let (token, _) = create_token(&Header::empty(), &Claims::empty());
+        let token = UntrustedToken::new(&token).unwrap();
+        let other_key = Hs256Key::new(b"other_key");
+        let err = policy
+            .validate(Hs256.validator::<Empty>(&other_key), &token)
+            .unwrap_err();
+        assert_matches!(err.errors(), [ValidationError::InvalidSignature]);
+
+        let policy = policy.with_algorithm("HS384").with_algorithm("HS512");
+        let err = policy
+            .validate(Hs256.validator::<Empty>(&other_key), &token)
+            .unwrap_err();
+        assert_matches!(
+            err.errors(),
+            [ValidationError::AlgorithmNotAllowed(alg)] if alg == "HS256"
+        );
+    }
+
+    #[test]
+    fn multiple_issuers() {
+        let policy = ValidationPolicy::default()
+            .without_required_claims()
+            .with_issuer("first")
+            .with_issuer("second");
+        let (token, key) = create_token(&Header::empty(), &Claims::empty().set_issuer("second"));
+        let token = UntrustedToken::new(&token).unwrap();
+        policy
+            .validate(Hs256.validator::<Empty>(&key), &token)
+            .unwrap();
+
+        let (token, key) = create_token(&Header::empty(), &Claims::empty());
+        let token = UntrustedToken::new(&token).unwrap();
+        let err = policy
+            .validate(Hs256.validator::<Empty>(&key), &token)
+            .unwrap_err();
+        assert_matches!(err.errors(), [ValidationError::NoClaim(Claim::Issuer)]);
+    }
+
+    #[test]
+    fn token_issued_in_future() {
+        let policy = ValidationPolicy::default()
+            .without_required_claims()
+            .with_max_age(Duration::minutes(5));
+        let mut claims = Claims::empty();
+        claims.issued_at = Some(Utc::now() + Duration::hours(1));
+        let (token, key) = create_token(&Header::empty(), &claims);
+        let token = UntrustedToken::new(&token).unwrap();
+        let err = policy
+            .validate(Hs256.validator::<Empty>(&key), &token)
+            .unwrap_err();
+        assert_matches!(err.errors(), [ValidationError::IssuedInFuture]);
+
+        // A small difference is covered by the leeway.
+        claims.issued_at = Some(Utc::now() + Duration::seconds(10));
+        let (token, key) = create_token(&Header::empty(), &claims);
+        let token = UntrustedToken::new(&token).unwrap();
+        policy
+            .validate(Hs256.validator::<Empty>(&key), &token)
+            .unwrap();
+    }
+
+    #[test]
+    fn missing_required_claims_are_reported_once() {
+        let policy = ValidationPolicy::default()
+            .without_required_claims()
+            .with_required_claim(Claim::Issuer)
+            .with_required_claim(Claim::Audience)
+            .with_required_claim(Claim::IssuedAt)
+            .with_issuer("issuer")
+            .with_audience("first")
+            .with_audience("second")
+            .with_max_age(Duration::minutes(5));
+        let (token, key) = create_token(&Header::empty(), &Claims::empty());
+        let token = UntrustedToken::new(&token).unwrap();
+        let err = policy
+            .validate(Hs256.validator::<Empty>(&key), &token)
+            .unwrap_err();
+        assert_matches!(
+            err.errors(),
+            [
+                ValidationError::NoClaim(Claim::Issuer),
+                ValidationError::NoClaim(Claim::Audience),
+                ValidationError::NoClaim(Claim::IssuedAt),
+            ]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Resolution of verifying keys for untrusted tokens.
+
+use crate::{
+    alloc::String,
+    jwk::{JsonWebKey, JsonWebKeySet, JwkError, KeyQuery, KeyUse},
+    Algorithm, Empty, Thumbprint, UntrustedToken, ValidationError,
+};
+
+/// Information about an untrusted token used to find the verifying key for it.
+///
+/// All information is taken from the token **before** its integrity is verified,
+/// and thus can be forged. It should only be used to select among keys trusted
+/// by the application.
+#[derive(Debug, Clone)]
+pub struct KeyRequest<'a> {
+    algorithm: &'a str,
+    key_id: Option<&'a str>,
+    key_set_url: Option<&'a str>,
+    certificate_thumbprint: Option<&'a Thumbprint<32>>,
+    issuer: Option<String>,
+}
+
+impl<'a> KeyRequest<'a> {
+    /// Creates a request for the specified token. The issuer is extracted from token claims
+    /// without verification; if the claims cannot be deserialized, the issuer is set to `None`.
+    pub fn new<H>(token: &'a UntrustedToken<'_, H>) -> Self {
+        let header = token.header();
+        let issuer = token
+            .deserialize_claims_unchecked::<Empty>()
+            .ok()
+            .and_then(|claims| claims.issuer);
+        Self {
+            algorithm: token.algorithm(),
+            key_id: header.key_id.as_deref(),
+            key_set_url: header.key_set_url.as_deref(),
+            certificate_thumbprint: header.certificate_thumbprint.as_ref(),
+            issuer,
+        }
+    }
+
+    /// Returns the token algorithm (the `alg` field in the token header).
+    pub fn algorithm(&self) -> &'a str {
+        self.algorithm
+    }
+
+    /// Returns the key ID (the `kid` field in the token header).
+    pub fn key_id(&self) -> Option<&'a str> {
+        self.key_id
+    }
+
+    /// Returns the URL of the key set (the `jku` field in the token header).
+    pub fn key_set_url(&self) -> Option<&'a str> {
+        self.key_set_url
+    }
+
+    /// Returns the SHA-256 thumbprint of the X.509 certificate for the key
+    /// (the `x5t#S256` field in the token header).
+    pub fn certificate_thumbprint(&self) -> Option<&'a Thumbprint<32>> {
+        self.certificate_thumbprint
+    }
+
+    /// Returns the unverified token issuer (the `iss` claim).
+    pub fn issuer(&self) -> Option<&str> {
+        self.issuer.as_deref()
+    }
+}
+
+/// Source of verifying keys for tokens, e.g., keys of multiple issuers.
+///
+/// A resolver is used by [`AlgorithmExt::validate_with_resolver()`] to select the key
+/// for a token based on the [information](KeyRequest) from its header and claims.
+/// The crate provides the following implementations:
+///
+/// - [`JsonWebKeySet`] finds a key by the key ID, algorithm and the certificate thumbprint.
+/// - Closures with the `Fn(&KeyRequest<'_>) -> Result<A::VerifyingKey, ValidationError>`
+///   signature allow implementing arbitrary lookup logic.
+///
+/// [`AlgorithmExt::validate_with_resolver()`]: crate::AlgorithmExt::validate_with_resolver()
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use jwt_compact::{prelude::*, alg::{Hs256, Hs256Key}};
+/// # use jwt_compact::{Empty, KeyRequest, ValidationError};
+/// # fn main() -> anyhow::Result<()> {
+/// // Keys of each tenant.
+/// let mut tenant_keys = HashMap::new();
+/// tenant_keys.insert("https://a.example.com", Hs256Key::new(b"tenant_a_secret"));
+/// tenant_keys.insert("https://b.example.com", Hs256Key::new(b"tenant_b_secret"));
+///
+/// let resolver = |request: &KeyRequest<'_>| {
+///     let issuer = request.issuer().ok_or(ValidationError::KeyNotFound)?;
+///     let key = tenant_keys.get(issuer).ok_or(ValidationError::KeyNotFound)?;
+///     Ok::<_, ValidationError>(key.clone())
+/// };
+///
+/// let claims = Claims::empty().set_issuer("https://b.example.com");
+/// let key = &tenant_keys["https://b.example.com"];
+/// let token = Hs256.token(&Header::empty(), &claims, key)?;
+/// let token = UntrustedToken::new(&token)?;
+/// let token: Token<Empty> = Hs256.validate_with_resolver(&token, &resolver)?;
+/// assert_eq!(token.claims().issuer.as_deref(), Some("https://b.example.com"));
+/// # Ok(())
+/// # }
+/// ```
+pub trait KeyResolver<A: Algorithm + ?Sized> {
+    /// Resolves the verifying key for a token.
+    ///
+    /// # Errors
+    ///
+    /// Should return [`ValidationError::KeyNotFound`] if there is no key for the token,
+    /// and [`ValidationError::KeyResolution`] for other errors (e.g., if the key
+    /// cannot be loaded).
+    fn resolve_key(
+        &self,
+        algorithm: &A,
+        request: &KeyRequest<'_>,
+    ) -> Result<A::VerifyingKey, ValidationError>;
+}
+
+impl<A, F> KeyResolver<A> for F
+where
+    A: Algorithm + ?Sized,
+    F: Fn(&KeyRequest<'_>) -> Result<A::VerifyingKey, ValidationError>,
+{
+    fn resolve_key(
+        &self,
+        _algorithm: &A,
+        request: &KeyRequest<'_>,
+    ) -> Result<A::VerifyingKey, ValidationError> {
+        self(request)
+    }
+}
+
+/// Finds a key intended for signatures with the token algorithm, using the token key ID
+/// (if present) as per [`KeyQuery`]. If the token header contains the SHA-256 certificate
+/// thumbprint (`x5t#S256`) and the key specifies this thumbprint as well, the thumbprints
+/// must match. Keys that cannot be converted to a verifying key for the algorithm
+/// are skipped.
+impl<A, T> KeyResolver<A> for JsonWebKeySet<'_, T>
+where
+    A: Algorithm + ?Sized,
+    A::VerifyingKey: for<'jwk> TryFrom<&'jwk JsonWebKey<'jwk>, Error = JwkError>,
+{
+    fn resolve_key(
+        &self,
+        algorithm: &A,
+        request: &KeyRequest<'_>,
+    ) -> Result<A::VerifyingKey, ValidationError> {
+        let mut query = KeyQuery::new()
+            .with_algorithm(request.algorithm())
+            .with_key_use(&KeyUse::Signature);
+        if let Some(key_id) = request.key_id() {
+            query = query.with_key_id(key_id);
+        }
+
+        let thumbprint = request.certificate_thumbprint();
+        let key = self
+            .filter(&query)
+            .filter(|key| match (thumbprint, &key.certificate_thumbprint) {
+                (Some(expected), Some(actual)) => expected == actual,
+                _ => true,
+            })
+            .find_map(|key| key.verifying_key(algorithm).ok());
+        key.ok_or(ValidationError::KeyNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alg::{Hs256, Hs256Key, Hs384, Hs384Key},
+        alloc::ToString,
+        jwk::ExtendedJsonWebKey,
+        AlgorithmExt, Claims, Header, Token,
+    };
+
+    use assert_matches::assert_matches;
+
+    fn key_set(keys: &[Hs256Key; 2]) -> JsonWebKeySet<'_> {
+        let first_key = ExtendedJsonWebKey::new(JsonWebKey::from(&keys[0]))
+            .with_key_id("1")
+            .with_certificate_thumbprint([1; 32]);
+        let second_key = ExtendedJsonWebKey::new(JsonWebKey::from(&keys[1]))
+            .with_key_id("2")
+            .with_algorithm("HS256");
+        JsonWebKeySet::new()
+            .with_key(first_key)
+            .with_key(second_key)
+    }
+
+    #[test]
+    fn resolving_keys_from_key_set() {
+        let keys = [Hs256Key::new(b"first"), Hs256Key::new(b"second")];
+        let key_set = key_set(&keys);
+        let claims = Claims::empty().set_issuer("https://example.com");
+
+        let header = Header::empty().with_key_id("2");
+        let token = Hs256.token(&header, &claims, &keys[1]).unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+        let request = KeyRequest::new(&token);
+        assert_eq!(request.algorithm(), "HS256");
+        assert_eq!(request.key_id(), Some("2"));
+        assert_eq!(request.issuer(), Some("https://example.com"));
+        let token: Token<Empty> = Hs256.validate_with_resolver(&token, &key_set).unwrap();
+        assert_eq!(token.header().key_id.as_deref(), Some("2"));
+
+        // Without the key ID, the first matching key is selected.
+        let token = Hs256.token(&Header::empty(), &claims, &keys[0]).unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+        Hs256
+            .validate_with_resolver::<Empty, _, _>(&token, &key_set)
+            .unwrap();
+
+        let header = Header::empty()
+            .with_key_id("1")
+            .with_certificate_thumbprint([2; 32]);
+        let token = Hs256.token(&header, &claims, &keys[0]).unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+        let err = Hs256
+            .validate_with_resolver::<Empty, _, _>(&token, &key_set)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::KeyNotFound);
+        assert_eq!(err.to_string(), "no verifying key found for token");
+    }
+
+    #[test]
+    fn key_algorithm_is_checked() {
+        let keys = [Hs256Key::new(b"first"), Hs256Key::new(b"second")];
+        let key_set = key_set(&keys);
+        let header = Header::empty().with_key_id("2");
+        let key = Hs384Key::new(b"second");
+        let token = Hs384.token(&header, &Claims::empty(), &key).unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+        let err = Hs384
+            .validate_with_resolver::<Empty, _, _>(&token, &key_set)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::KeyNotFound);
+    }
+
+    #[test]
+    fn resolving_keys_with_closure() {
+        let key = Hs256Key::new(b"secret");
+        let token = Hs256
+            .token(&Header::empty(), &Claims::empty(), &key)
+            .unwrap();
+        let token = UntrustedToken::new(&token).unwrap();
+
+        let resolver = |request: &KeyRequest<'_>| match request.issuer() {
+            Some(_) => Ok(key.clone()),
+            None => Err(ValidationError::KeyResolution(anyhow::anyhow!("no issuer"))),
+        };
+        let err = Hs256
+            .validate_with_resolver::<Empty, _, _>(&token, &resolver)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::KeyResolution(_));
+        assert_eq!(err.to_string(), "cannot resolve verifying key: no issuer");
+    }
+}
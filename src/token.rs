@@ -5,17 +5,32 @@ use serde::{
     de::{DeserializeOwned, Error as DeError, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
 use smallvec::{smallvec, SmallVec};
+use subtle::ConstantTimeEq;
 
 use core::{cmp, fmt};
 
+#[cfg(feature = "pem")]
+use crate::alg::{encoding::decode_certificate_pem, KeyEncodingError};
 use crate::{
-    alloc::{format, Cow, String, Vec},
-    Algorithm, Claims, Empty, ParseError, ValidationError,
+    alloc::{format, Cow, String, ToOwned, Vec},
+    traits::encode_base64_buf,
+    Algorithm, Claims, CreationError, Empty, ParseError, ValidationError,
 };
 
 /// Maximum "reasonable" signature size in bytes.
-const SIGNATURE_SIZE: usize = 128;
+pub(crate) const SIGNATURE_SIZE: usize = 128;
+
+/// Header parameters registered by RFC 7515, which must not be listed in the `crit` header
+/// parameter.
+const REGISTERED_HEADER_PARAMS: &[&str] = &[
+    "alg", "jku", "jwk", "kid", "x5u", "x5c", "x5t", "x5t#S256", "typ", "cty", "crit",
+];
+
+/// Critical header extensions understood by the crate itself.
+pub(crate) const SUPPORTED_CRITICAL_EXTENSIONS: &[&str] = &["b64"];
 
 /// Representation of a X.509 certificate thumbprint (`x5t` and `x5t#S256` fields in
 /// the JWT [`Header`]).
@@ -68,6 +83,59 @@ pub enum Thumbprint<const N: usize> {
     String(String),
 }
 
+impl<const N: usize> Thumbprint<N> {
+    /// Compares this thumbprint to the `expected` digest in constant time. String thumbprints
+    /// never match.
+    fn matches(&self, expected: &[u8; N]) -> bool {
+        match self {
+            Self::Bytes(bytes) => bytes.ct_eq(expected).into(),
+            Self::String(_) => false,
+        }
+    }
+}
+
+impl Thumbprint<20> {
+    /// Computes the SHA-1 thumbprint of a DER-encoded X.509 certificate, as used
+    /// in the `x5t` header field.
+    pub fn from_certificate_der(certificate: &[u8]) -> Self {
+        Self::Bytes(Sha1::digest(certificate).into())
+    }
+
+    /// Computes the SHA-1 thumbprint of a PEM-encoded X.509 certificate, as used
+    /// in the `x5t` header field.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not valid PEM, or if its label is not `CERTIFICATE`.
+    #[cfg(feature = "pem")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pem")))]
+    pub fn from_certificate_pem(certificate: &str) -> Result<Self, KeyEncodingError> {
+        let certificate = decode_certificate_pem(certificate)?;
+        Ok(Self::from_certificate_der(&certificate))
+    }
+}
+
+impl Thumbprint<32> {
+    /// Computes the SHA-256 thumbprint of a DER-encoded X.509 certificate, as used
+    /// in the `x5t#S256` header field.
+    pub fn from_certificate_der(certificate: &[u8]) -> Self {
+        Self::Bytes(Sha256::digest(certificate).into())
+    }
+
+    /// Computes the SHA-256 thumbprint of a PEM-encoded X.509 certificate, as used
+    /// in the `x5t#S256` header field.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not valid PEM, or if its label is not `CERTIFICATE`.
+    #[cfg(feature = "pem")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pem")))]
+    pub fn from_certificate_pem(certificate: &str) -> Result<Self, KeyEncodingError> {
+        let certificate = decode_certificate_pem(certificate)?;
+        Ok(Self::from_certificate_der(&certificate))
+    }
+}
+
 impl<const N: usize> From<[u8; N]> for Thumbprint<N> {
     fn from(value: [u8; N]) -> Self {
         Self::Bytes(value)
@@ -163,14 +231,12 @@ impl<'de, const N: usize> Deserialize<'de> for Thumbprint<N> {
 ///
 /// ```
 /// # use jwt_compact::Header;
-/// use sha2::{digest::Digest, Sha256};
-///
 /// let my_key_cert = // DER-encoded key certificate
 /// #   b"Hello, world!";
-/// let thumbprint: [u8; 32] = Sha256::digest(my_key_cert).into();
 /// let header = Header::empty()
 ///     .with_key_id("my-key-id")
-///     .with_certificate_thumbprint(thumbprint);
+///     .with_certificate_thumbprints(my_key_cert);
+/// assert!(header.validate_certificate_thumbprints(my_key_cert).is_ok());
 /// ```
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -196,6 +262,23 @@ pub struct Header<T = Empty> {
     #[serde(rename = "x5u", default, skip_serializing_if = "Option::is_none")]
     pub certificate_url: Option<String>,
 
+    /// X.509 certificate chain for the signing key, with each certificate in the DER format.
+    /// The certificate containing the signing key must be the first one. This field is renamed
+    /// to [`x5c`] for serialization; certificates are serialized using the standard
+    /// (not URL-safe) base64 encoding with padding, as per the spec.
+    ///
+    /// If the `x509` crate feature is enabled, the chain can be validated and the signing key
+    /// extracted from it using `x509::CertificateVerifier`.
+    ///
+    /// [`x5c`]: https://www.rfc-editor.org/rfc/rfc7515.html#section-4.1.6
+    #[serde(
+        rename = "x5c",
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "crate::jwk::certificate_chain"
+    )]
+    pub certificate_chain: Vec<Vec<u8>>,
+
     /// SHA-1 thumbprint of the X.509 certificate for the signing key.
     /// This field is renamed to [`x5t`] for serialization.
     ///
@@ -224,9 +307,11 @@ pub struct Header<T = Empty> {
     /// For the token creation and validation to work properly, the fields type must [`Serialize`]
     /// to a JSON object.
     ///
-    /// Note that these fields do not include the signing algorithm (`alg`) and the token
-    /// content type (`cty`) since both these fields have predefined semantics and are used
-    /// internally by the crate logic.
+    /// Note that these fields do not include the signing algorithm (`alg`), the token
+    /// content type (`cty`), the payload encoding flag (`b64`) and the list of critical
+    /// extensions (`crit`) since these fields have predefined semantics and are used
+    /// internally by the crate logic. Critical extensions of a parsed token can be accessed
+    /// via [`UntrustedToken::critical_extensions()`].
     #[serde(flatten)]
     pub other_fields: T,
 }
@@ -238,6 +323,7 @@ impl Header {
             key_set_url: None,
             key_id: None,
             certificate_url: None,
+            certificate_chain: Vec::new(),
             certificate_sha1_thumbprint: None,
             certificate_thumbprint: None,
             token_type: None,
@@ -253,6 +339,7 @@ impl<T> Header<T> {
             key_set_url: None,
             key_id: None,
             certificate_url: None,
+            certificate_chain: Vec::new(),
             certificate_sha1_thumbprint: None,
             certificate_thumbprint: None,
             token_type: None,
@@ -281,6 +368,16 @@ impl<T> Header<T> {
         self
     }
 
+    /// Sets the `certificate_chain` field for this header.
+    #[must_use]
+    pub fn with_certificate_chain(
+        mut self,
+        certificates: impl IntoIterator<Item = impl Into<Vec<u8>>>,
+    ) -> Self {
+        self.certificate_chain = certificates.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// Sets the `certificate_sha1_thumbprint` field for this header.
     #[must_use]
     pub fn with_certificate_sha1_thumbprint(
@@ -301,12 +398,51 @@ impl<T> Header<T> {
         self
     }
 
+    /// Sets the `certificate_sha1_thumbprint` and `certificate_thumbprint` fields
+    /// for this header by computing the thumbprints of the DER-encoded X.509 `certificate`.
+    #[must_use]
+    pub fn with_certificate_thumbprints(self, certificate: &[u8]) -> Self {
+        self.with_certificate_sha1_thumbprint(Thumbprint::<20>::from_certificate_der(certificate))
+            .with_certificate_thumbprint(Thumbprint::<32>::from_certificate_der(certificate))
+    }
+
     /// Sets the `token_type` field for this header.
     #[must_use]
     pub fn with_token_type(mut self, token_type: impl Into<String>) -> Self {
         self.token_type = Some(token_type.into());
         self
     }
+
+    /// Checks that the certificate thumbprints in this header (`certificate_sha1_thumbprint`
+    /// and `certificate_thumbprint`) correspond to the DER-encoded X.509 `certificate`,
+    /// e.g., the certificate that the verifying key was obtained from. Thumbprints are compared
+    /// in constant time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header contains no thumbprints, or if any of the thumbprints
+    /// does not match the certificate. Thumbprints in the [string form](Thumbprint::String)
+    /// never match.
+    pub fn validate_certificate_thumbprints(
+        &self,
+        certificate: &[u8],
+    ) -> Result<(), ValidationError> {
+        if self.certificate_sha1_thumbprint.is_none() && self.certificate_thumbprint.is_none() {
+            return Err(ValidationError::NoCertificateThumbprint);
+        }
+
+        if let Some(thumbprint) = &self.certificate_sha1_thumbprint {
+            if !thumbprint.matches(&Sha1::digest(certificate).into()) {
+                return Err(ValidationError::CertificateThumbprintMismatch);
+            }
+        }
+        if let Some(thumbprint) = &self.certificate_thumbprint {
+            if !thumbprint.matches(&Sha256::digest(certificate).into()) {
+                return Err(ValidationError::CertificateThumbprintMismatch);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -315,15 +451,137 @@ pub(crate) struct CompleteHeader<'a, T> {
     pub algorithm: Cow<'a, str>,
     #[serde(rename = "cty", default, skip_serializing_if = "Option::is_none")]
     pub content_type: Option<String>,
+    /// Whether the payload is base64url-encoded as per [RFC 7797].
+    ///
+    /// [RFC 7797]: https://www.rfc-editor.org/rfc/rfc7797
+    #[serde(rename = "b64", default, skip_serializing_if = "Option::is_none")]
+    pub encoded_payload: Option<bool>,
+    #[serde(rename = "crit", default, skip_serializing_if = "Option::is_none")]
+    pub critical: Option<Vec<String>>,
+    /// Key ID associated with the signing key if it is not specified in the `inner` header.
+    /// Deserialized key IDs are always placed into the `inner` header.
+    #[serde(
+        rename = "kid",
+        skip_deserializing,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub key_id: Option<Cow<'a, str>>,
     #[serde(flatten)]
     pub inner: T,
 }
 
+impl<'a, T> CompleteHeader<'a, &'a Header<T>> {
+    /// Creates a header for a token signed with the specified `algorithm` and `signing_key`.
+    /// The key ID associated with the signing key (if any) is added to the header, or checked
+    /// against the key ID in the `header`.
+    pub fn for_signing<A: Algorithm + ?Sized>(
+        algorithm: &A,
+        header: &'a Header<T>,
+        signing_key: &'a A::SigningKey,
+    ) -> Result<Self, CreationError> {
+        algorithm.check_signing_key(signing_key)?;
+        let mut this = Self::new(algorithm.name(), header);
+        if let Some(expected_key_id) = algorithm.key_id(signing_key) {
+            match &header.key_id {
+                Some(key_id) if key_id != expected_key_id => {
+                    return Err(CreationError::KeyIdMismatch {
+                        expected: expected_key_id.to_owned(),
+                        actual: key_id.clone(),
+                    });
+                }
+                Some(_) => { /* The key ID is already in the header */ }
+                None => {
+                    this.key_id = Some(Cow::Borrowed(expected_key_id));
+                }
+            }
+        }
+        Ok(this)
+    }
+}
+
+impl<'a, T> CompleteHeader<'a, T> {
+    pub fn new(algorithm: Cow<'a, str>, inner: T) -> Self {
+        Self {
+            algorithm,
+            content_type: None,
+            encoded_payload: None,
+            critical: None,
+            key_id: None,
+            inner,
+        }
+    }
+
+    /// Checks whether the token payload is base64url-encoded (which is the default).
+    pub fn is_payload_encoded(&self) -> Result<bool, ParseError> {
+        let Some(encoded_payload) = self.encoded_payload else {
+            return Ok(true);
+        };
+        // RFC 7797 requires `b64` to be listed as a critical header parameter.
+        let is_critical = self.critical.as_ref().map_or(false, |critical| {
+            critical.iter().any(|param| param == "b64")
+        });
+        if is_critical {
+            Ok(encoded_payload)
+        } else {
+            let err = serde_json::Error::custom("`b64` header parameter must be listed in `crit`");
+            Err(ParseError::MalformedHeader(err))
+        }
+    }
+}
+
+/// Checks the `critical` header parameter against the rules in RFC 7515, section 4.1.11.
+/// Recognition of the listed extensions is checked during validation.
+pub(crate) fn check_critical_params(
+    critical: &[String],
+    raw_header: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), ParseError> {
+    let message = if critical.is_empty() {
+        "`crit` header parameter must not be empty".into()
+    } else if let Some(param) = critical
+        .iter()
+        .find(|&param| REGISTERED_HEADER_PARAMS.contains(&param.as_str()))
+    {
+        format!("registered header parameter `{param}` must not be listed in `crit`")
+    } else if let Some(param) = critical
+        .iter()
+        .find(|&param| !raw_header.contains_key(param))
+    {
+        format!("critical header parameter `{param}` is missing")
+    } else if let Some((_, param)) = critical
+        .iter()
+        .enumerate()
+        .find(|&(i, param)| critical[..i].contains(param))
+    {
+        format!("critical header parameter `{param}` is listed multiple times")
+    } else {
+        return Ok(());
+    };
+    Err(ParseError::MalformedHeader(DeError::custom(message)))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ContentType {
     Json,
-    #[cfg(feature = "serde_cbor")]
+    #[cfg(feature = "cbor")]
     Cbor,
+    /// Nested token.
+    Jwt,
+    /// Claims of a CBOR Web Token, which use integer keys for registered claims.
+    #[cfg(feature = "cwt")]
+    Cwt,
+}
+
+impl ContentType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            #[cfg(feature = "cbor")]
+            Self::Cbor => "CBOR",
+            Self::Jwt => "JWT",
+            #[cfg(feature = "cwt")]
+            Self::Cwt => "CWT",
+        }
+    }
 }
 
 /// Parsed, but unvalidated token.
@@ -381,6 +639,8 @@ pub struct UntrustedToken<'a, H = Empty> {
     content_type: ContentType,
     serialized_claims: Vec<u8>,
     signature: SmallVec<[u8; SIGNATURE_SIZE]>,
+    critical_extensions: Vec<String>,
+    pub(crate) unprotected_header: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
 /// Token with validated integrity.
@@ -486,45 +746,236 @@ where
     }
 }
 
+/// Token with validated integrity, which contains another token (i.e., has the `cty` field
+/// in its header set to `JWT`) as per [RFC 7519, section 5.2].
+///
+/// A nested token is produced by [`Validator::validate_nested()`] or (if the `jwe` crate
+/// feature is enabled) by `JweAlgorithm::decrypt_nested()`. The inner token is not validated;
+/// it should be parsed and validated as the next step.
+///
+/// [RFC 7519, section 5.2]: https://www.rfc-editor.org/rfc/rfc7519#section-5.2
+/// [`Validator::validate_nested()`]: crate::Validator::validate_nested()
+///
+/// # Examples
+///
+/// ```
+/// # use jwt_compact::{alg::{Hs256, Hs256Key, Hs384, Hs384Key}, prelude::*, Empty};
+/// # fn main() -> anyhow::Result<()> {
+/// let inner_key = Hs256Key::new(b"super_secret_key_donut_steel");
+/// let outer_key = Hs384Key::new(b"another_super_secret_key");
+/// let claims = Claims::empty().set_subject("alice");
+/// let inner_token = Hs256.token(&Header::empty(), &claims, &inner_key)?;
+/// let token_string = Hs384.nested_token(&Header::empty(), &inner_token, &outer_key)?;
+///
+/// // Validate the outer token...
+/// let token = UntrustedToken::new(&token_string)?;
+/// let nested = Hs384.validator::<Empty>(&outer_key).validate_nested(&token)?;
+/// // ...and then the inner one.
+/// let inner_token: UntrustedToken = nested.inner_token()?;
+/// let inner_token = Hs256.validator::<Empty>(&inner_key).validate(&inner_token)?;
+/// assert_eq!(inner_token.claims().subject.as_deref(), Some("alice"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NestedToken<H = Empty> {
+    header: Header<H>,
+    inner: Vec<u8>,
+}
+
+impl<H> NestedToken<H> {
+    pub(crate) fn new(header: Header<H>, inner: Vec<u8>) -> Self {
+        Self { header, inner }
+    }
+
+    /// Gets the header of the outer token.
+    pub fn header(&self) -> &Header<H> {
+        &self.header
+    }
+
+    /// Parses the inner token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the inner token is not a well-formed signed token.
+    pub fn inner_token<H2: DeserializeOwned>(&self) -> Result<UntrustedToken<'_, H2>, ParseError> {
+        let inner =
+            core::str::from_utf8(&self.inner).map_err(|_| ParseError::InvalidTokenStructure)?;
+        UntrustedToken::try_from(inner)
+    }
+
+    /// Parses the inner token as an encrypted token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the inner token is not a well-formed encrypted token.
+    #[cfg(feature = "jwe")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "jwe")))]
+    pub fn inner_encrypted_token<H2: DeserializeOwned>(
+        &self,
+    ) -> Result<crate::jwe::EncryptedToken<'_, H2>, ParseError> {
+        let inner =
+            core::str::from_utf8(&self.inner).map_err(|_| ParseError::InvalidTokenStructure)?;
+        crate::jwe::EncryptedToken::try_from(inner)
+    }
+}
+
 impl<'a, H: DeserializeOwned> TryFrom<&'a str> for UntrustedToken<'a, H> {
     type Error = ParseError;
 
     fn try_from(s: &'a str) -> Result<Self, Self::Error> {
-        let token_parts: Vec<_> = s.splitn(4, '.').collect();
-        match &token_parts[..] {
-            [header, claims, signature] => {
-                let header = Base64UrlUnpadded::decode_vec(header)
-                    .map_err(|_| ParseError::InvalidBase64Encoding)?;
-                let serialized_claims = Base64UrlUnpadded::decode_vec(claims)
-                    .map_err(|_| ParseError::InvalidBase64Encoding)?;
-
-                let mut decoded_signature = smallvec![0; 3 * (signature.len() + 3) / 4];
-                let signature_len =
-                    Base64UrlUnpadded::decode(signature, &mut decoded_signature[..])
-                        .map_err(|_| ParseError::InvalidBase64Encoding)?
-                        .len();
-                decoded_signature.truncate(signature_len);
-
-                let header: CompleteHeader<_> =
-                    serde_json::from_slice(&header).map_err(ParseError::MalformedHeader)?;
-                let content_type = match header.content_type {
-                    None => ContentType::Json,
-                    Some(ref s) if s.eq_ignore_ascii_case("json") => ContentType::Json,
-                    #[cfg(feature = "serde_cbor")]
-                    Some(ref s) if s.eq_ignore_ascii_case("cbor") => ContentType::Cbor,
-                    Some(s) => return Err(ParseError::UnsupportedContentType(s)),
-                };
-                let signed_data = s.rsplit_once('.').unwrap().0.as_bytes();
-                Ok(Self {
-                    signed_data: Cow::Borrowed(signed_data),
-                    header: header.inner,
-                    algorithm: header.algorithm.into_owned(),
-                    content_type,
-                    serialized_claims,
-                    signature: decoded_signature,
-                })
-            }
-            _ => Err(ParseError::InvalidTokenStructure),
+        let (header, payload, signature) = split_token(s)?;
+        let header = decode_header(header)?;
+        let serialized_claims = if header.is_payload_encoded()? {
+            Base64UrlUnpadded::decode_vec(payload).map_err(|_| ParseError::InvalidBase64Encoding)?
+        } else {
+            payload.as_bytes().to_vec()
+        };
+        let signature = decode_signature(signature)?;
+
+        let signed_data = s.rsplit_once('.').unwrap().0.as_bytes();
+        Self::from_parts(
+            Cow::Borrowed(signed_data),
+            header,
+            serialized_claims,
+            signature,
+        )
+    }
+}
+
+/// Splits a token in the compact serialization into the header, payload and signature.
+fn split_token(s: &str) -> Result<(&str, &str, &str), ParseError> {
+    let token_parts: Vec<_> = s.splitn(4, '.').collect();
+    match token_parts[..] {
+        [header, payload, signature] => Ok((header, payload, signature)),
+        _ => Err(ParseError::InvalidTokenStructure),
+    }
+}
+
+fn decode_header<H: DeserializeOwned>(
+    header: &str,
+) -> Result<CompleteHeader<'static, Header<H>>, ParseError> {
+    let raw_header =
+        Base64UrlUnpadded::decode_vec(header).map_err(|_| ParseError::InvalidBase64Encoding)?;
+    let header: CompleteHeader<'static, Header<H>> =
+        serde_json::from_slice(&raw_header).map_err(ParseError::MalformedHeader)?;
+    if let Some(critical) = &header.critical {
+        // Only tokens with critical header params need to parse the header for the second time.
+        let raw_header =
+            serde_json::from_slice(&raw_header).map_err(ParseError::MalformedHeader)?;
+        check_critical_params(critical, &raw_header)?;
+    }
+    Ok(header)
+}
+
+fn decode_signature(signature: &str) -> Result<SmallVec<[u8; SIGNATURE_SIZE]>, ParseError> {
+    let mut decoded_signature = smallvec![0; 3 * (signature.len() + 3) / 4];
+    let signature_len = Base64UrlUnpadded::decode(signature, &mut decoded_signature[..])
+        .map_err(|_| ParseError::InvalidBase64Encoding)?
+        .len();
+    decoded_signature.truncate(signature_len);
+    Ok(decoded_signature)
+}
+
+impl<H: DeserializeOwned> UntrustedToken<'static, H> {
+    /// Parses a token with a [detached payload], i.e., one with the empty payload part
+    /// (`header..signature`). The payload is supplied out-of-band; depending on the `b64` token
+    /// header parameter, it is either signed [as is][RFC 7797] (as produced by
+    /// [`AlgorithmExt::detached_token()`]), or base64url-encoded before signing.
+    ///
+    /// The returned token can be validated in the same way as tokens with attached payloads;
+    /// the payload is deserialized as token claims.
+    ///
+    /// [detached payload]: https://www.rfc-editor.org/rfc/rfc7515#appendix-F
+    /// [RFC 7797]: https://www.rfc-editor.org/rfc/rfc7797
+    /// [`AlgorithmExt::detached_token()`]: crate::AlgorithmExt::detached_token()
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use jwt_compact::{alg::{Hs256, Hs256Key}, prelude::*, Empty};
+    /// # fn main() -> anyhow::Result<()> {
+    /// let key = Hs256Key::new(b"super_secret_key_donut_steel");
+    /// let body = br#"{"event":"push","sub":"repo"}"#;
+    /// let token_string = Hs256.detached_token(&Header::empty(), body, &key)?;
+    ///
+    /// let token: UntrustedToken = UntrustedToken::with_detached_payload(&token_string, body)?;
+    /// let token = Hs256.validator::<serde_json::Value>(&key)
+    ///     .validate_for_signed_token(&token)?
+    ///     .token;
+    /// assert_eq!(token.claims().subject.as_deref(), Some("repo"));
+    /// assert_eq!(token.claims().custom["event"], "push");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_detached_payload(s: &str, payload: &[u8]) -> Result<Self, ParseError> {
+        let (encoded_header, encoded_payload, signature) = split_token(s)?;
+        if !encoded_payload.is_empty() {
+            return Err(ParseError::InvalidTokenStructure);
+        }
+        let header = decode_header(encoded_header)?;
+        let signature = decode_signature(signature)?;
+
+        let mut signed_data = Vec::with_capacity(encoded_header.len() + 1 + payload.len());
+        signed_data.extend_from_slice(encoded_header.as_bytes());
+        signed_data.push(b'.');
+        if header.is_payload_encoded()? {
+            encode_base64_buf(payload, &mut signed_data);
+        } else {
+            signed_data.extend_from_slice(payload);
+        }
+        Self::from_parts(Cow::Owned(signed_data), header, payload.to_vec(), signature)
+    }
+}
+
+impl<'a, H> UntrustedToken<'a, H> {
+    /// Assembles a token from the parsed parts.
+    pub(crate) fn from_parts(
+        signed_data: Cow<'a, [u8]>,
+        header: CompleteHeader<'_, Header<H>>,
+        serialized_claims: Vec<u8>,
+        signature: SmallVec<[u8; SIGNATURE_SIZE]>,
+    ) -> Result<Self, ParseError> {
+        let content_type = match header.content_type {
+            None => ContentType::Json,
+            Some(ref s) if s.eq_ignore_ascii_case("json") => ContentType::Json,
+            #[cfg(feature = "cbor")]
+            Some(ref s) if s.eq_ignore_ascii_case("cbor") => ContentType::Cbor,
+            Some(ref s) if s.eq_ignore_ascii_case("jwt") => ContentType::Jwt,
+            Some(s) => return Err(ParseError::UnsupportedContentType(s)),
+        };
+        Ok(Self {
+            signed_data,
+            header: header.inner,
+            algorithm: header.algorithm.into_owned(),
+            content_type,
+            serialized_claims,
+            signature,
+            critical_extensions: header.critical.unwrap_or_default(),
+            unprotected_header: None,
+        })
+    }
+}
+
+#[cfg(feature = "cwt")]
+impl UntrustedToken<'static> {
+    /// Assembles a token from the parts of a parsed CWT.
+    pub(crate) fn from_cwt_parts(
+        signed_data: Vec<u8>,
+        header: Header,
+        algorithm: String,
+        serialized_claims: Vec<u8>,
+        signature: Vec<u8>,
+    ) -> Self {
+        Self {
+            signed_data: Cow::Owned(signed_data),
+            header,
+            algorithm,
+            content_type: ContentType::Cwt,
+            serialized_claims,
+            signature: SmallVec::from_vec(signature),
+            critical_extensions: Vec::new(),
+            unprotected_header: None,
         }
     }
 }
@@ -547,6 +998,8 @@ impl<H> UntrustedToken<'_, H> {
             content_type: self.content_type,
             serialized_claims: self.serialized_claims,
             signature: self.signature,
+            critical_extensions: self.critical_extensions,
+            unprotected_header: self.unprotected_header,
         }
     }
 
@@ -560,6 +1013,24 @@ impl<H> UntrustedToken<'_, H> {
         &self.algorithm
     }
 
+    /// Returns header extensions that are marked as critical in the token header
+    /// (the `crit` header parameter). A token with critical extensions only passes
+    /// validation if all these extensions are [understood] by the application.
+    ///
+    /// [understood]: crate::Validator::with_critical_extensions()
+    pub fn critical_extensions(&self) -> &[String] {
+        &self.critical_extensions
+    }
+
+    /// Returns the unprotected header of a signature in the [JWS JSON serialization], or `None`
+    /// for other tokens. The unprotected header is **not** covered by the signature and is not
+    /// reflected in [`Self::header()`]; thus, it should only be used for hints, such as the key ID.
+    ///
+    /// [JWS JSON serialization]: crate::UntrustedJsonToken
+    pub fn unprotected_header(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        self.unprotected_header.as_ref()
+    }
+
     /// Returns signature bytes from the token. These bytes are **not** guaranteed to form a valid
     /// signature.
     pub fn signature_bytes(&self) -> &[u8] {
@@ -576,9 +1047,27 @@ impl<H> UntrustedToken<'_, H> {
             ContentType::Json => serde_json::from_slice(&self.serialized_claims)
                 .map_err(ValidationError::MalformedClaims),
 
-            #[cfg(feature = "serde_cbor")]
-            ContentType::Cbor => serde_cbor::from_slice(&self.serialized_claims)
+            #[cfg(feature = "cbor")]
+            ContentType::Cbor => crate::cbor::from_slice(&self.serialized_claims)
+                .map_err(ValidationError::MalformedCborClaims),
+
+            #[cfg(feature = "cwt")]
+            ContentType::Cwt => crate::cwt::deserialize_claims(&self.serialized_claims)
                 .map_err(ValidationError::MalformedCborClaims),
+
+            ContentType::Jwt => Err(ValidationError::UnexpectedContentType(
+                ContentType::Jwt.as_str().into(),
+            )),
+        }
+    }
+
+    /// Returns the nested token from this token without checking token integrity.
+    pub(crate) fn nested_token_unchecked(&self) -> Result<Vec<u8>, ValidationError> {
+        if self.content_type == ContentType::Jwt {
+            Ok(self.serialized_claims.clone())
+        } else {
+            let content_type = self.content_type.as_str().into();
+            Err(ValidationError::UnexpectedContentType(content_type))
         }
     }
 }
@@ -590,9 +1079,9 @@ mod tests {
 
     use super::*;
     use crate::{
-        alg::{Hs256, Hs256Key},
+        alg::{Hs256, Hs256Key, Hs256Signature},
         alloc::{ToOwned, ToString},
-        AlgorithmExt, Empty,
+        AlgorithmExt, AlgorithmSignature, Empty,
     };
 
     type Obj = serde_json::Map<String, serde_json::Value>;
@@ -660,7 +1149,9 @@ mod tests {
         let header = r#"{"alg":"HS256","x5t":"lDpwLQbzRZmu4fjajvn3KWAx1pk"}"#;
         let header: CompleteHeader<Header<Empty>> = serde_json::from_str(header).unwrap();
         let thumbprint = header.inner.certificate_sha1_thumbprint.as_ref().unwrap();
-        let Thumbprint::Bytes(thumbprint) = thumbprint else { unreachable!() };
+        let Thumbprint::Bytes(thumbprint) = thumbprint else {
+            unreachable!()
+        };
 
         assert_eq!(thumbprint[0], 0x94);
         assert_eq!(thumbprint[19], 0x99);
@@ -680,7 +1171,9 @@ mod tests {
         let header = r#"{"alg":"HS256","x5t":"lDpwLQbzRZmu4fjajvn3KWAx1pk=="}"#;
         let header: CompleteHeader<Header<Empty>> = serde_json::from_str(header).unwrap();
         let thumbprint = header.inner.certificate_sha1_thumbprint.as_ref().unwrap();
-        let Thumbprint::Bytes(thumbprint) = thumbprint else { unreachable!() };
+        let Thumbprint::Bytes(thumbprint) = thumbprint else {
+            unreachable!()
+        };
 
         assert_eq!(thumbprint[0], 0x94);
         assert_eq!(thumbprint[19], 0x99);
@@ -692,7 +1185,9 @@ mod tests {
             r#"{"alg":"HS256","x5t":"NjVBRjY5MDlCMUIwNzU4RTA2QzZFMDQ4QzQ2MDAyQjVDNjk1RTM2Qg"}"#;
         let header: CompleteHeader<Header<Empty>> = serde_json::from_str(header).unwrap();
         let thumbprint = header.inner.certificate_sha1_thumbprint.as_ref().unwrap();
-        let Thumbprint::String(thumbprint) = thumbprint else { unreachable!() };
+        let Thumbprint::String(thumbprint) = thumbprint else {
+            unreachable!()
+        };
 
         assert_eq!(thumbprint, "65AF6909B1B0758E06C6E048C46002B5C695E36B");
 
@@ -712,7 +1207,9 @@ mod tests {
             r#"{"alg":"HS256","x5t":"NjVBRjY5MDlCMUIwNzU4RTA2QzZFMDQ4QzQ2MDAyQjVDNjk1RTM2Qg=="}"#;
         let header: CompleteHeader<Header<Empty>> = serde_json::from_str(header).unwrap();
         let thumbprint = header.inner.certificate_sha1_thumbprint.as_ref().unwrap();
-        let Thumbprint::String(thumbprint) = thumbprint else { unreachable!() };
+        let Thumbprint::String(thumbprint) = thumbprint else {
+            unreachable!()
+        };
 
         assert_eq!(thumbprint, "65AF6909B1B0758E06C6E048C46002B5C695E36B");
     }
@@ -746,7 +1243,9 @@ mod tests {
         let header = r#"{"alg":"HS256","x5t#S256":"MV9b23bQeMQ7isAGTkoBZGErH853yGk0W_yUx1iU7dM"}"#;
         let header: CompleteHeader<Header<Empty>> = serde_json::from_str(header).unwrap();
         let thumbprint = header.inner.certificate_thumbprint.as_ref().unwrap();
-        let Thumbprint::Bytes(thumbprint) = thumbprint else { unreachable!() };
+        let Thumbprint::Bytes(thumbprint) = thumbprint else {
+            unreachable!()
+        };
 
         assert_eq!(thumbprint[0], 0x31);
         assert_eq!(thumbprint[31], 0xd3);
@@ -761,6 +1260,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn certificate_thumbprints() {
+        // Thumbprints are computed over arbitrary bytes, so a real certificate is not required.
+        let certificate = b"abc";
+        let header = Header::empty().with_certificate_thumbprints(certificate);
+        assert_eq!(
+            serde_json::to_value(&header).unwrap(),
+            serde_json::json!({
+                "x5t": "qZk-NkcGgWq6PiVxeFDCbJzQ2J0",
+                "x5t#S256": "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0",
+            })
+        );
+        header
+            .validate_certificate_thumbprints(certificate)
+            .unwrap();
+        let err = header.validate_certificate_thumbprints(b"abd").unwrap_err();
+        assert_matches!(err, ValidationError::CertificateThumbprintMismatch);
+
+        let header = Header::empty()
+            .with_certificate_thumbprint(Thumbprint::<32>::from_certificate_der(certificate));
+        header
+            .validate_certificate_thumbprints(certificate)
+            .unwrap();
+        let header = header.with_certificate_sha1_thumbprint("abc");
+        let err = header
+            .validate_certificate_thumbprints(certificate)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::CertificateThumbprintMismatch);
+
+        let err = Header::empty()
+            .validate_certificate_thumbprints(certificate)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::NoCertificateThumbprint);
+    }
+
     #[test]
     fn malformed_header() {
         let mangled_headers = [
@@ -873,4 +1407,213 @@ mod tests {
         mangled_string.push('a');
         test_invalid_signature_len(&mangled_string, 33);
     }
+
+    /// Example from RFC 7797, section 4.2.
+    const UNENCODED_PAYLOAD_TOKEN: &str =
+        "eyJhbGciOiJIUzI1NiIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19.\
+                                           .A5dxf2s96_n5FLueVuW1Z_vh161FwXZC4YLPff6dmDY";
+
+    #[test]
+    fn rfc7797_example() {
+        let key = Base64UrlUnpadded::decode_vec(HS256_KEY).unwrap();
+        let key = Hs256Key::new(key);
+        let token_string = Hs256
+            .detached_token(&Header::empty(), b"$.02", &key)
+            .unwrap();
+        assert_eq!(token_string, UNENCODED_PAYLOAD_TOKEN);
+
+        let token: UntrustedToken =
+            UntrustedToken::with_detached_payload(UNENCODED_PAYLOAD_TOKEN, b"$.02").unwrap();
+        assert_eq!(token.algorithm(), "HS256");
+        assert!(token.signed_data.ends_with(b".$.02"));
+        let signature = Hs256Signature::try_from_slice(token.signature_bytes());
+        assert!(Hs256.verify_signature(&signature.unwrap(), &key, &token.signed_data));
+    }
+
+    #[test]
+    fn detached_unencoded_payload() {
+        let key = Base64UrlUnpadded::decode_vec(HS256_KEY).unwrap();
+        let key = Hs256Key::new(key);
+        let payload = br#"{"sub":"webhook","event":"push"}"#;
+        let token_string = Hs256
+            .detached_token(&Header::empty(), payload, &key)
+            .unwrap();
+
+        let token: UntrustedToken =
+            UntrustedToken::with_detached_payload(&token_string, payload).unwrap();
+        let token = Hs256.validator::<Obj>(&key).validate(&token).unwrap();
+        assert_eq!(token.claims().subject.as_deref(), Some("webhook"));
+        assert_eq!(token.claims().custom["event"], "push");
+
+        let mangled_payload = br#"{"sub":"webhook","event":"pull"}"#;
+        let token: UntrustedToken =
+            UntrustedToken::with_detached_payload(&token_string, mangled_payload).unwrap();
+        let err = Hs256.validator::<Obj>(&key).validate(&token).unwrap_err();
+        assert_matches!(err, ValidationError::InvalidSignature);
+
+        // If the payload is attached in the base64url-encoded form, it is interpreted as is.
+        let (header, signature) = token_string.split_once("..").unwrap();
+        let payload = Base64UrlUnpadded::encode_string(payload);
+        let attached_token = format!("{header}.{payload}.{signature}");
+        let token = UntrustedToken::new(&attached_token).unwrap();
+        let err = Hs256.validator::<Obj>(&key).validate(&token).unwrap_err();
+        assert_matches!(err, ValidationError::MalformedClaims(_));
+    }
+
+    #[test]
+    fn detached_encoded_payload() {
+        let key = Base64UrlUnpadded::decode_vec(HS256_KEY).unwrap();
+        let key = Hs256Key::new(key);
+        let (header, rest) = HS256_TOKEN.split_once('.').unwrap();
+        let (payload, signature) = rest.split_once('.').unwrap();
+        let payload = Base64UrlUnpadded::decode_vec(payload).unwrap();
+
+        let detached_token = format!("{header}..{signature}");
+        let token: UntrustedToken =
+            UntrustedToken::with_detached_payload(&detached_token, &payload).unwrap();
+        let token = Hs256.validator::<Obj>(&key).validate(&token).unwrap();
+        assert_eq!(token.claims().issuer.as_deref(), Some("joe"));
+
+        let err =
+            UntrustedToken::<Empty>::with_detached_payload(HS256_TOKEN, &payload).unwrap_err();
+        assert_matches!(err, ParseError::InvalidTokenStructure);
+    }
+
+    #[test]
+    fn unencoded_payload_requires_crit_header() {
+        let header = Base64UrlUnpadded::encode_string(br#"{"alg":"HS256","b64":false}"#);
+        let token = format!("{header}..A5dxf2s96_n5FLueVuW1Z_vh161FwXZC4YLPff6dmDY");
+        let err = UntrustedToken::<Empty>::with_detached_payload(&token, b"$.02").unwrap_err();
+        assert_matches!(err, ParseError::MalformedHeader(err) if err.to_string().contains("crit"));
+    }
+
+    #[test]
+    fn attached_unencoded_payload() {
+        let key = Base64UrlUnpadded::decode_vec(HS256_KEY).unwrap();
+        let key = Hs256Key::new(key);
+        let token_string = Hs256.detached_token(&Header::empty(), b"{}", &key).unwrap();
+        let attached_token = token_string.replace("..", ".{}.");
+
+        let token = UntrustedToken::new(&attached_token).unwrap();
+        Hs256.validator::<Empty>(&key).validate(&token).unwrap();
+    }
+
+    fn token_with_header(header: &str) -> String {
+        let header = Base64UrlUnpadded::encode_string(header.as_bytes());
+        let mut token = HS256_TOKEN.to_owned();
+        token.replace_range(..token.find('.').unwrap(), &header);
+        token
+    }
+
+    #[test]
+    fn malformed_critical_params() {
+        let headers_and_errors = [
+            (r#"{"alg":"HS256","crit":[]}"#, "must not be empty"),
+            (r#"{"alg":"HS256","crit":"exp"}"#, "invalid type"),
+            (
+                r#"{"alg":"HS256","crit":["kid"],"kid":"key"}"#,
+                "`kid` must not be listed",
+            ),
+            (r#"{"alg":"HS256","crit":["exp"]}"#, "`exp` is missing"),
+            (
+                r#"{"alg":"HS256","crit":["exp","exp"],"exp":1}"#,
+                "`exp` is listed multiple times",
+            ),
+        ];
+
+        for (header, expected_err) in headers_and_errors {
+            let token = token_with_header(header);
+            let err = UntrustedToken::new(&token).unwrap_err();
+            assert_matches!(
+                err,
+                ParseError::MalformedHeader(err) if err.to_string().contains(expected_err),
+                "Failing header: {header}"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_critical_extension() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let header = Header::new(serde_json::json!({
+            "crit": ["exp", "custom"],
+            "exp": 1,
+            "custom": true,
+        }));
+        let token = Hs256.token(&header, &Claims::empty(), &key).unwrap();
+        let token = UntrustedToken::<serde_json::Value>::try_from(token.as_str()).unwrap();
+        assert_eq!(token.critical_extensions(), ["exp", "custom"]);
+
+        let err = Hs256.validator::<Empty>(&key).validate(&token).unwrap_err();
+        assert_matches!(err, ValidationError::UnknownCriticalExtension(ext) if ext == "exp");
+        let err = Hs256
+            .validator::<Empty>(&key)
+            .with_critical_extensions(&["exp"])
+            .validate(&token)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::UnknownCriticalExtension(ext) if ext == "custom");
+
+        Hs256
+            .validator::<Empty>(&key)
+            .with_critical_extensions(&["custom", "exp"])
+            .validate(&token)
+            .unwrap();
+    }
+
+    #[test]
+    fn nested_token() {
+        let inner_key = Base64UrlUnpadded::decode_vec(HS256_KEY).unwrap();
+        let inner_key = Hs256Key::new(inner_key);
+        let outer_key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let header = Header::empty().with_key_id("outer");
+        let token_string = Hs256
+            .nested_token(&header, HS256_TOKEN, &outer_key)
+            .unwrap();
+
+        let token = UntrustedToken::new(&token_string).unwrap();
+        let err = Hs256
+            .validator::<Empty>(&outer_key)
+            .validate(&token)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::UnexpectedContentType(ty) if ty == "JWT");
+        let err = Hs256
+            .validator::<Empty>(&inner_key)
+            .validate_nested(&token)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::InvalidSignature);
+
+        let nested = Hs256
+            .validator::<Empty>(&outer_key)
+            .validate_nested(&token)
+            .unwrap();
+        assert_eq!(nested.header().key_id.as_deref(), Some("outer"));
+        let inner_token: UntrustedToken = nested.inner_token().unwrap();
+        let inner_token = Hs256
+            .validator::<Obj>(&inner_key)
+            .validate(&inner_token)
+            .unwrap();
+        assert_eq!(inner_token.claims().issuer.as_deref(), Some("joe"));
+
+        let token = UntrustedToken::new(HS256_TOKEN).unwrap();
+        let err = Hs256
+            .validator::<Empty>(&inner_key)
+            .validate_nested(&token)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::UnexpectedContentType(ty) if ty == "JSON");
+    }
+
+    #[test]
+    fn malformed_nested_token() {
+        let key = Hs256Key::new(b"super_secret_key_donut_steel");
+        let token_string = Hs256
+            .nested_token(&Header::empty(), "not.a.token", &key)
+            .unwrap();
+        let token = UntrustedToken::new(&token_string).unwrap();
+        let nested = Hs256
+            .validator::<Empty>(&key)
+            .validate_nested(&token)
+            .unwrap();
+        let err = nested.inner_token::<Empty>().unwrap_err();
+        assert_matches!(err, ParseError::InvalidBase64Encoding);
+    }
 }
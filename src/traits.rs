@@ -7,8 +7,9 @@ use core::{marker::PhantomData, num::NonZeroUsize};
 
 use crate::{
     alloc::{Cow, String, ToOwned, Vec},
-    token::CompleteHeader,
-    Claims, CreationError, Header, SignedToken, Token, UntrustedToken, ValidationError,
+    token::{CompleteHeader, SUPPORTED_CRITICAL_EXTENSIONS},
+    Claims, CreationError, Header, KeyRequest, KeyResolver, NestedToken, SignedToken, Token,
+    UntrustedToken, ValidationError,
 };
 
 /// Signature for a certain JWT signing [`Algorithm`].
@@ -50,6 +51,37 @@ pub trait Algorithm {
     /// Returns the name of this algorithm, as mentioned in the `alg` field of the JWT header.
     fn name(&self) -> Cow<'static, str>;
 
+    /// Returns the key ID associated with the `signing_key`, if any. If specified, the key ID
+    /// is recorded in the `kid` field of the header of created tokens, and token creation
+    /// fails if the header specifies a different key ID.
+    ///
+    /// The default implementation returns `None`. See [`KeyedAlg`] for an implementation
+    /// returning a key ID.
+    ///
+    /// [`KeyedAlg`]: crate::alg::KeyedAlg
+    fn key_id<'k>(&self, signing_key: &'k Self::SigningKey) -> Option<&'k str> {
+        let _ = signing_key;
+        None
+    }
+
+    /// Checks whether the `signing_key` can be used with this algorithm. This method is called
+    /// before signing a token; if it returns an error, token creation fails with this error.
+    ///
+    /// The default implementation always returns `Ok(())`, which is appropriate for algorithms
+    /// with a single statically typed key. Algorithms supporting multiple key types
+    /// (such as [`AnyAlgorithm`]) should override this method.
+    ///
+    /// [`AnyAlgorithm`]: crate::alg::AnyAlgorithm
+    ///
+    /// # Errors
+    ///
+    /// Returns an error (usually, [`CreationError::IncompatibleKey`]) if the key cannot be used
+    /// with this algorithm.
+    fn check_signing_key(&self, signing_key: &Self::SigningKey) -> Result<(), CreationError> {
+        let _ = signing_key;
+        Ok(())
+    }
+
     /// Signs a `message` with the `signing_key`.
     fn sign(&self, signing_key: &Self::SigningKey, message: &[u8]) -> Self::Signature;
 
@@ -110,6 +142,14 @@ impl<A: Algorithm> Algorithm for Renamed<A> {
         Cow::Borrowed(self.name)
     }
 
+    fn key_id<'k>(&self, signing_key: &'k Self::SigningKey) -> Option<&'k str> {
+        self.inner.key_id(signing_key)
+    }
+
+    fn check_signing_key(&self, signing_key: &Self::SigningKey) -> Result<(), CreationError> {
+        self.inner.check_signing_key(signing_key)
+    }
+
     fn sign(&self, signing_key: &Self::SigningKey, message: &[u8]) -> Self::Signature {
         self.inner.sign(signing_key, message)
     }
@@ -138,8 +178,13 @@ pub trait AlgorithmExt: Algorithm {
         T: Serialize;
 
     /// Creates a new token with CBOR-encoded claims and serializes it to string.
-    #[cfg(feature = "serde_cbor")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "serde_cbor")))]
+    ///
+    /// Claims are encoded deterministically as per [RFC 8949], so that the token signature
+    /// is reproducible for the same header and claims.
+    ///
+    /// [RFC 8949]: https://www.rfc-editor.org/rfc/rfc8949#section-4.2.1
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
     fn compact_token<T>(
         &self,
         header: &Header<impl Serialize>,
@@ -149,10 +194,73 @@ pub trait AlgorithmExt: Algorithm {
     where
         T: Serialize;
 
+    /// Creates a new [CBOR Web Token](crate::cwt) secured with a `COSE_Sign1` structure
+    /// (or a `COSE_Mac0` structure for MAC algorithms, such as `HS256`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CreationError::UnsupportedAlgorithm`] if the algorithm does not have
+    /// a registered COSE identifier.
+    #[cfg(feature = "cwt")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cwt")))]
+    fn cwt<T>(
+        &self,
+        header: &crate::cwt::CoseHeader,
+        claims: &Claims<T>,
+        signing_key: &Self::SigningKey,
+    ) -> Result<Vec<u8>, CreationError>
+    where
+        T: Serialize;
+
+    /// Creates a new token with another token (either signed or encrypted) as the payload,
+    /// as per [RFC 7519, section 5.2]. The created token has the `cty` header field set to `JWT`.
+    /// Such a token can be validated with [`Validator::validate_nested()`].
+    ///
+    /// [RFC 7519, section 5.2]: https://www.rfc-editor.org/rfc/rfc7519#section-5.2
+    fn nested_token(
+        &self,
+        header: &Header<impl Serialize>,
+        inner_token: &str,
+        signing_key: &Self::SigningKey,
+    ) -> Result<String, CreationError>;
+
+    /// Creates a new token with a detached, unencoded `payload` as per [RFC 7797].
+    ///
+    /// The token header has the `b64` parameter set to `false` (which is marked as critical
+    /// via the `crit` parameter), and the signature is computed over the raw `payload`
+    /// rather than its base64url encoding. The payload is not included into the token,
+    /// which has the `header..signature` form; the token can be parsed back
+    /// using [`UntrustedToken::with_detached_payload()`].
+    ///
+    /// [RFC 7797]: https://www.rfc-editor.org/rfc/rfc7797
+    fn detached_token(
+        &self,
+        header: &Header<impl Serialize>,
+        payload: &[u8],
+        signing_key: &Self::SigningKey,
+    ) -> Result<String, CreationError>;
+
     /// Creates a JWT validator for the specified verifying key and the claims type.
     /// The validator can then be used to validate integrity of one or more tokens.
     fn validator<'a, T>(&'a self, verifying_key: &'a Self::VerifyingKey) -> Validator<'a, Self, T>;
 
+    /// Validates the token integrity against a verifying key obtained from the `resolver`
+    /// based on the token header and unverified claims.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the resolver fails to provide a key, or if the token integrity
+    /// cannot be verified using the resolved key.
+    fn validate_with_resolver<T, H, R>(
+        &self,
+        token: &UntrustedToken<'_, H>,
+        resolver: &R,
+    ) -> Result<Token<T, H>, ValidationError>
+    where
+        T: DeserializeOwned,
+        H: Clone,
+        R: KeyResolver<Self> + ?Sized;
+
     /// Validates the token integrity against the provided `verifying_key`.
     #[deprecated = "Use `.validator().validate()` for added flexibility"]
     fn validate_integrity<T>(
@@ -187,28 +295,12 @@ impl<A: Algorithm> AlgorithmExt for A {
     where
         T: Serialize,
     {
-        let complete_header = CompleteHeader {
-            algorithm: self.name(),
-            content_type: None,
-            inner: header,
-        };
-        let header = serde_json::to_string(&complete_header).map_err(CreationError::Header)?;
-        let mut buffer = Vec::new();
-        encode_base64_buf(&header, &mut buffer);
-
-        let claims = serde_json::to_string(claims).map_err(CreationError::Claims)?;
-        buffer.push(b'.');
-        encode_base64_buf(&claims, &mut buffer);
-
-        let signature = self.sign(signing_key, &buffer);
-        buffer.push(b'.');
-        encode_base64_buf(signature.as_bytes(), &mut buffer);
-
-        // SAFETY: safe by construction: base64 alphabet and `.` char are valid UTF-8.
-        Ok(unsafe { String::from_utf8_unchecked(buffer) })
+        let complete_header = CompleteHeader::for_signing(self, header, signing_key)?;
+        let claims = serde_json::to_vec(claims).map_err(CreationError::Claims)?;
+        sign_token(self, &complete_header, &claims, signing_key)
     }
 
-    #[cfg(feature = "serde_cbor")]
+    #[cfg(feature = "cbor")]
     fn compact_token<T>(
         &self,
         header: &Header<impl Serialize>,
@@ -219,19 +311,59 @@ impl<A: Algorithm> AlgorithmExt for A {
         T: Serialize,
     {
         let complete_header = CompleteHeader {
-            algorithm: self.name(),
             content_type: Some("CBOR".to_owned()),
-            inner: header,
+            ..CompleteHeader::for_signing(self, header, signing_key)?
+        };
+        let claims = crate::cbor::to_vec(claims).map_err(CreationError::CborClaims)?;
+        sign_token(self, &complete_header, &claims, signing_key)
+    }
+
+    #[cfg(feature = "cwt")]
+    fn cwt<T>(
+        &self,
+        header: &crate::cwt::CoseHeader,
+        claims: &Claims<T>,
+        signing_key: &Self::SigningKey,
+    ) -> Result<Vec<u8>, CreationError>
+    where
+        T: Serialize,
+    {
+        crate::cwt::create_cwt(self, header, claims, signing_key)
+    }
+
+    fn nested_token(
+        &self,
+        header: &Header<impl Serialize>,
+        inner_token: &str,
+        signing_key: &Self::SigningKey,
+    ) -> Result<String, CreationError> {
+        let complete_header = CompleteHeader {
+            content_type: Some("JWT".to_owned()),
+            ..CompleteHeader::for_signing(self, header, signing_key)?
+        };
+        sign_token(self, &complete_header, inner_token.as_bytes(), signing_key)
+    }
+
+    fn detached_token(
+        &self,
+        header: &Header<impl Serialize>,
+        payload: &[u8],
+        signing_key: &Self::SigningKey,
+    ) -> Result<String, CreationError> {
+        let complete_header = CompleteHeader {
+            encoded_payload: Some(false),
+            critical: Some(core::iter::once("b64".to_owned()).collect()),
+            ..CompleteHeader::for_signing(self, header, signing_key)?
         };
         let header = serde_json::to_string(&complete_header).map_err(CreationError::Header)?;
         let mut buffer = Vec::new();
         encode_base64_buf(&header, &mut buffer);
+        let header_len = buffer.len();
 
-        let claims = serde_cbor::to_vec(claims).map_err(CreationError::CborClaims)?;
         buffer.push(b'.');
-        encode_base64_buf(&claims, &mut buffer);
-
+        buffer.extend_from_slice(payload);
         let signature = self.sign(signing_key, &buffer);
+        buffer.truncate(header_len + 1);
         buffer.push(b'.');
         encode_base64_buf(signature.as_bytes(), &mut buffer);
 
@@ -243,10 +375,25 @@ impl<A: Algorithm> AlgorithmExt for A {
         Validator {
             algorithm: self,
             verifying_key,
+            critical_extensions: &[],
             _claims: PhantomData,
         }
     }
 
+    fn validate_with_resolver<T, H, R>(
+        &self,
+        token: &UntrustedToken<'_, H>,
+        resolver: &R,
+    ) -> Result<Token<T, H>, ValidationError>
+    where
+        T: DeserializeOwned,
+        H: Clone,
+        R: KeyResolver<Self> + ?Sized,
+    {
+        let verifying_key = resolver.resolve_key(self, &KeyRequest::new(token))?;
+        self.validator::<T>(&verifying_key).validate(token)
+    }
+
     fn validate_integrity<T>(
         &self,
         token: &UntrustedToken<'_>,
@@ -277,37 +424,100 @@ impl<A: Algorithm> AlgorithmExt for A {
 pub struct Validator<'a, A: Algorithm + ?Sized, T> {
     algorithm: &'a A,
     verifying_key: &'a A::VerifyingKey,
+    critical_extensions: &'a [&'a str],
     _claims: PhantomData<fn() -> T>,
 }
 
 impl<A: Algorithm + ?Sized, T> Clone for Validator<'_, A, T> {
     fn clone(&self) -> Self {
-        Self {
-            algorithm: self.algorithm,
-            verifying_key: self.verifying_key,
-            _claims: PhantomData,
-        }
+        *self
     }
 }
 
 impl<A: Algorithm + ?Sized, T> Copy for Validator<'_, A, T> {}
 
-impl<A: Algorithm + ?Sized, T: DeserializeOwned> Validator<'_, A, T> {
-    /// Validates the token integrity against a verifying key enclosed in this validator.
-    pub fn validate<H: Clone>(
+impl<'a, A: Algorithm + ?Sized, T> Validator<'a, A, T> {
+    /// Registers [critical header extensions][crit] understood by the application.
+    /// Tokens listing other extensions in the `crit` header parameter will fail validation
+    /// with [`ValidationError::UnknownCriticalExtension`]. Extensions supported by the crate
+    /// itself (e.g., `b64` from RFC 7797) do not need to be registered.
+    ///
+    /// [crit]: https://www.rfc-editor.org/rfc/rfc7515#section-4.1.11
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use jwt_compact::{alg::{Hs256, Hs256Key}, prelude::*, Empty, ValidationError};
+    /// # use serde::{Deserialize, Serialize};
+    /// #[derive(Debug, Clone, Serialize, Deserialize)]
+    /// struct HeaderExtensions {
+    ///     exp: i64,
+    /// }
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let key = Hs256Key::new(b"super_secret_key_donut_steel");
+    /// let header = Header::new(serde_json::json!({
+    ///     "crit": ["exp"],
+    ///     "exp": 1_363_284_000,
+    /// }));
+    /// let token_string = Hs256.token(&header, &Claims::empty(), &key)?;
+    /// // `crit` is parsed by the crate and is not included into the header extensions.
+    /// let token: UntrustedToken<HeaderExtensions> = token_string.as_str().try_into()?;
+    /// assert_eq!(token.critical_extensions(), ["exp"]);
+    ///
+    /// let err = Hs256.validator::<Empty>(&key).validate(&token).unwrap_err();
+    /// assert!(matches!(err, ValidationError::UnknownCriticalExtension(ext) if ext == "exp"));
+    /// Hs256
+    ///     .validator::<Empty>(&key)
+    ///     .with_critical_extensions(&["exp"])
+    ///     .validate(&token)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn with_critical_extensions(mut self, extensions: &'a [&'a str]) -> Self {
+        self.critical_extensions = extensions;
+        self
+    }
+
+    /// Validates a nested token (i.e., one with the `cty` header field set to `JWT`)
+    /// against a verifying key enclosed in this validator. The claims type of the validator
+    /// is not used.
+    ///
+    /// See [`NestedToken`] for an example of usage.
+    ///
+    /// # Errors
+    ///
+    /// In addition to errors returned by [`Self::validate()`], returns
+    /// [`ValidationError::UnexpectedContentType`] if the token is not nested.
+    pub fn validate_nested<H: Clone>(
         self,
         token: &UntrustedToken<'_, H>,
-    ) -> Result<Token<T, H>, ValidationError> {
-        self.validate_for_signed_token(token)
-            .map(|signed| signed.token)
+    ) -> Result<NestedToken<H>, ValidationError> {
+        let (_, inner) = self.check_integrity(token, || token.nested_token_unchecked())?;
+        Ok(NestedToken::new(token.header().clone(), inner))
     }
 
-    /// Validates the token integrity against a verifying key enclosed in this validator,
-    /// and returns the validated [`Token`] together with its signature.
-    pub fn validate_for_signed_token<H: Clone>(
-        self,
+    fn check_critical_extensions<H>(
+        &self,
         token: &UntrustedToken<'_, H>,
-    ) -> Result<SignedToken<A, T, H>, ValidationError> {
+    ) -> Result<(), ValidationError> {
+        let unknown_extension = token.critical_extensions().iter().find(|&extension| {
+            !SUPPORTED_CRITICAL_EXTENSIONS.contains(&extension.as_str())
+                && !self.critical_extensions.contains(&extension.as_str())
+        });
+        match unknown_extension {
+            Some(extension) => Err(ValidationError::UnknownCriticalExtension(extension.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the token integrity and parses the token payload using the provided closure.
+    fn check_integrity<H, P>(
+        &self,
+        token: &UntrustedToken<'_, H>,
+        parse_payload: impl FnOnce() -> Result<P, ValidationError>,
+    ) -> Result<(A::Signature, P), ValidationError> {
         let expected_alg = self.algorithm.name();
         if expected_alg != token.algorithm() {
             return Err(ValidationError::AlgorithmMismatch {
@@ -315,6 +525,7 @@ impl<A: Algorithm + ?Sized, T: DeserializeOwned> Validator<'_, A, T> {
                 actual: token.algorithm().to_owned(),
             });
         }
+        self.check_critical_extensions(token)?;
 
         let signature = token.signature_bytes();
         if let Some(expected_len) = A::Signature::LENGTH {
@@ -328,16 +539,37 @@ impl<A: Algorithm + ?Sized, T: DeserializeOwned> Validator<'_, A, T> {
 
         let signature =
             A::Signature::try_from_slice(signature).map_err(ValidationError::MalformedSignature)?;
-        // We assume that parsing claims is less computationally demanding than
+        // We assume that parsing the payload is less computationally demanding than
         // validating a signature.
-        let claims = token.deserialize_claims_unchecked::<T>()?;
+        let payload = parse_payload()?;
         if !self
             .algorithm
             .verify_signature(&signature, self.verifying_key, &token.signed_data)
         {
             return Err(ValidationError::InvalidSignature);
         }
+        Ok((signature, payload))
+    }
+}
+
+impl<A: Algorithm + ?Sized, T: DeserializeOwned> Validator<'_, A, T> {
+    /// Validates the token integrity against a verifying key enclosed in this validator.
+    pub fn validate<H: Clone>(
+        self,
+        token: &UntrustedToken<'_, H>,
+    ) -> Result<Token<T, H>, ValidationError> {
+        self.validate_for_signed_token(token)
+            .map(|signed| signed.token)
+    }
 
+    /// Validates the token integrity against a verifying key enclosed in this validator,
+    /// and returns the validated [`Token`] together with its signature.
+    pub fn validate_for_signed_token<H: Clone>(
+        self,
+        token: &UntrustedToken<'_, H>,
+    ) -> Result<SignedToken<A, T, H>, ValidationError> {
+        let (signature, claims) =
+            self.check_integrity(token, || token.deserialize_claims_unchecked::<T>())?;
         Ok(SignedToken {
             signature,
             token: Token::new(token.header().clone(), claims),
@@ -345,7 +577,27 @@ impl<A: Algorithm + ?Sized, T: DeserializeOwned> Validator<'_, A, T> {
     }
 }
 
-fn encode_base64_buf(source: impl AsRef<[u8]>, buffer: &mut Vec<u8>) {
+fn sign_token<A: Algorithm>(
+    algorithm: &A,
+    header: &CompleteHeader<'_, impl Serialize>,
+    payload: &[u8],
+    signing_key: &A::SigningKey,
+) -> Result<String, CreationError> {
+    let header = serde_json::to_string(header).map_err(CreationError::Header)?;
+    let mut buffer = Vec::new();
+    encode_base64_buf(&header, &mut buffer);
+    buffer.push(b'.');
+    encode_base64_buf(payload, &mut buffer);
+
+    let signature = algorithm.sign(signing_key, &buffer);
+    buffer.push(b'.');
+    encode_base64_buf(signature.as_bytes(), &mut buffer);
+
+    // SAFETY: safe by construction: base64 alphabet and `.` char are valid UTF-8.
+    Ok(unsafe { String::from_utf8_unchecked(buffer) })
+}
+
+pub(crate) fn encode_base64_buf(source: impl AsRef<[u8]>, buffer: &mut Vec<u8>) {
     let source = source.as_ref();
     let previous_len = buffer.len();
     let claims_len = Base64UrlUnpadded::encoded_len(source);
@@ -0,0 +1,747 @@
+//! Validation of X.509 certificate chains passed in the [`x5c`] header field of tokens.
+//!
+//! A token may carry the certificate chain for its signing key in the [`Header`]
+//! ([`Header::certificate_chain`]). [`CertificateVerifier`] validates such a chain against
+//! a caller-supplied set of trust anchors and, if the chain is valid, extracts the public key
+//! from its leaf certificate. The key can then be used to validate the token in the usual way.
+//! No network requests are performed; in particular, revocation is not checked.
+//!
+//! Chain validation is a simplified version of the path validation algorithm from [RFC 5280]:
+//!
+//! - Each certificate must be issued by the next certificate in the chain, i.e., its issuer
+//!   must be equal to the subject of the next certificate, and its signature must be verifiable
+//!   with the public key of the next certificate.
+//! - The last certificate in the chain must either be one of the trust anchors, or be issued
+//!   by one of them.
+//! - All certificates in the chain must be valid at the current time, with the leeway
+//!   from the supplied [`TimeOptions`].
+//! - Issuing certificates in the chain must be marked as CA certificates via the basic
+//!   constraints extension, and must satisfy the path length constraint if it is present.
+//! - If the key usage extension is present, it must allow signing certificates (for
+//!   issuing certificates) or digital signatures (for the leaf certificate).
+//! - Critical extensions other than basic constraints, key usage and subject alternative name
+//!   are rejected, since they cannot be processed.
+//!
+//! Trust anchors are trusted as is; their validity period and extensions are not checked.
+//!
+//! Certificate signatures are verified with the crypto backends enabled via crate features.
+//! The following certificate signature algorithms are supported:
+//!
+//! - ECDSA on the P-256, P-384 and P-521 curves with SHA-256, SHA-384 and SHA-512
+//!   respectively (`p256`, `p384` and `p521` features)
+//! - Ed25519 (any of `EdDSA` backends)
+//! - RSA with PKCS#1 v1.5 padding and SHA-256, SHA-384 or SHA-512 (`rsa` feature)
+//!
+//! [`x5c`]: https://www.rfc-editor.org/rfc/rfc7515.html#section-4.1.6
+//! [RFC 5280]: https://www.rfc-editor.org/rfc/rfc5280#section-6
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "p256")]
+//! # fn main() {
+//! use jwt_compact::{alg::Es256, prelude::*, x509::CertificateVerifier};
+//!
+//! fn validate_token(
+//!     token: &str,
+//!     root_certificates_pem: &str,
+//!     time_options: &TimeOptions,
+//! ) -> anyhow::Result<Token<serde_json::Value>> {
+//!     let verifier = CertificateVerifier::from_pem(root_certificates_pem)?;
+//!     let token = UntrustedToken::new(token)?;
+//!     // Validate the certificate chain from the token header and extract
+//!     // the signing key from the leaf certificate.
+//!     let key = verifier.verify_header(token.header(), time_options)?;
+//!     // Validate the token using the extracted key.
+//!     let token = Es256.validator::<serde_json::Value>(&key).validate(&token)?;
+//!     token.claims().validate_expiration(time_options)?;
+//!     Ok(token)
+//! }
+//! # }
+//! # #[cfg(not(feature = "p256"))]
+//! # fn main() {}
+//! ```
+
+use x509_cert::{
+    der::{self, asn1::ObjectIdentifier, Decode, Encode, Reader, SliceReader},
+    ext::pkix::{BasicConstraints, KeyUsage, SubjectAltName},
+    Certificate,
+};
+
+use core::{fmt, time::Duration};
+
+use crate::{
+    alg::{KeyEncodingError, KeyFormat, PublicKeyEncoding},
+    alloc::{String, ToString, Vec},
+    Header, TimeOptions,
+};
+
+/// Maximum supported number of certificates in a chain.
+const MAX_CHAIN_LENGTH: usize = 10;
+
+/// OIDs of extensions that may be marked critical.
+const PROCESSED_EXTENSIONS: &[ObjectIdentifier] = &[
+    ObjectIdentifier::new_unwrap("2.5.29.15"), // key usage
+    ObjectIdentifier::new_unwrap("2.5.29.17"), // subject alternative name
+    ObjectIdentifier::new_unwrap("2.5.29.19"), // basic constraints
+];
+
+/// Errors that can occur during validation of an X.509 certificate chain.
+///
+/// Certificates in the chain are identified by their zero-based index, with the leaf
+/// certificate having index 0.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CertificateError {
+    /// Token header does not contain a certificate chain.
+    NoCertificateChain,
+    /// Certificate chain is too long.
+    ChainTooLong {
+        /// Maximum supported number of certificates in a chain.
+        max_length: usize,
+    },
+    /// Certificate in the chain cannot be parsed.
+    Malformed {
+        /// Index of the certificate in the chain.
+        index: usize,
+        /// Underlying error.
+        error: anyhow::Error,
+    },
+    /// Trust anchor certificate cannot be parsed.
+    MalformedTrustAnchor(anyhow::Error),
+    /// Certificate is not issued by the next certificate in the chain.
+    IssuerMismatch {
+        /// Index of the certificate in the chain.
+        index: usize,
+    },
+    /// Certificate is signed with an unsupported algorithm, or an algorithm that does not
+    /// correspond to the issuer key.
+    UnsupportedSignatureAlgorithm {
+        /// Index of the certificate in the chain.
+        index: usize,
+        /// Object identifier of the signature algorithm.
+        algorithm: String,
+    },
+    /// Certificate signature is invalid.
+    InvalidSignature {
+        /// Index of the certificate in the chain.
+        index: usize,
+    },
+    /// Public key in the certificate cannot be decoded.
+    Key {
+        /// Index of the certificate in the chain.
+        index: usize,
+        /// Underlying error.
+        error: KeyEncodingError,
+    },
+    /// Certificate is not yet valid.
+    NotYetValid {
+        /// Index of the certificate in the chain.
+        index: usize,
+    },
+    /// Certificate has expired.
+    Expired {
+        /// Index of the certificate in the chain.
+        index: usize,
+    },
+    /// Certificate used to sign another certificate is not a CA certificate.
+    NotCertificateAuthority {
+        /// Index of the certificate in the chain.
+        index: usize,
+    },
+    /// Path length constraint of a CA certificate is violated.
+    PathLengthExceeded {
+        /// Index of the certificate in the chain.
+        index: usize,
+    },
+    /// Key usage extension of the certificate does not allow its use in the chain.
+    InvalidKeyUsage {
+        /// Index of the certificate in the chain.
+        index: usize,
+    },
+    /// Certificate contains a critical extension that cannot be processed.
+    UnsupportedCriticalExtension {
+        /// Index of the certificate in the chain.
+        index: usize,
+        /// Object identifier of the extension.
+        extension: String,
+    },
+    /// Chain does not lead to any of the trust anchors.
+    UntrustedChain,
+    /// Certificate thumbprint from the token header (`x5t` or `x5t#S256`) does not match
+    /// the leaf certificate.
+    ThumbprintMismatch,
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoCertificateChain => formatter.write_str("token has no certificate chain"),
+            Self::ChainTooLong { max_length } => write!(
+                formatter,
+                "certificate chain is too long (max supported length is {max_length})"
+            ),
+            Self::Malformed { index, error } => {
+                write!(formatter, "cannot parse certificate #{index}: {error}")
+            }
+            Self::MalformedTrustAnchor(err) => {
+                write!(formatter, "cannot parse trust anchor certificate: {err}")
+            }
+            Self::IssuerMismatch { index } => write!(
+                formatter,
+                "certificate #{index} is not issued by the next certificate in the chain"
+            ),
+            Self::UnsupportedSignatureAlgorithm { index, algorithm } => write!(
+                formatter,
+                "certificate #{index} is signed with unsupported algorithm {algorithm}"
+            ),
+            Self::InvalidSignature { index } => {
+                write!(formatter, "certificate #{index} has invalid signature")
+            }
+            Self::Key { index, error } => {
+                write!(
+                    formatter,
+                    "cannot decode key in certificate #{index}: {error}"
+                )
+            }
+            Self::NotYetValid { index } => {
+                write!(formatter, "certificate #{index} is not yet valid")
+            }
+            Self::Expired { index } => write!(formatter, "certificate #{index} has expired"),
+            Self::NotCertificateAuthority { index } => {
+                write!(formatter, "certificate #{index} is not a CA certificate")
+            }
+            Self::PathLengthExceeded { index } => write!(
+                formatter,
+                "path length constraint of certificate #{index} is violated"
+            ),
+            Self::InvalidKeyUsage { index } => write!(
+                formatter,
+                "key usage of certificate #{index} does not allow its use in the chain"
+            ),
+            Self::UnsupportedCriticalExtension { index, extension } => write!(
+                formatter,
+                "certificate #{index} has unsupported critical extension {extension}"
+            ),
+            Self::UntrustedChain => {
+                formatter.write_str("certificate chain does not lead to a trust anchor")
+            }
+            Self::ThumbprintMismatch => formatter.write_str(
+                "certificate thumbprint in token header does not match the leaf certificate",
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CertificateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Malformed { error, .. } | Self::MalformedTrustAnchor(error) => {
+                Some(error.as_ref())
+            }
+            Self::Key { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Parsed certificate together with its encoding.
+#[derive(Debug, Clone)]
+struct ParsedCertificate {
+    inner: Certificate,
+    /// DER encoding of the entire certificate.
+    der: Vec<u8>,
+    /// DER encoding of the `TBSCertificate`, i.e. the signed part of the certificate.
+    signed_der: Vec<u8>,
+}
+
+impl ParsedCertificate {
+    fn new(der: &[u8]) -> anyhow::Result<Self> {
+        let inner = Certificate::from_der(der).map_err(|err| anyhow::anyhow!(err))?;
+        let signed_der = Self::signed_part(der).map_err(|err| anyhow::anyhow!(err))?;
+        Ok(Self {
+            inner,
+            der: der.to_vec(),
+            signed_der: signed_der.to_vec(),
+        })
+    }
+
+    /// Extracts the `TBSCertificate` bytes as they are encoded in the certificate.
+    fn signed_part(der: &[u8]) -> der::Result<&[u8]> {
+        let mut reader = SliceReader::new(der)?;
+        let header = der::Header::decode(&mut reader)?;
+        header.tag.assert_eq(der::Tag::Sequence)?;
+        reader.tlv_bytes()
+    }
+
+    fn subject_key_der(&self) -> Vec<u8> {
+        self.inner
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .expect("cannot re-encode parsed public key info")
+    }
+
+    fn check_issuer_constraints(&self, index: usize) -> Result<(), CertificateError> {
+        let tbs = &self.inner.tbs_certificate;
+        let constraints = tbs.get::<BasicConstraints>();
+        let constraints = constraints.map_err(|err| CertificateError::Malformed {
+            index,
+            error: anyhow::anyhow!(err),
+        })?;
+        match constraints {
+            Some((_, constraints)) if constraints.ca => {
+                // `index - 1` is the number of intermediate CA certificates below this one.
+                if let Some(max_path_len) = constraints.path_len_constraint {
+                    if index - 1 > usize::from(max_path_len) {
+                        return Err(CertificateError::PathLengthExceeded { index });
+                    }
+                }
+            }
+            _ => return Err(CertificateError::NotCertificateAuthority { index }),
+        }
+
+        if let Some(usage) = self.key_usage(index)? {
+            if !usage.key_cert_sign() {
+                return Err(CertificateError::InvalidKeyUsage { index });
+            }
+        }
+        Ok(())
+    }
+
+    fn key_usage(&self, index: usize) -> Result<Option<KeyUsage>, CertificateError> {
+        let usage = self.inner.tbs_certificate.get::<KeyUsage>();
+        let usage = usage.map_err(|err| CertificateError::Malformed {
+            index,
+            error: anyhow::anyhow!(err),
+        })?;
+        Ok(usage.map(|(_, usage)| usage))
+    }
+
+    fn check_extensions(&self, index: usize) -> Result<(), CertificateError> {
+        let extensions = self.inner.tbs_certificate.extensions.as_deref();
+        for extension in extensions.unwrap_or_default() {
+            if extension.critical && !PROCESSED_EXTENSIONS.contains(&extension.extn_id) {
+                return Err(CertificateError::UnsupportedCriticalExtension {
+                    index,
+                    extension: extension.extn_id.to_string(),
+                });
+            }
+        }
+        // Check that the subject alternative name is well-formed; it is not processed otherwise.
+        let tbs = &self.inner.tbs_certificate;
+        tbs.get::<SubjectAltName>()
+            .map_err(|err| CertificateError::Malformed {
+                index,
+                error: anyhow::anyhow!(err),
+            })?;
+        Ok(())
+    }
+
+    fn check_validity<F>(
+        &self,
+        index: usize,
+        time_options: &TimeOptions<F>,
+    ) -> Result<(), CertificateError>
+    where
+        F: Fn() -> chrono::DateTime<chrono::Utc>,
+    {
+        let validity = &self.inner.tbs_certificate.validity;
+        let now = (time_options.clock_fn)();
+        let leeway = time_options.leeway.num_seconds();
+        let not_before = unix_seconds(validity.not_before.to_unix_duration());
+        let not_after = unix_seconds(validity.not_after.to_unix_duration());
+
+        if now.timestamp().saturating_add(leeway) < not_before {
+            Err(CertificateError::NotYetValid { index })
+        } else if now.timestamp().saturating_sub(leeway) > not_after {
+            Err(CertificateError::Expired { index })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn is_named_issuer(&self, issuer: &Self) -> bool {
+        self.inner.tbs_certificate.issuer == issuer.inner.tbs_certificate.subject
+    }
+
+    /// Checks that the signature of this certificate (with the specified `index` in the chain)
+    /// is verifiable with the key of `issuer`.
+    fn check_signature(&self, index: usize, issuer: &Self) -> Result<(), CertificateError> {
+        let signature_algorithm = &self.inner.signature_algorithm;
+        if *signature_algorithm != self.inner.tbs_certificate.signature {
+            return Err(CertificateError::UnsupportedSignatureAlgorithm {
+                index,
+                algorithm: signature_algorithm.oid.to_string(),
+            });
+        }
+        let issuer_key = &issuer.inner.tbs_certificate.subject_public_key_info;
+        // For EC keys, algorithm parameters specify the curve.
+        let curve = issuer_key.algorithm.parameters.as_ref();
+        let curve = curve.and_then(|params| params.decode_as().ok());
+
+        // The signature must not have unused bits.
+        let is_valid = match self.inner.signature.as_bytes() {
+            Some(signature) => signatures::verify(
+                signature_algorithm.oid,
+                curve,
+                &issuer.subject_key_der(),
+                &self.signed_der,
+                signature,
+            ),
+            None => Err(SignatureError::Malformed),
+        };
+        match is_valid {
+            Ok(true) => Ok(()),
+            Ok(false) | Err(SignatureError::Malformed) => {
+                Err(CertificateError::InvalidSignature { index })
+            }
+            Err(SignatureError::UnsupportedAlgorithm) => {
+                Err(CertificateError::UnsupportedSignatureAlgorithm {
+                    index,
+                    algorithm: signature_algorithm.oid.to_string(),
+                })
+            }
+            Err(SignatureError::Key(error)) => Err(CertificateError::Key {
+                index: index + 1,
+                error,
+            }),
+        }
+    }
+}
+
+fn unix_seconds(duration: Duration) -> i64 {
+    i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+}
+
+/// Verifier of X.509 certificate chains against a fixed set of trust anchors.
+///
+/// See the [module-level docs](self) for details on the validation procedure.
+#[derive(Debug, Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "x509")))]
+pub struct CertificateVerifier {
+    trust_anchors: Vec<ParsedCertificate>,
+}
+
+impl CertificateVerifier {
+    /// Creates a verifier with the specified DER-encoded trust anchor certificates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the certificates cannot be parsed.
+    pub fn new(
+        trust_anchors: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    ) -> Result<Self, CertificateError> {
+        let trust_anchors = trust_anchors.into_iter().map(|der| {
+            ParsedCertificate::new(der.as_ref()).map_err(CertificateError::MalformedTrustAnchor)
+        });
+        Ok(Self {
+            trust_anchors: trust_anchors.collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Creates a verifier with the trust anchor certificates encoded in PEM. The input
+    /// may contain multiple concatenated `CERTIFICATE` PEM blocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not valid PEM, or if any of the certificates
+    /// cannot be parsed.
+    pub fn from_pem(pem: &str) -> Result<Self, CertificateError> {
+        let certificates = Certificate::load_pem_chain(pem.as_bytes())
+            .map_err(|err| CertificateError::MalformedTrustAnchor(anyhow::anyhow!(err)))?;
+        let trust_anchors = certificates.iter().map(|certificate| {
+            let der = certificate
+                .to_der()
+                .map_err(|err| CertificateError::MalformedTrustAnchor(anyhow::anyhow!(err)))?;
+            ParsedCertificate::new(&der).map_err(CertificateError::MalformedTrustAnchor)
+        });
+        Ok(Self {
+            trust_anchors: trust_anchors.collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Validates the certificate chain from the token `header` and returns the public key
+    /// from the leaf certificate. If the header contains certificate thumbprints
+    /// (`x5t` or `x5t#S256`), they are checked to match the leaf certificate
+    /// as per [`Header::validate_certificate_thumbprints()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header has no certificate chain, if the chain is invalid,
+    /// or if the thumbprint does not match.
+    pub fn verify_header<K, T, F>(
+        &self,
+        header: &Header<T>,
+        time_options: &TimeOptions<F>,
+    ) -> Result<K, CertificateError>
+    where
+        K: PublicKeyEncoding,
+        F: Fn() -> chrono::DateTime<chrono::Utc>,
+    {
+        let leaf = header
+            .certificate_chain
+            .first()
+            .ok_or(CertificateError::NoCertificateChain)?;
+        let has_thumbprints =
+            header.certificate_sha1_thumbprint.is_some() || header.certificate_thumbprint.is_some();
+        if has_thumbprints {
+            header
+                .validate_certificate_thumbprints(leaf)
+                .map_err(|_| CertificateError::ThumbprintMismatch)?;
+        }
+        self.verify_chain(&header.certificate_chain, time_options)
+    }
+
+    /// Validates a certificate chain and returns the public key from the leaf certificate.
+    /// The chain must consist of DER-encoded certificates, starting from the leaf one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the chain is empty or invalid, or if the leaf key cannot be decoded
+    /// as `K`.
+    pub fn verify_chain<K, F>(
+        &self,
+        chain: &[impl AsRef<[u8]>],
+        time_options: &TimeOptions<F>,
+    ) -> Result<K, CertificateError>
+    where
+        K: PublicKeyEncoding,
+        F: Fn() -> chrono::DateTime<chrono::Utc>,
+    {
+        if chain.is_empty() {
+            return Err(CertificateError::NoCertificateChain);
+        }
+        if chain.len() > MAX_CHAIN_LENGTH {
+            return Err(CertificateError::ChainTooLong {
+                max_length: MAX_CHAIN_LENGTH,
+            });
+        }
+
+        let chain = chain.iter().enumerate().map(|(index, der)| {
+            ParsedCertificate::new(der.as_ref())
+                .map_err(|error| CertificateError::Malformed { index, error })
+        });
+        let chain: Vec<_> = chain.collect::<Result<_, _>>()?;
+
+        for (index, pair) in chain.windows(2).enumerate() {
+            if !pair[0].is_named_issuer(&pair[1]) {
+                return Err(CertificateError::IssuerMismatch { index });
+            }
+        }
+        for (index, certificate) in chain.iter().enumerate() {
+            certificate.check_extensions(index)?;
+            certificate.check_validity(index, time_options)?;
+            if index > 0 {
+                certificate.check_issuer_constraints(index)?;
+            }
+        }
+        if let Some(usage) = chain[0].key_usage(0)? {
+            if !usage.digital_signature() {
+                return Err(CertificateError::InvalidKeyUsage { index: 0 });
+            }
+        }
+        for (index, pair) in chain.windows(2).enumerate() {
+            pair[0].check_signature(index, &pair[1])?;
+        }
+        self.check_trust(&chain)?;
+
+        K::from_public_der(KeyFormat::Spki, &chain[0].subject_key_der())
+            .map_err(|error| CertificateError::Key { index: 0, error })
+    }
+
+    /// Checks that the last certificate in the chain is a trust anchor or is issued by one.
+    fn check_trust(&self, chain: &[ParsedCertificate]) -> Result<(), CertificateError> {
+        let index = chain.len() - 1;
+        let last = &chain[index];
+        if self
+            .trust_anchors
+            .iter()
+            .any(|anchor| anchor.der == last.der)
+        {
+            return Ok(());
+        }
+
+        let mut last_error = CertificateError::UntrustedChain;
+        let issuers = self
+            .trust_anchors
+            .iter()
+            .filter(|anchor| last.is_named_issuer(anchor));
+        for anchor in issuers {
+            match last.check_signature(index, anchor) {
+                Ok(()) => return Ok(()),
+                Err(CertificateError::Key { .. }) => {
+                    // The anchor key is not supported; this is not the fault of the chain.
+                }
+                Err(err) => last_error = err,
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// Error verifying a certificate signature.
+#[derive(Debug)]
+enum SignatureError {
+    UnsupportedAlgorithm,
+    Malformed,
+    Key(KeyEncodingError),
+}
+
+impl From<KeyEncodingError> for SignatureError {
+    fn from(err: KeyEncodingError) -> Self {
+        match err {
+            KeyEncodingError::UnexpectedAlgorithm { .. } => Self::UnsupportedAlgorithm,
+            _ => Self::Key(err),
+        }
+    }
+}
+
+/// Verification of certificate signatures using the algorithms enabled via crate features.
+mod signatures {
+    use super::{ObjectIdentifier, SignatureError};
+
+    #[cfg(any(
+        feature = "p256",
+        feature = "p384",
+        feature = "p521",
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact",
+        feature = "rsa",
+    ))]
+    use crate::{
+        alg::{KeyFormat, PublicKeyEncoding},
+        Algorithm, AlgorithmSignature,
+    };
+
+    #[cfg(feature = "p256")]
+    const ECDSA_WITH_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+    #[cfg(feature = "p256")]
+    const P256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+    #[cfg(feature = "p384")]
+    const ECDSA_WITH_SHA384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.3");
+    #[cfg(feature = "p384")]
+    const P384: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.34");
+    #[cfg(feature = "p521")]
+    const ECDSA_WITH_SHA512: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.4");
+    #[cfg(feature = "p521")]
+    const P521: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.132.0.35");
+    #[cfg(any(
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact"
+    ))]
+    const ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+    #[cfg(feature = "rsa")]
+    const SHA256_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+    #[cfg(feature = "rsa")]
+    const SHA384_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.12");
+    #[cfg(feature = "rsa")]
+    const SHA512_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.13");
+
+    /// Verifies a certificate `signature` over `message` with the issuer key.
+    #[allow(unused_variables)] // some variables are unused depending on the enabled features
+    pub(super) fn verify(
+        algorithm: ObjectIdentifier,
+        curve: Option<ObjectIdentifier>,
+        key_der: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, SignatureError> {
+        match (algorithm, curve) {
+            #[cfg(feature = "p256")]
+            (ECDSA_WITH_SHA256, Some(P256)) => {
+                let signature = ecdsa_signature(signature, 32)?;
+                verify_with(&crate::alg::Es256, key_der, message, &signature)
+            }
+            #[cfg(feature = "p384")]
+            (ECDSA_WITH_SHA384, Some(P384)) => {
+                let signature = ecdsa_signature(signature, 48)?;
+                verify_with(&crate::alg::Es384, key_der, message, &signature)
+            }
+            #[cfg(feature = "p521")]
+            (ECDSA_WITH_SHA512, Some(P521)) => {
+                let signature = ecdsa_signature(signature, 66)?;
+                verify_with(&crate::alg::Es512, key_der, message, &signature)
+            }
+            #[cfg(any(
+                feature = "exonum-crypto",
+                feature = "ed25519-dalek",
+                feature = "ed25519-compact"
+            ))]
+            (ED25519, _) => verify_with(&crate::alg::Ed25519, key_der, message, signature),
+            #[cfg(feature = "rsa")]
+            (SHA256_WITH_RSA, _) => {
+                verify_with(&crate::alg::Rsa::rs256(), key_der, message, signature)
+            }
+            #[cfg(feature = "rsa")]
+            (SHA384_WITH_RSA, _) => {
+                verify_with(&crate::alg::Rsa::rs384(), key_der, message, signature)
+            }
+            #[cfg(feature = "rsa")]
+            (SHA512_WITH_RSA, _) => {
+                verify_with(&crate::alg::Rsa::rs512(), key_der, message, signature)
+            }
+            _ => Err(SignatureError::UnsupportedAlgorithm),
+        }
+    }
+
+    #[cfg(any(
+        feature = "p256",
+        feature = "p384",
+        feature = "p521",
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact",
+        feature = "rsa",
+    ))]
+    fn verify_with<A>(
+        algorithm: &A,
+        key_der: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, SignatureError>
+    where
+        A: Algorithm,
+        A::VerifyingKey: PublicKeyEncoding,
+    {
+        let key = A::VerifyingKey::from_public_der(KeyFormat::Spki, key_der)?;
+        if let Some(expected_len) = A::Signature::LENGTH {
+            if signature.len() != expected_len.get() {
+                return Err(SignatureError::Malformed);
+            }
+        }
+        let signature =
+            A::Signature::try_from_slice(signature).map_err(|_| SignatureError::Malformed)?;
+        Ok(algorithm.verify_signature(&signature, &key, message))
+    }
+
+    /// Converts a DER-encoded ECDSA signature to the fixed-size `r || s` form.
+    #[cfg(any(feature = "p256", feature = "p384", feature = "p521"))]
+    fn ecdsa_signature(
+        der: &[u8],
+        scalar_len: usize,
+    ) -> Result<crate::alloc::Vec<u8>, SignatureError> {
+        use x509_cert::der::{asn1::UintRef, Decode, Reader, SliceReader};
+
+        let mut reader = SliceReader::new(der).map_err(|_| SignatureError::Malformed)?;
+        let (r, s) = reader
+            .sequence(|reader| Ok((UintRef::decode(reader)?, UintRef::decode(reader)?)))
+            .and_then(|scalars| reader.finish(scalars))
+            .map_err(|_| SignatureError::Malformed)?;
+
+        let mut signature = crate::alloc::Vec::with_capacity(2 * scalar_len);
+        for scalar in [r, s] {
+            let bytes = scalar.as_bytes();
+            if bytes.len() > scalar_len {
+                return Err(SignatureError::Malformed);
+            }
+            // Left-pad the scalar with zeros.
+            signature.resize(signature.len() + scalar_len - bytes.len(), 0);
+            signature.extend_from_slice(bytes);
+        }
+        Ok(signature)
+    }
+}
@@ -9,9 +9,10 @@ use serde_json::json;
 
 mod shared;
 
-use crate::shared::{create_claims, test_algorithm, Obj, SampleClaims};
+use crate::shared::{create_claims, test_algorithm, CompactClaims, Obj, SampleClaims};
 use jwt_compact::{
-    alg::*, prelude::*, Algorithm, AlgorithmExt, ParseError, Thumbprint, ValidationError,
+    alg::*, prelude::*, Algorithm, AlgorithmExt, CreationError, JsonTokenBuilder, ParseError,
+    Renamed, Thumbprint, UntrustedJsonToken, ValidationError,
 };
 
 #[test]
@@ -38,7 +39,7 @@ fn hs256_reference() {
         validated_token.claims().expiration.unwrap().timestamp(),
         1_300_819_380
     );
-    assert_eq!(validated_token.claims().custom["iss"], json!("joe"));
+    assert_eq!(validated_token.claims().issuer.as_deref(), Some("joe"));
     assert_eq!(
         validated_token.claims().custom["http://example.com/is_root"],
         json!(true)
@@ -87,10 +88,10 @@ fn hs384_reference() {
         .validate(&token)
         .unwrap();
     assert_eq!(token.claims().issued_at.unwrap().timestamp(), 1_516_239_022);
+    assert_eq!(token.claims().subject.as_deref(), Some("1234567890"));
     assert_eq!(
         token.claims().custom,
         SampleClaims {
-            subject: "1234567890".to_owned(),
             name: "John Doe".to_owned(),
             admin: true,
         }
@@ -117,10 +118,10 @@ fn hs512_reference() {
         .validate(&token)
         .unwrap();
     assert_eq!(token.claims().issued_at.unwrap().timestamp(), 1_516_239_122);
+    assert_eq!(token.claims().subject.as_deref(), Some("987654321"));
     assert_eq!(
         token.claims().custom,
         SampleClaims {
-            subject: "987654321".to_owned(),
             name: "Jane Doe".to_owned(),
             admin: false,
         }
@@ -162,8 +163,8 @@ fn es256_reference() {
         token.claims().expiration.unwrap().timestamp(),
         1_300_819_380
     );
+    assert_eq!(token.claims().issuer.as_deref(), Some("joe"));
     let expected_claims = json!({
-        "iss": "joe",
         "http://example.com/is_root": true,
     });
     assert_eq!(token.claims().custom, *expected_claims.as_object().unwrap());
@@ -200,10 +201,11 @@ fn es256k_reference() {
         .validate(&token)
         .unwrap();
     assert_eq!(token.claims().issued_at.unwrap().timestamp(), 1_561_814_788);
-    let expected_claims = json!({
-        "bla": "bla",
-        "iss": "did:uport:2nQtiQG6Cgm1GYTBaaKAgr76uY7iSexUkqX",
-    });
+    assert_eq!(
+        token.claims().issuer.as_deref(),
+        Some("did:uport:2nQtiQG6Cgm1GYTBaaKAgr76uY7iSexUkqX")
+    );
+    let expected_claims = json!({ "bla": "bla" });
     assert_eq!(token.claims().custom, *expected_claims.as_object().unwrap());
 }
 
@@ -258,10 +260,11 @@ fn ed25519_reference() {
         .validate(&token)
         .unwrap();
     assert_eq!(token.claims().issued_at.unwrap().timestamp(), 1_561_815_526);
-    let expected_claims = json!({
-        "foo": "bar",
-        "iss": "did:uport:2nQtiQG6Cgm1GYTBaaKAgr76uY7iSexUkqX",
-    });
+    assert_eq!(
+        token.claims().issuer.as_deref(),
+        Some("did:uport:2nQtiQG6Cgm1GYTBaaKAgr76uY7iSexUkqX")
+    );
+    let expected_claims = json!({ "foo": "bar" });
     assert_eq!(token.claims().custom, *expected_claims.as_object().unwrap());
 }
 
@@ -293,7 +296,8 @@ fn wso2_reference() {
 
     let exp = Utc.timestamp_opt(1452594892, 0).unwrap();
     assert_eq!(claims.expiration, Some(exp));
-    assert_eq!(claims.custom["sub"], "admin@carbon.super");
+    assert_eq!(claims.subject.as_deref(), Some("admin@carbon.super"));
+    assert_eq!(claims.issuer.as_deref(), Some("http://wso2.org/gateway"));
     assert_eq!(claims.custom["http://wso2.org/claims/organization"], "WSO2");
 }
 
@@ -315,7 +319,7 @@ fn hs512_algorithm() {
     test_algorithm(&Hs512, &key, &key);
 }
 
-#[cfg(feature = "serde_cbor")]
+#[cfg(feature = "cbor")]
 #[test]
 fn compact_token_hs256() {
     let claims = create_claims();
@@ -335,18 +339,14 @@ fn compact_token_hs256() {
     assert_eq!(*token.claims(), claims);
 
     // Check that we can collect unknown / hard to parse claims into `Claims.custom`.
-    // `serde_cbor::Value` is not defined without `std`.
-    #[cfg(feature = "std")]
-    {
-        use std::collections::HashMap;
-
-        let generic_token: Token<HashMap<String, serde_cbor::Value>> =
-            Hs256.validator(&key).validate(&untrusted_token).unwrap();
-        assert_matches::assert_matches!(
-            generic_token.claims().custom["sub"],
-            serde_cbor::Value::Bytes(_)
-        );
-    }
+    use std::collections::HashMap;
+
+    let generic_token: Token<HashMap<String, ciborium::Value>> =
+        Hs256.validator(&key).validate(&untrusted_token).unwrap();
+    assert_matches!(
+        generic_token.claims().custom["uid"],
+        ciborium::Value::Bytes(_)
+    );
 }
 
 #[cfg(feature = "exonum-crypto")]
@@ -469,6 +469,72 @@ fn es256_algorithm() {
     assert_eq!(verifying_key, verifying_key_copy);
 }
 
+#[cfg(feature = "p384")]
+#[test]
+fn es384_algorithm() {
+    use rand::Rng;
+
+    type SecretKey = <Es384 as Algorithm>::SigningKey;
+    type PublicKey = <Es384 as Algorithm>::VerifyingKey;
+
+    let mut rng = thread_rng();
+    let signing_key = loop {
+        let mut bytes = [0_u8; 48];
+        rng.fill(&mut bytes[..]);
+        if let Ok(key) = SecretKey::from_slice(&bytes) {
+            break key;
+        }
+    };
+    let verifying_key = signing_key.to_verifying_key();
+    test_algorithm(&Es384, &signing_key, &verifying_key);
+
+    // Test correctness of `SigningKey` / `VerifyingKey` trait implementations.
+    let signing_key_bytes = SigningKey::as_bytes(&signing_key);
+    let signing_key_copy: SecretKey = SigningKey::from_slice(&signing_key_bytes).unwrap();
+    assert_eq!(signing_key.as_bytes(), signing_key_copy.as_bytes());
+    assert_eq!(verifying_key, signing_key.to_verifying_key());
+
+    let verifying_key_bytes = verifying_key.as_bytes();
+    assert_eq!(verifying_key_bytes.len(), 49);
+    let verifying_key_copy: PublicKey = VerifyingKey::from_slice(&verifying_key_bytes).unwrap();
+    assert_eq!(verifying_key, verifying_key_copy);
+}
+
+#[cfg(feature = "p521")]
+#[test]
+fn es512_algorithm() {
+    use rand::Rng;
+
+    type SecretKey = <Es512 as Algorithm>::SigningKey;
+    type PublicKey = <Es512 as Algorithm>::VerifyingKey;
+
+    let mut rng = thread_rng();
+    let signing_key = loop {
+        let mut bytes = [0_u8; 66];
+        rng.fill(&mut bytes[1..]);
+        bytes[0] = rng.gen_range(0..=1); // the scalar is 521 bits long
+        if let Ok(key) = SecretKey::from_slice(&bytes) {
+            break key;
+        }
+    };
+    let verifying_key = signing_key.to_verifying_key();
+    test_algorithm(&Es512, &signing_key, &verifying_key);
+
+    // Test correctness of `SigningKey` / `VerifyingKey` trait implementations.
+    let signing_key_bytes = SigningKey::as_bytes(&signing_key);
+    let signing_key_copy: SecretKey = SigningKey::from_slice(&signing_key_bytes).unwrap();
+    assert_eq!(signing_key.as_bytes(), signing_key_copy.as_bytes());
+    assert_eq!(
+        verifying_key.as_bytes(),
+        signing_key.to_verifying_key().as_bytes()
+    );
+
+    let verifying_key_bytes = verifying_key.as_bytes();
+    assert_eq!(verifying_key_bytes.len(), 67);
+    let verifying_key_copy: PublicKey = VerifyingKey::from_slice(&verifying_key_bytes).unwrap();
+    assert_eq!(verifying_key.as_bytes(), verifying_key_copy.as_bytes());
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct HeaderExtensions {
     custom: String,
@@ -493,7 +559,7 @@ fn test_algorithm_with_custom_header<A: Algorithm>(
     let claims = create_claims();
 
     // Successful case with a compact token.
-    #[cfg(feature = "serde_cbor")]
+    #[cfg(feature = "cbor")]
     {
         let token_string = algorithm
             .compact_token(&header, &claims, signing_key)
@@ -558,3 +624,201 @@ fn hs512_algorithm_with_custom_header() {
     let key = Hs512Key::generate(&mut thread_rng()).into_inner();
     test_algorithm_with_custom_header(&Hs512, &key, &key);
 }
+
+#[test]
+fn any_algorithm_with_symmetric_keys() {
+    let key = Hs256Key::generate(&mut thread_rng()).into_inner();
+    let jwk = jwt_compact::jwk::JsonWebKey::from(&key);
+    let signing_key = AnySigningKey::try_from(&jwk).unwrap();
+    let verifying_key = signing_key.to_verifying_key();
+    test_algorithm(&AnyAlgorithm::Hs256, &signing_key, &verifying_key);
+    test_algorithm(&AnyAlgorithm::Hs512, &signing_key, &verifying_key);
+
+    // Check interoperability with the concrete algorithm.
+    let claims = create_claims();
+    let token_string = Hs256.token(&Header::empty(), &claims, &key).unwrap();
+    let token = UntrustedToken::new(&token_string).unwrap();
+    let allowed = [AnyAlgorithm::Hs256];
+    let algorithm = AnyAlgorithm::select(&allowed, &token, &verifying_key).unwrap();
+    let token = algorithm
+        .validator::<CompactClaims>(&verifying_key)
+        .validate(&token)
+        .unwrap();
+    assert_eq!(*token.claims(), claims);
+
+    let token_string = Hs384
+        .token(&Header::empty(), &claims, &Hs384Key::new(key.as_ref()))
+        .unwrap();
+    let token = UntrustedToken::new(&token_string).unwrap();
+    let err = AnyAlgorithm::select(&allowed, &token, &verifying_key).unwrap_err();
+    assert_matches!(err, ValidationError::AlgorithmNotAllowed(alg) if alg == "HS384");
+}
+
+#[cfg(feature = "p256")]
+#[test]
+fn any_algorithm_with_es256() {
+    use rand::Rng;
+
+    type SecretKey = <Es256 as Algorithm>::SigningKey;
+
+    let mut rng = thread_rng();
+    let signing_key = loop {
+        let bytes: [u8; 32] = rng.gen();
+        if let Ok(key) = SecretKey::from_slice(&bytes) {
+            break key;
+        }
+    };
+    let jwk = jwt_compact::jwk::JsonWebKey::from(&signing_key);
+    let any_signing_key = AnySigningKey::try_from(&jwk).unwrap();
+    let any_verifying_key = AnyVerifyingKey::try_from(&jwk).unwrap();
+    assert_matches!(any_verifying_key, AnyVerifyingKey::P256(_));
+    test_algorithm(&AnyAlgorithm::Es256, &any_signing_key, &any_verifying_key);
+
+    let claims = create_claims();
+    let token_string = Es256
+        .token(&Header::empty(), &claims, &signing_key)
+        .unwrap();
+    let token = UntrustedToken::new(&token_string).unwrap();
+    let allowed = [AnyAlgorithm::Hs256, AnyAlgorithm::Es256];
+    let algorithm = AnyAlgorithm::select(&allowed, &token, &any_verifying_key).unwrap();
+    assert_matches!(algorithm, AnyAlgorithm::Es256);
+    algorithm
+        .validator::<CompactClaims>(&any_verifying_key)
+        .validate(&token)
+        .unwrap();
+}
+
+#[cfg(feature = "p256")]
+#[test]
+fn any_algorithm_prevents_algorithm_confusion() {
+    use rand::Rng;
+
+    type SecretKey = <Es256 as Algorithm>::SigningKey;
+
+    let mut rng = thread_rng();
+    let signing_key = loop {
+        let bytes: [u8; 32] = rng.gen();
+        if let Ok(key) = SecretKey::from_slice(&bytes) {
+            break key;
+        }
+    };
+    let verifying_key = signing_key.to_verifying_key();
+    let any_verifying_key = AnyVerifyingKey::P256(verifying_key);
+
+    // Sign a token using the public key bytes as a symmetric secret.
+    let bogus_key = Hs256Key::new(verifying_key.as_bytes());
+    let claims = create_claims();
+    let token_string = Hs256.token(&Header::empty(), &claims, &bogus_key).unwrap();
+    let token = UntrustedToken::new(&token_string).unwrap();
+
+    let allowed = [AnyAlgorithm::Hs256, AnyAlgorithm::Es256];
+    let err = AnyAlgorithm::select(&allowed, &token, &any_verifying_key).unwrap_err();
+    assert_matches!(
+        err,
+        ValidationError::IncompatibleKey { algorithm } if algorithm == "HS256"
+    );
+    // Even if the selection step is skipped, the token does not pass validation.
+    let err = AnyAlgorithm::Hs256
+        .validator::<CompactClaims>(&any_verifying_key)
+        .validate(&token)
+        .unwrap_err();
+    assert_matches!(err, ValidationError::InvalidSignature);
+}
+
+#[test]
+fn any_algorithm_names() {
+    assert_matches!(AnyAlgorithm::from_name("HS256"), Some(AnyAlgorithm::Hs256));
+    assert!(AnyAlgorithm::from_name("none").is_none());
+    assert!(AnyAlgorithm::from_name("hs256").is_none());
+
+    #[cfg(any(
+        feature = "exonum-crypto",
+        feature = "ed25519-dalek",
+        feature = "ed25519-compact"
+    ))]
+    {
+        assert_matches!(AnyAlgorithm::from_name("EdDSA"), Some(AnyAlgorithm::EdDsa));
+        // The non-standard name is not supported.
+        assert!(AnyAlgorithm::from_name("Ed25519").is_none());
+    }
+    #[cfg(feature = "rsa")]
+    {
+        let alg = AnyAlgorithm::from_name("PS384").unwrap();
+        assert_eq!(alg.name(), "PS384");
+    }
+}
+
+#[test]
+fn keyed_algorithm() {
+    let key = Hs256Key::generate(&mut thread_rng()).into_inner();
+    let keyed_key = KeyedSigningKey::new(key.clone(), "my-key");
+    test_algorithm(&KeyedAlg(Hs256), &keyed_key, &key);
+
+    let claims = create_claims();
+    let token_string = KeyedAlg(Hs256)
+        .token(&Header::empty(), &claims, &keyed_key)
+        .unwrap();
+    let token = UntrustedToken::new(&token_string).unwrap();
+    assert_eq!(token.header().key_id.as_deref(), Some("my-key"));
+    Hs256
+        .validator::<CompactClaims>(&key)
+        .validate(&token)
+        .unwrap();
+
+    // A matching key ID in the header is fine.
+    let header = Header::empty().with_key_id("my-key");
+    let token_string = KeyedAlg(Hs256)
+        .nested_token(&header, &token_string, &keyed_key)
+        .unwrap();
+    let token = UntrustedToken::new(&token_string).unwrap();
+    assert_eq!(token.header().key_id.as_deref(), Some("my-key"));
+
+    let header = Header::empty().with_key_id("other-key");
+    let err = KeyedAlg(Hs256)
+        .token(&header, &claims, &keyed_key)
+        .unwrap_err();
+    assert_matches!(
+        &err,
+        CreationError::KeyIdMismatch { expected, actual }
+            if expected == "my-key" && actual == "other-key"
+    );
+    assert_eq!(
+        err.to_string(),
+        "key ID in token header (other-key) differs from the signing key ID (my-key)"
+    );
+    let err = KeyedAlg(Hs256)
+        .detached_token(&header, b"payload", &keyed_key)
+        .unwrap_err();
+    assert_matches!(err, CreationError::KeyIdMismatch { .. });
+}
+
+#[test]
+fn keyed_algorithm_with_wrappers() {
+    let key = Hs256Key::generate(&mut thread_rng());
+    let keyed_key = KeyedSigningKey::new(key, "my-key");
+    let alg = Renamed::new(KeyedAlg(StrongAlg(Hs256)), "HS2");
+    let token_string = alg
+        .token(&Header::empty(), &Claims::empty(), &keyed_key)
+        .unwrap();
+    let token = UntrustedToken::new(&token_string).unwrap();
+    assert_eq!(token.algorithm(), "HS2");
+    assert_eq!(token.header().key_id.as_deref(), Some("my-key"));
+}
+
+#[test]
+fn keyed_algorithm_with_json_serialization() {
+    let key = Hs256Key::generate(&mut thread_rng()).into_inner();
+    let keyed_key = KeyedSigningKey::new(key, "my-key");
+    let token_string = JsonTokenBuilder::new(&Claims::empty())
+        .unwrap()
+        .sign(&KeyedAlg(Hs256), &Header::empty(), &keyed_key)
+        .unwrap()
+        .to_flattened_json()
+        .unwrap();
+    let token = UntrustedJsonToken::new(&token_string).unwrap();
+    let signature = &token.signatures()[0];
+    assert_eq!(
+        signature.header().key_id.as_deref(),
+        Some(keyed_key.key_id())
+    );
+}
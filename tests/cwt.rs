@@ -0,0 +1,176 @@
+//! Tests for CBOR Web Tokens.
+
+use assert_matches::assert_matches;
+use chrono::{Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use jwt_compact::{
+    alg::{Hs256, Hs256Key, KeyedAlg, KeyedSigningKey},
+    cwt::{CoseHeader, UntrustedCwt},
+    jwk::JsonWebKey,
+    prelude::*,
+    Algorithm, CreationError, Renamed, ValidationError, ValidationPolicy,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SampleClaims {
+    name: String,
+}
+
+fn create_claims() -> Claims<SampleClaims> {
+    let now = Utc.with_ymd_and_hms(2020, 9, 1, 10, 0, 0).single().unwrap();
+    let mut claims = Claims::new(SampleClaims {
+        name: "John Doe".to_owned(),
+    })
+    .set_issuer("coap://as.example.com")
+    .set_subject("john");
+    claims.issued_at = Some(now);
+    claims.expiration = Some(now + Duration::days(7));
+    claims
+}
+
+fn test_cwt_algorithm<A: Algorithm>(
+    algorithm: &A,
+    signing_key: &A::SigningKey,
+    verifying_key: &A::VerifyingKey,
+) {
+    let claims = create_claims();
+    let header = CoseHeader::empty().with_key_id(*b"key");
+    let token_bytes = algorithm.cwt(&header, &claims, signing_key).unwrap();
+
+    let token = UntrustedCwt::new(&token_bytes).unwrap();
+    assert_eq!(token.algorithm(), algorithm.name());
+    assert_eq!(token.header().key_id.as_deref(), Some(&b"key"[..]));
+    let token = algorithm
+        .validator::<SampleClaims>(verifying_key)
+        .validate(token.as_ref())
+        .unwrap();
+    assert_eq!(*token.claims(), claims);
+
+    let mut mangled_bytes = token_bytes;
+    let signature_byte = mangled_bytes.len() - 1;
+    mangled_bytes[signature_byte] ^= 1;
+    let token = UntrustedCwt::new(&mangled_bytes).unwrap();
+    let err = algorithm
+        .validator::<SampleClaims>(verifying_key)
+        .validate(token.as_ref())
+        .unwrap_err();
+    assert_matches!(err, ValidationError::InvalidSignature);
+}
+
+#[test]
+fn hs256_cwt() {
+    let key = Hs256Key::new(b"super_secret_key_donut_steel");
+    test_cwt_algorithm(&Hs256, &key, &key);
+}
+
+#[test]
+fn cwt_with_validation_policy() {
+    let key = Hs256Key::new(b"super_secret_key_donut_steel");
+    let claims = create_claims();
+    let token_bytes = Hs256.cwt(&CoseHeader::empty(), &claims, &key).unwrap();
+    let token = UntrustedCwt::new(&token_bytes).unwrap();
+
+    let now = claims.issued_at.unwrap() + Duration::hours(1);
+    let policy = ValidationPolicy::new(TimeOptions::new(Duration::zero(), move || now))
+        .with_issuer("coap://as.example.com");
+    let validated = policy
+        .validate(Hs256.validator::<SampleClaims>(&key), token.as_ref())
+        .unwrap();
+    assert_eq!(validated.claims().subject.as_deref(), Some("john"));
+
+    let now = claims.expiration.unwrap() + Duration::hours(1);
+    let policy = ValidationPolicy::new(TimeOptions::new(Duration::zero(), move || now))
+        .with_issuer("coap://as.example.org");
+    let err = policy
+        .validate(Hs256.validator::<SampleClaims>(&key), token.as_ref())
+        .unwrap_err();
+    assert_matches!(
+        err.errors(),
+        [
+            ValidationError::Expired,
+            ValidationError::IssuerMismatch { .. }
+        ]
+    );
+}
+
+#[test]
+fn algorithm_without_cose_identifier() {
+    let key = Hs256Key::new(b"super_secret_key_donut_steel");
+    let renamed = Renamed::new(Hs256, "HS256-custom");
+    let err = renamed
+        .cwt(&CoseHeader::empty(), &create_claims(), &key)
+        .unwrap_err();
+    assert_matches!(err, CreationError::UnsupportedAlgorithm(name) if name == "HS256-custom");
+}
+
+#[test]
+fn cwt_with_keyed_algorithm() {
+    let key = Hs256Key::new(b"super_secret_key_donut_steel");
+    let keyed_key = KeyedSigningKey::new(key, "key");
+    test_cwt_algorithm(&KeyedAlg(Hs256), &keyed_key, keyed_key.as_ref());
+
+    let claims = create_claims();
+    let token_bytes = KeyedAlg(Hs256)
+        .cwt(&CoseHeader::empty(), &claims, &keyed_key)
+        .unwrap();
+    let token = UntrustedCwt::new(&token_bytes).unwrap();
+    assert_eq!(token.header().key_id.as_deref(), Some(&b"key"[..]));
+
+    let header = CoseHeader::empty().with_key_id(*b"other");
+    let err = KeyedAlg(Hs256)
+        .cwt(&header, &claims, &keyed_key)
+        .unwrap_err();
+    assert_matches!(
+        err,
+        CreationError::KeyIdMismatch { expected, actual } if expected == "key" && actual == "other"
+    );
+}
+
+#[test]
+fn symmetric_key_from_cose_key() {
+    let key = Hs256Key::new(b"super_secret_key_donut_steel");
+    let cose_key = JsonWebKey::from(&key).to_cose_key().unwrap();
+    let jwk = JsonWebKey::from_cose_key(&cose_key).unwrap();
+    let restored_key = Hs256Key::try_from(&jwk).unwrap();
+    assert_eq!(restored_key.as_ref(), key.as_ref());
+}
+
+#[cfg(feature = "p256")]
+#[test]
+fn es256_cwt() {
+    use jwt_compact::alg::{Es256, SigningKey};
+    use rand::{thread_rng, Rng};
+
+    type SecretKey = <Es256 as Algorithm>::SigningKey;
+    type PublicKey = <Es256 as Algorithm>::VerifyingKey;
+
+    let mut rng = thread_rng();
+    let signing_key = loop {
+        let bytes: [u8; 32] = rng.gen();
+        if let Ok(key) = SecretKey::from_slice(&bytes) {
+            break key;
+        }
+    };
+    let verifying_key = signing_key.to_verifying_key();
+    test_cwt_algorithm(&Es256, &signing_key, &verifying_key);
+
+    // Check that the verifying key survives the `COSE_Key` roundtrip.
+    let cose_key = JsonWebKey::from(&verifying_key).to_cose_key().unwrap();
+    let jwk = JsonWebKey::from_cose_key(&cose_key).unwrap();
+    assert_eq!(PublicKey::try_from(&jwk).unwrap(), verifying_key);
+}
+
+#[cfg(feature = "ed25519-compact")]
+#[test]
+fn ed25519_cwt() {
+    use jwt_compact::alg::Ed25519;
+
+    let (signing_key, verifying_key) = Ed25519::generate(&mut rand::thread_rng());
+    test_cwt_algorithm(&Ed25519, &signing_key, &verifying_key);
+
+    let cose_key = JsonWebKey::from(&verifying_key).to_cose_key().unwrap();
+    let jwk = JsonWebKey::from_cose_key(&cose_key).unwrap();
+    let restored_key = <Ed25519 as Algorithm>::VerifyingKey::try_from(&jwk).unwrap();
+    assert_eq!(restored_key, verifying_key);
+}
@@ -0,0 +1,344 @@
+//! Tests for JSON Web Encryption.
+
+use assert_matches::assert_matches;
+use base64ct::{Base64UrlUnpadded, Encoding};
+use serde::{Deserialize, Serialize};
+
+use jwt_compact::{
+    alg::{Hs256, Hs256Key},
+    jwe::{
+        A128CbcHs256, A128Gcm, A128Kw, A256CbcHs512, A256Gcm, A256Kw, ContentEncryption, Direct,
+        EncryptedToken, JweAlgorithm, KeyManagement, SymmetricKey,
+    },
+    jwk::JsonWebKey,
+    prelude::*,
+    Empty, ParseError, ValidationError,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SampleClaims {
+    name: String,
+}
+
+fn create_claims() -> Claims<SampleClaims> {
+    let claims = SampleClaims {
+        name: "John Doe".to_owned(),
+    };
+    Claims::new(claims).set_subject("john")
+}
+
+fn mangle_part(token: &str, part_idx: usize) -> String {
+    let mut parts: Vec<_> = token.split('.').map(str::to_owned).collect();
+    let mut bytes = Base64UrlUnpadded::decode_vec(&parts[part_idx]).unwrap();
+    bytes[0] ^= 1;
+    parts[part_idx] = Base64UrlUnpadded::encode_string(&bytes);
+    parts.join(".")
+}
+
+fn test_jwe_algorithm<K, E>(
+    jwe: &JweAlgorithm<K, E>,
+    encryption_key: &K::EncryptionKey,
+    decryption_key: &K::DecryptionKey,
+) -> EncryptedToken<'static>
+where
+    K: KeyManagement,
+    E: ContentEncryption,
+{
+    let claims = create_claims();
+    let header = Header::empty().with_key_id("key");
+    let token_string = jwe.encrypt(&header, &claims, encryption_key).unwrap();
+
+    let token = EncryptedToken::new(&token_string).unwrap();
+    assert_eq!(token.algorithm(), jwe.key_management().name());
+    assert_eq!(token.content_encryption(), jwe.content_encryption().name());
+    assert_eq!(token.header().key_id.as_deref(), Some("key"));
+    let token = jwe
+        .decrypt::<SampleClaims, _>(&token, decryption_key)
+        .unwrap();
+    assert_eq!(token.claims().custom, claims.custom);
+    assert_eq!(token.claims().subject.as_deref(), Some("john"));
+
+    // Mangle the encrypted key (if present), IV, ciphertext and the authentication tag.
+    let encrypted_key_len = token_string.split('.').nth(1).unwrap().len();
+    let start_idx = if encrypted_key_len == 0 { 2 } else { 1 };
+    for part_idx in start_idx..5 {
+        let mangled = mangle_part(&token_string, part_idx);
+        let mangled = EncryptedToken::new(&mangled).unwrap();
+        let err = jwe
+            .decrypt::<SampleClaims, _>(&mangled, decryption_key)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::DecryptionFailed);
+    }
+
+    EncryptedToken::new(&token_string).unwrap().into_owned()
+}
+
+#[test]
+fn direct_encryption() {
+    let key = SymmetricKey::new([5; 16]);
+    test_jwe_algorithm(&JweAlgorithm::new(Direct, A128Gcm), &key, &key);
+    let key = SymmetricKey::new([5; 32]);
+    test_jwe_algorithm(&JweAlgorithm::new(Direct, A256Gcm), &key, &key);
+}
+
+#[test]
+fn direct_encryption_with_aes_cbc() {
+    let key = SymmetricKey::new([5; 32]);
+    test_jwe_algorithm(&JweAlgorithm::new(Direct, A128CbcHs256), &key, &key);
+    let key = SymmetricKey::new([5; 64]);
+    test_jwe_algorithm(&JweAlgorithm::new(Direct, A256CbcHs512), &key, &key);
+
+    let err = JweAlgorithm::new(Direct, A128CbcHs256)
+        .encrypt(&Header::empty(), &create_claims(), &key)
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid key length"), "{err}");
+}
+
+#[test]
+fn aes_key_wrap() {
+    let jwk = serde_json::json!({ "kty": "oct", "k": "GawgguFyGrWKav7AX4VKUg" });
+    let jwk: JsonWebKey<'_> = serde_json::from_value(jwk).unwrap();
+    let key = SymmetricKey::try_from(&jwk).unwrap();
+    let token = test_jwe_algorithm(&JweAlgorithm::new(A128Kw, A256Gcm), &key, &key);
+    // Content encryption key (32 bytes) + 8 bytes of the integrity check value.
+    assert_eq!(token.encrypted_key().len(), 40);
+
+    let key = SymmetricKey::new([11; 32]);
+    test_jwe_algorithm(&JweAlgorithm::new(A256Kw, A128Gcm), &key, &key);
+    test_jwe_algorithm(&JweAlgorithm::new(A256Kw, A256CbcHs512), &key, &key);
+
+    let err = JweAlgorithm::new(A256Kw, A128Gcm)
+        .encrypt(
+            &Header::empty(),
+            &create_claims(),
+            &SymmetricKey::new([1; 16]),
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid key length"), "{err}");
+}
+
+#[test]
+fn sign_then_encrypt() {
+    let signing_key = Hs256Key::new(b"super_secret_key_donut_steel");
+    let signed_token = Hs256
+        .token(&Header::empty(), &create_claims(), &signing_key)
+        .unwrap();
+    let encryption_key = SymmetricKey::new([7; 16]);
+    let jwe = JweAlgorithm::new(A128Kw, A256Gcm);
+    let token_string = jwe
+        .encrypt_nested(&Header::empty(), &signed_token, &encryption_key)
+        .unwrap();
+
+    let token = EncryptedToken::new(&token_string).unwrap();
+    assert_eq!(token.content_type(), Some("JWT"));
+    let err = jwe
+        .decrypt::<SampleClaims, _>(&token, &encryption_key)
+        .unwrap_err();
+    assert_matches!(err, ValidationError::UnexpectedContentType(ty) if ty == "JWT");
+
+    let nested = jwe.decrypt_nested(&token, &encryption_key).unwrap();
+    let inner_token: UntrustedToken = nested.inner_token().unwrap();
+    let inner_token = Hs256
+        .validator::<SampleClaims>(&signing_key)
+        .validate(&inner_token)
+        .unwrap();
+    assert_eq!(inner_token.claims().custom, create_claims().custom);
+
+    let err = nested.inner_encrypted_token::<Empty>().unwrap_err();
+    assert_matches!(err, ParseError::InvalidTokenStructure);
+}
+
+#[test]
+fn encrypt_then_sign() {
+    let encryption_key = SymmetricKey::new([7; 32]);
+    let jwe = JweAlgorithm::new(Direct, A256Gcm);
+    let encrypted_token = jwe
+        .encrypt(&Header::empty(), &create_claims(), &encryption_key)
+        .unwrap();
+    let signing_key = Hs256Key::new(b"super_secret_key_donut_steel");
+    let token_string = Hs256
+        .nested_token(&Header::empty(), &encrypted_token, &signing_key)
+        .unwrap();
+
+    let token = UntrustedToken::new(&token_string).unwrap();
+    let nested = Hs256
+        .validator::<Empty>(&signing_key)
+        .validate_nested(&token)
+        .unwrap();
+    let inner_token: EncryptedToken = nested.inner_encrypted_token().unwrap();
+    let inner_token = jwe
+        .decrypt::<SampleClaims, _>(&inner_token, &encryption_key)
+        .unwrap();
+    assert_eq!(inner_token.claims().subject.as_deref(), Some("john"));
+
+    let inner_token = nested.inner_encrypted_token::<Empty>().unwrap();
+    let err = jwe
+        .decrypt_nested(&inner_token, &encryption_key)
+        .unwrap_err();
+    assert_matches!(err, ValidationError::UnexpectedContentType(ty) if ty == "JSON");
+}
+
+#[test]
+fn symmetric_key_from_incorrect_jwk() {
+    let jwk = JsonWebKey::KeyPair {
+        curve: "X25519".into(),
+        x: vec![0; 32].into(),
+        secret: None,
+    };
+    let err = SymmetricKey::try_from(&jwk).unwrap_err();
+    assert!(err.to_string().contains("unexpected key type"), "{err}");
+}
+
+#[cfg(feature = "rsa")]
+#[test]
+fn rsa_oaep() {
+    use jwt_compact::{
+        alg::{RsaPrivateKey, RsaPublicKey},
+        jwe::RsaOaep256,
+    };
+
+    let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2_048).unwrap();
+    let public_key = RsaPublicKey::from(&private_key);
+    let jwe = JweAlgorithm::new(RsaOaep256, A256Gcm);
+    let token = test_jwe_algorithm(&jwe, &public_key, &private_key);
+    assert_eq!(token.encrypted_key().len(), 256);
+
+    let other_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2_048).unwrap();
+    let err = jwe
+        .decrypt::<SampleClaims, _>(&token, &other_key)
+        .unwrap_err();
+    assert_matches!(err, ValidationError::DecryptionFailed);
+}
+
+#[cfg(feature = "p256")]
+mod ecdh_p256 {
+    use super::*;
+    use jwt_compact::jwe::{EcdhEs, P256};
+    use p256::{PublicKey, SecretKey};
+
+    #[test]
+    fn direct_key_agreement() {
+        let secret_key = SecretKey::random(&mut rand::thread_rng());
+        let public_key = secret_key.public_key();
+        let jwe = JweAlgorithm::new(EcdhEs::<P256>::direct(), A128Gcm);
+        let token = test_jwe_algorithm(&jwe, &public_key, &secret_key);
+        assert_eq!(token.algorithm(), "ECDH-ES");
+        assert!(token.encrypted_key().is_empty());
+
+        // Check that the ephemeral key is a valid P-256 key.
+        let ephemeral_key = token.key_management_header().ephemeral_key.as_ref();
+        let ephemeral_key = ephemeral_key.unwrap();
+        assert_eq!(ephemeral_key.key_type().to_string(), "EC");
+        let ephemeral_key = PublicKey::try_from(ephemeral_key).unwrap();
+        assert_ne!(ephemeral_key, public_key);
+
+        let other_key = SecretKey::random(&mut rand::thread_rng());
+        let err = jwe
+            .decrypt::<SampleClaims, _>(&token, &other_key)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::DecryptionFailed);
+    }
+
+    #[test]
+    fn key_agreement_with_key_wrap() {
+        let secret_key = SecretKey::random(&mut rand::thread_rng());
+        let public_key = secret_key.public_key();
+
+        let jwe = JweAlgorithm::new(EcdhEs::<P256>::a128kw(), A256Gcm);
+        let token = test_jwe_algorithm(&jwe, &public_key, &secret_key);
+        assert_eq!(token.algorithm(), "ECDH-ES+A128KW");
+        assert_eq!(token.encrypted_key().len(), 40);
+
+        let jwe = JweAlgorithm::new(EcdhEs::<P256>::a256kw(), A128Gcm);
+        let token = test_jwe_algorithm(&jwe, &public_key, &secret_key);
+        assert_eq!(token.algorithm(), "ECDH-ES+A256KW");
+        assert_eq!(token.encrypted_key().len(), 24);
+    }
+
+    #[test]
+    fn keys_from_jwk() {
+        let secret_key = SecretKey::random(&mut rand::thread_rng());
+        let jwk = JsonWebKey::from(&secret_key);
+        assert_eq!(SecretKey::try_from(&jwk).unwrap(), secret_key);
+        let public_key = PublicKey::try_from(&jwk).unwrap();
+        assert_eq!(public_key, secret_key.public_key());
+        let public_jwk = JsonWebKey::from(&public_key);
+        assert_matches!(public_jwk, JsonWebKey::EllipticCurve { secret: None, .. });
+    }
+}
+
+#[cfg(feature = "x25519-dalek")]
+mod ecdh_x25519 {
+    use super::*;
+    use jwt_compact::jwe::{EcdhEs, X25519};
+    use x25519_dalek::{PublicKey, StaticSecret};
+
+    #[test]
+    fn direct_key_agreement() {
+        let secret_key = StaticSecret::random_from_rng(rand::thread_rng());
+        let public_key = PublicKey::from(&secret_key);
+        let jwe = JweAlgorithm::new(EcdhEs::<X25519>::direct(), A256Gcm);
+        let token = test_jwe_algorithm(&jwe, &public_key, &secret_key);
+        let ephemeral_key = token.key_management_header().ephemeral_key.as_ref();
+        assert_matches!(
+            ephemeral_key.unwrap(),
+            JsonWebKey::KeyPair { curve, secret: None, .. } if curve == "X25519"
+        );
+    }
+
+    #[test]
+    fn key_agreement_with_key_wrap() {
+        let secret_key = StaticSecret::random_from_rng(rand::thread_rng());
+        let public_key = PublicKey::from(&secret_key);
+        let jwe = JweAlgorithm::new(EcdhEs::<X25519>::a256kw(), A256Gcm);
+        test_jwe_algorithm(&jwe, &public_key, &secret_key);
+    }
+
+    #[test]
+    fn low_order_ephemeral_key() {
+        let secret_key = StaticSecret::random_from_rng(rand::thread_rng());
+        let public_key = PublicKey::from(&secret_key);
+        let jwe = JweAlgorithm::new(EcdhEs::<X25519>::direct(), A128Gcm);
+        let token = jwe
+            .encrypt(&Header::empty(), &create_claims(), &public_key)
+            .unwrap();
+
+        // Replace the ephemeral key with the identity point.
+        let (header, rest) = token.split_once('.').unwrap();
+        let header = Base64UrlUnpadded::decode_vec(header).unwrap();
+        let mut header: serde_json::Value = serde_json::from_slice(&header).unwrap();
+        header["epk"]["x"] = Base64UrlUnpadded::encode_string(&[0; 32]).into();
+        let header = serde_json::to_vec(&header).unwrap();
+        let token = format!("{}.{rest}", Base64UrlUnpadded::encode_string(&header));
+
+        let token = EncryptedToken::new(&token).unwrap();
+        let err = jwe
+            .decrypt::<SampleClaims, _>(&token, &secret_key)
+            .unwrap_err();
+        assert_matches!(err, ValidationError::DecryptionFailed);
+    }
+
+    #[test]
+    fn keys_from_jwk() {
+        let secret_key = StaticSecret::random_from_rng(rand::thread_rng());
+        let jwk = JsonWebKey::from(&secret_key);
+        let restored = StaticSecret::try_from(&jwk).unwrap();
+        assert_eq!(restored.to_bytes(), secret_key.to_bytes());
+        let public_key = PublicKey::try_from(&jwk).unwrap();
+        assert_eq!(public_key, PublicKey::from(&secret_key));
+
+        let JsonWebKey::KeyPair { curve, x, secret } = jwk else {
+            unreachable!();
+        };
+        let mismatched_jwk = JsonWebKey::KeyPair {
+            curve,
+            x: vec![1; 32].into(),
+            secret,
+        };
+        let Err(err) = StaticSecret::try_from(&mismatched_jwk) else {
+            panic!("unexpected success");
+        };
+        assert!(err.to_string().contains("do not match"), "{err}");
+        assert_eq!(x.len(), 32);
+    }
+}
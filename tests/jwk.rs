@@ -28,10 +28,11 @@ fn assert_jwk_roundtrip(jwk: &JsonWebKey<'_>) {
     let restored_from_json: JsonWebKey<'_> = serde_json::from_value(json).unwrap();
     assert_eq!(restored_from_json, *jwk);
 
-    #[cfg(feature = "serde_cbor")]
+    #[cfg(feature = "cbor")]
     {
-        let bytes = serde_cbor::to_vec(jwk).unwrap();
-        let restored_from_cbor: JsonWebKey<'_> = serde_cbor::from_slice(&bytes).unwrap();
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(jwk, &mut bytes).unwrap();
+        let restored_from_cbor: JsonWebKey<'_> = ciborium::de::from_reader(&bytes[..]).unwrap();
         assert_eq!(restored_from_cbor, *jwk);
     }
 }
@@ -678,6 +679,227 @@ mod es256 {
     }
 }
 
+#[cfg(feature = "p384")]
+mod es384 {
+    use super::*;
+
+    use jwt_compact::{
+        alg::{Es384, SecretBytes, SigningKey, VerifyingKey},
+        Algorithm,
+    };
+
+    type SecretKey = <Es384 as Algorithm>::SigningKey;
+    type PublicKey = <Es384 as Algorithm>::VerifyingKey;
+
+    fn signing_key() -> SecretKey {
+        const SECRET: [u8; 48] = [7; 48];
+        SecretKey::from_slice(&SECRET).unwrap()
+    }
+
+    #[test]
+    fn verifying_jwk() {
+        let public_key = signing_key().to_verifying_key();
+        let jwk = JsonWebKey::from(&public_key);
+        assert!(!jwk.is_signing_key());
+        assert_jwk_roundtrip(&jwk);
+        assert_matches!(
+            &jwk,
+            JsonWebKey::EllipticCurve { curve, x, y, secret: None }
+                if curve == "P-384" && x.len() == 48 && y.len() == 48
+        );
+        assert_eq!(PublicKey::try_from(&jwk).unwrap(), public_key);
+
+        let bytes = VerifyingKey::as_bytes(&public_key);
+        assert_eq!(PublicKey::from_slice(&bytes).unwrap(), public_key);
+    }
+
+    #[test]
+    fn signing_jwk() {
+        let secret_key = signing_key();
+        let jwk = JsonWebKey::from(&secret_key);
+        assert!(jwk.is_signing_key());
+        assert_jwk_roundtrip(&jwk);
+
+        let restored_key = SecretKey::try_from(&jwk).unwrap();
+        assert_eq!(restored_key.to_bytes(), secret_key.to_bytes());
+        let public_key = PublicKey::try_from(&jwk).unwrap();
+        assert_eq!(public_key, secret_key.to_verifying_key());
+
+        let public_jwk = JsonWebKey::from(&public_key);
+        assert_eq!(public_jwk, jwk.to_verifying_key());
+        assert_eq!(
+            jwk.thumbprint::<Sha256>(),
+            public_jwk.thumbprint::<Sha256>()
+        );
+
+        let err = SecretKey::try_from(&public_jwk).map(drop).unwrap_err();
+        assert_matches!(err, JwkError::NoField(field) if field == "d");
+    }
+
+    #[test]
+    fn incorrect_curve() {
+        let jwk = serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+            "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0",
+        });
+        let jwk: JsonWebKey<'_> = serde_json::from_value(jwk).unwrap();
+        let err = PublicKey::try_from(&jwk).unwrap_err();
+
+        assert_matches!(
+            err,
+            JwkError::UnexpectedValue { field, expected, actual }
+                if field == "crv" && expected == "P-384" && actual == "P-256"
+        );
+    }
+
+    #[test]
+    fn incorrect_x_len() {
+        let jwk = serde_json::json!({
+            "kty": "EC",
+            "crv": "P-384",
+            "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+            "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0",
+        });
+        let jwk: JsonWebKey<'_> = serde_json::from_value(jwk).unwrap();
+        let err = PublicKey::try_from(&jwk).unwrap_err();
+
+        assert_matches!(
+            err,
+            JwkError::UnexpectedLen {
+                field,
+                expected: 48,
+                actual: 32,
+            } if field == "x"
+        );
+    }
+
+    #[test]
+    fn key_mismatch() {
+        let secret_key = signing_key();
+        let mut jwk = JsonWebKey::from(&secret_key);
+        if let JsonWebKey::EllipticCurve { secret, .. } = &mut jwk {
+            let other_key = SecretKey::from_slice(&[8; 48]).unwrap();
+            *secret = Some(SecretBytes::owned(other_key.to_bytes().to_vec()));
+        }
+        let err = SecretKey::try_from(&jwk).map(drop).unwrap_err();
+        assert_matches!(err, JwkError::MismatchedKeys);
+    }
+}
+
+#[cfg(feature = "p521")]
+mod es512 {
+    use super::*;
+
+    use jwt_compact::{
+        alg::{Es512, SecretBytes, SigningKey, VerifyingKey},
+        Algorithm,
+    };
+
+    type SecretKey = <Es512 as Algorithm>::SigningKey;
+    type PublicKey = <Es512 as Algorithm>::VerifyingKey;
+
+    /// Taken from https://www.rfc-editor.org/rfc/rfc7515.html#appendix-A.4
+    fn reference_jwk() -> JsonWebKey<'static> {
+        let jwk = serde_json::json!({
+            "kty": "EC",
+            "crv": "P-521",
+            "x": "AekpBQ8ST8a8VcfVOTNl353vSrDCLLJXmPk06wTjxrrjcBpXp5EOnYG_NjFZ6OvLFV1jSfS9tsz4qUxcWceqwQGk",
+            "y": "ADSmRA43Z1DSNx_RvcLI87cdL07l6jQyyBXMoxVg_l2Th-x3S1WDhjDly79ajL4Kkd0AZMaZmh9ubmf63e3kyMj2",
+            "d": "AY5pb7A0UFiB3RELSD64fTLOSV_jazdF7fLYyuTw8lOfRhWg6Y6rUrPAxerEzgdRhajnu0ferB0d53vM9mE15j2C",
+        });
+        serde_json::from_value(jwk).unwrap()
+    }
+
+    #[test]
+    fn verifying_jwk() {
+        let jwk = reference_jwk().to_verifying_key();
+        assert!(!jwk.is_signing_key());
+        assert_jwk_roundtrip(&jwk);
+
+        let public_key = PublicKey::try_from(&jwk).unwrap();
+        assert_eq!(JsonWebKey::from(&public_key), jwk);
+
+        let bytes = VerifyingKey::as_bytes(&public_key);
+        assert_eq!(bytes.len(), 67);
+        let restored_key = PublicKey::from_slice(&bytes).unwrap();
+        assert_eq!(JsonWebKey::from(&restored_key), jwk);
+    }
+
+    #[test]
+    fn signing_jwk() {
+        let jwk = reference_jwk();
+        assert!(jwk.is_signing_key());
+        assert_jwk_roundtrip(&jwk);
+
+        let secret_key = SecretKey::try_from(&jwk).unwrap();
+        assert_eq!(JsonWebKey::from(&secret_key), jwk);
+        let public_key = PublicKey::try_from(&jwk).unwrap();
+        assert_eq!(
+            VerifyingKey::as_bytes(&public_key),
+            VerifyingKey::as_bytes(&secret_key.to_verifying_key())
+        );
+
+        let public_jwk = JsonWebKey::from(&public_key);
+        assert_eq!(public_jwk, jwk.to_verifying_key());
+        assert_eq!(
+            jwk.thumbprint::<Sha256>(),
+            public_jwk.thumbprint::<Sha256>()
+        );
+
+        let err = SecretKey::try_from(&public_jwk).map(drop).unwrap_err();
+        assert_matches!(err, JwkError::NoField(field) if field == "d");
+    }
+
+    #[test]
+    fn incorrect_curve() {
+        let mut jwk = reference_jwk();
+        if let JsonWebKey::EllipticCurve { curve, .. } = &mut jwk {
+            *curve = "P-384".into();
+        }
+        let err = PublicKey::try_from(&jwk).map(drop).unwrap_err();
+
+        assert_matches!(
+            err,
+            JwkError::UnexpectedValue { field, expected, actual }
+                if field == "crv" && expected == "P-521" && actual == "P-384"
+        );
+    }
+
+    #[test]
+    fn incorrect_scalar_len() {
+        let mut jwk = reference_jwk();
+        if let JsonWebKey::EllipticCurve { secret, .. } = &mut jwk {
+            let mut bytes = secret.as_deref().unwrap().to_vec();
+            bytes.truncate(65);
+            *secret = Some(SecretBytes::owned(bytes));
+        }
+        let err = SecretKey::try_from(&jwk).map(drop).unwrap_err();
+
+        assert_matches!(
+            err,
+            JwkError::UnexpectedLen {
+                field,
+                expected: 66,
+                actual: 65,
+            } if field == "d"
+        );
+    }
+
+    #[test]
+    fn key_mismatch() {
+        let mut jwk = reference_jwk();
+        if let JsonWebKey::EllipticCurve { secret, .. } = &mut jwk {
+            let mut bytes = [0_u8; 66];
+            bytes[65] = 1;
+            *secret = Some(SecretBytes::owned(bytes.to_vec()));
+        }
+        let err = SecretKey::try_from(&jwk).map(drop).unwrap_err();
+        assert_matches!(err, JwkError::MismatchedKeys);
+    }
+}
+
 #[cfg(any(
     feature = "exonum-crypto",
     feature = "ed25519-dalek",
@@ -2,7 +2,6 @@
 
 use assert_matches::assert_matches;
 use rand::thread_rng;
-use rsa::{pkcs1::DecodeRsaPrivateKey, pkcs8::DecodePublicKey};
 
 mod shared;
 
@@ -51,7 +50,7 @@ MwIDAQAB
 
 #[test]
 fn rs256_algorithm() {
-    let signing_key = RsaPrivateKey::from_pkcs1_pem(RSA_PRIVATE_KEY).unwrap();
+    let signing_key = RsaPrivateKey::from_private_pem(RSA_PRIVATE_KEY).unwrap();
     signing_key.validate().unwrap();
     let verifying_key = signing_key.to_public_key();
     test_algorithm(&Rsa::rs256(), &signing_key, &verifying_key);
@@ -60,7 +59,7 @@ fn rs256_algorithm() {
 #[test]
 fn rs256_algorithm_with_checked_len() {
     let signing_key = {
-        let key = RsaPrivateKey::from_pkcs1_pem(RSA_PRIVATE_KEY).unwrap();
+        let key = RsaPrivateKey::from_private_pem(RSA_PRIVATE_KEY).unwrap();
         key.validate().unwrap();
         StrongKey::try_from(key).unwrap()
     };
@@ -68,6 +67,43 @@ fn rs256_algorithm_with_checked_len() {
     test_algorithm(&StrongAlg(Rsa::rs256()), &signing_key, &verifying_key);
 }
 
+#[test]
+fn key_encoding_roundtrip() {
+    let signing_key = RsaPrivateKey::from_private_pem(RSA_PRIVATE_KEY).unwrap();
+    let pkcs8_der = signing_key.to_private_der(KeyFormat::Pkcs8).unwrap();
+    let restored_key = RsaPrivateKey::from_private_der(KeyFormat::Pkcs8, &pkcs8_der).unwrap();
+    assert_eq!(restored_key, signing_key);
+    let pkcs1_pem = signing_key.to_private_pem(KeyFormat::Pkcs1).unwrap();
+    let pkcs1_pem = core::str::from_utf8(&pkcs1_pem).unwrap();
+    assert_eq!(pkcs1_pem.trim_end(), RSA_PRIVATE_KEY);
+
+    let public_key = RsaPublicKey::from_public_pem(RSA_PUBLIC_KEY).unwrap();
+    assert_eq!(public_key, signing_key.to_public_key());
+    let spki_pem = public_key.to_public_pem(KeyFormat::Spki).unwrap();
+    assert_eq!(spki_pem.trim_end(), RSA_PUBLIC_KEY);
+    let pkcs1_pem = public_key.to_public_pem(KeyFormat::Pkcs1).unwrap();
+    assert!(pkcs1_pem.starts_with("-----BEGIN RSA PUBLIC KEY-----"));
+    assert_eq!(
+        RsaPublicKey::from_public_pem(&pkcs1_pem).unwrap(),
+        public_key
+    );
+
+    let err = RsaPrivateKey::from_private_pem(RSA_PUBLIC_KEY).unwrap_err();
+    assert_matches!(
+        err,
+        KeyEncodingError::UnexpectedPemLabel { key_type: "RSA private key", label }
+            if label == "PUBLIC KEY"
+    );
+    let err = public_key.to_public_der(KeyFormat::Sec1).unwrap_err();
+    assert_matches!(
+        err,
+        KeyEncodingError::UnsupportedFormat {
+            format: KeyFormat::Sec1,
+            ..
+        }
+    );
+}
+
 #[test]
 fn rs256_algorithm_with_generated_keys() {
     //! Since RSA key generation is very slow in the debug mode, we test generated keys
@@ -93,7 +129,7 @@ fn ps256_checked_len_fails_on_undersized_key() {
     assert!(StrongKey::try_from(small_public_key).is_err());
     assert!(StrongKey::try_from(small_private_key).is_err());
 
-    let public_key = RsaPublicKey::from_public_key_pem(RSA_PUBLIC_KEY).unwrap();
+    let public_key = RsaPublicKey::from_public_pem(RSA_PUBLIC_KEY).unwrap();
     let public_key = StrongKey::try_from(public_key).unwrap();
 
     let err = StrongAlg(Rsa::ps256())
@@ -105,7 +141,7 @@ fn ps256_checked_len_fails_on_undersized_key() {
 
 #[test]
 fn rs384_algorithm() {
-    let signing_key = RsaPrivateKey::from_pkcs1_pem(RSA_PRIVATE_KEY).unwrap();
+    let signing_key = RsaPrivateKey::from_private_pem(RSA_PRIVATE_KEY).unwrap();
     signing_key.validate().unwrap();
     let verifying_key = signing_key.to_public_key();
     test_algorithm(&Rsa::rs384(), &signing_key, &verifying_key);
@@ -113,7 +149,7 @@ fn rs384_algorithm() {
 
 #[test]
 fn rs512_algorithm() {
-    let signing_key = RsaPrivateKey::from_pkcs1_pem(RSA_PRIVATE_KEY).unwrap();
+    let signing_key = RsaPrivateKey::from_private_pem(RSA_PRIVATE_KEY).unwrap();
     signing_key.validate().unwrap();
     let verifying_key = signing_key.to_public_key();
     test_algorithm(&Rsa::rs512(), &signing_key, &verifying_key);
@@ -121,7 +157,7 @@ fn rs512_algorithm() {
 
 #[test]
 fn ps256_algorithm() {
-    let signing_key = RsaPrivateKey::from_pkcs1_pem(RSA_PRIVATE_KEY).unwrap();
+    let signing_key = RsaPrivateKey::from_private_pem(RSA_PRIVATE_KEY).unwrap();
     signing_key.validate().unwrap();
     let verifying_key = signing_key.to_public_key();
     test_algorithm(&Rsa::ps256(), &signing_key, &verifying_key);
@@ -129,7 +165,7 @@ fn ps256_algorithm() {
 
 #[test]
 fn ps384_algorithm() {
-    let signing_key = RsaPrivateKey::from_pkcs1_pem(RSA_PRIVATE_KEY).unwrap();
+    let signing_key = RsaPrivateKey::from_private_pem(RSA_PRIVATE_KEY).unwrap();
     signing_key.validate().unwrap();
     let verifying_key = signing_key.to_public_key();
     test_algorithm(&Rsa::ps384(), &signing_key, &verifying_key);
@@ -137,14 +173,14 @@ fn ps384_algorithm() {
 
 #[test]
 fn ps512_algorithm() {
-    let signing_key = RsaPrivateKey::from_pkcs1_pem(RSA_PRIVATE_KEY).unwrap();
+    let signing_key = RsaPrivateKey::from_private_pem(RSA_PRIVATE_KEY).unwrap();
     signing_key.validate().unwrap();
     let verifying_key = signing_key.to_public_key();
     test_algorithm(&Rsa::ps512(), &signing_key, &verifying_key);
 }
 
 fn test_rsa_reference(rsa: Rsa, token: &str) {
-    let public_key = RsaPublicKey::from_public_key_pem(RSA_PUBLIC_KEY).unwrap();
+    let public_key = RsaPublicKey::from_public_pem(RSA_PUBLIC_KEY).unwrap();
     let token = UntrustedToken::new(token).unwrap();
     assert_eq!(token.algorithm(), rsa.name());
 
@@ -156,10 +192,13 @@ fn test_rsa_reference(rsa: Rsa, token: &str) {
         validated_token.claims().issued_at.unwrap().timestamp(),
         1_516_239_022
     );
+    assert_eq!(
+        validated_token.claims().subject.as_deref(),
+        Some("1234567890")
+    );
     assert_eq!(
         validated_token.claims().custom,
         SampleClaims {
-            subject: "1234567890".to_owned(),
             name: "John Doe".to_owned(),
             admin: true
         }
@@ -13,8 +13,6 @@ pub type Obj = serde_json::Map<String, serde_json::Value>;
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct SampleClaims {
-    #[serde(rename = "sub")]
-    pub subject: String,
     pub name: String,
     #[serde(default)]
     pub admin: bool,
@@ -22,18 +20,17 @@ pub struct SampleClaims {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CompactClaims {
-    /// We use a public claim (https://tools.ietf.org/html/rfc7519#section-4.1.2)
-    /// with a custom (de)serializer. This allows to store the `subject` efficiently
-    /// in the CBOR encoding.
-    #[serde(rename = "sub", with = "HexForm")]
-    subject: [u8; 32],
+    /// We use a private claim with a custom (de)serializer. This allows to store
+    /// the `user_id` efficiently in the CBOR encoding.
+    #[serde(rename = "uid", with = "HexForm")]
+    user_id: [u8; 32],
 }
 
 pub fn create_claims() -> Claims<CompactClaims> {
     let now = Utc.with_ymd_and_hms(2020, 9, 1, 10, 0, 0).single().unwrap();
     let now = now - Duration::nanoseconds(i64::from(now.timestamp_subsec_nanos()));
 
-    let mut claims = Claims::new(CompactClaims { subject: [1; 32] });
+    let mut claims = Claims::new(CompactClaims { user_id: [1; 32] });
     claims.issued_at = Some(now);
     claims.expiration = Some(now + Duration::days(7));
     claims
@@ -50,7 +47,7 @@ pub fn test_algorithm<A: Algorithm>(
     let claims = create_claims();
 
     // Successful case with a compact token.
-    #[cfg(feature = "serde_cbor")]
+    #[cfg(feature = "cbor")]
     {
         let token_string = algorithm
             .compact_token(&Header::empty(), &claims, signing_key)